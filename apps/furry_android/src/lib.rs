@@ -9,9 +9,12 @@ use jni::objects::{JClass, JString};
 use jni::sys::{jboolean, jbyteArray, jint, jlong, jstring, JNI_FALSE, JNI_TRUE};
 use jni::JNIEnv;
 
-use furry_converter::{detect_format, pack_to_furry, unpack_from_furry, PackOptions};
+use furry_converter::{
+    detect_format, finish_output, pack_to_furry, unpack_from_furry, ConverterError, PackOptions,
+    PaddingTarget, TagsJsonV1, TAGS_SCHEMA_V1,
+};
 use furry_crypto::MasterKey;
-use furry_format::{FurryReader, MetaKind};
+use furry_format::{FurryHeaderV1, FurryReader};
 
 /// 初始化日志（Android）
 #[cfg(target_os = "android")]
@@ -103,7 +106,9 @@ fn pack_to_furry_impl(
     let master_key = MasterKey::default_key();
 
     let options = PackOptions {
-        padding_bytes: (padding_kb as u64) * 1024,
+        padding: PaddingTarget::Bytes((padding_kb as u64) * 1024),
+        // 应用可能随时被系统杀掉，确保文件落盘后再报告成功
+        sync_on_finish: true,
         ..Default::default()
     };
 
@@ -114,8 +119,10 @@ fn pack_to_furry_impl(
         format,
         &master_key,
         &options,
-    ) {
-        Ok(_) => 0,
+    )
+    .and_then(|()| finish_output(&mut output, &options))
+    {
+        Ok(()) => 0,
         Err(_) => -5,
     }
 }
@@ -284,15 +291,9 @@ fn is_valid_furry_file_impl(env: &mut JNIEnv<'_>, file_path: JString<'_>) -> jbo
         Err(_) => return JNI_FALSE,
     };
 
-    use std::io::Read;
     let mut reader = std::io::BufReader::new(file);
-    let mut magic = [0u8; 8];
-
-    if reader.read_exact(&mut magic).is_err() {
-        return JNI_FALSE;
-    }
 
-    if &magic == b"FURRYFMT" {
+    if FurryHeaderV1::validate_quick(&mut reader).is_ok() {
         JNI_TRUE
     } else {
         JNI_FALSE
@@ -333,26 +334,28 @@ fn get_original_format_impl(env: &mut JNIEnv<'_>, file_path: JString<'_>) -> jst
     };
 
     let path = PathBuf::from(path_str);
-    let file = match File::open(&path) {
+    let mut file = match File::open(&path) {
         Ok(f) => f,
         Err(_) => return to_jstring(env, ""),
     };
 
+    // 头部带格式提示时无需派生密钥、解密索引
+    if let Some(format) = FurryHeaderV1::validate_quick(&mut file)
+        .ok()
+        .and_then(|header| header.original_format_hint())
+    {
+        return to_jstring(env, format.extension());
+    }
+
     let master_key = MasterKey::default_key();
-    let reader = match FurryReader::open(file, &master_key) {
+    let mut reader = match FurryReader::open(file, &master_key) {
         Ok(r) => r,
         Err(_) => return to_jstring(env, ""),
     };
 
-    let ext = match reader.index.header.original_format {
-        furry_format::OriginalFormat::Mp3 => "mp3",
-        furry_format::OriginalFormat::Wav => "wav",
-        furry_format::OriginalFormat::Ogg => "ogg",
-        furry_format::OriginalFormat::Flac => "flac",
-        furry_format::OriginalFormat::Unknown => "",
-    };
+    let ext = reader.original_extension();
 
-    to_jstring(env, ext)
+    to_jstring(env, &ext)
 }
 
 /// JNI: 获取 tags JSON（com.furry_player.NativeLib）
@@ -389,25 +392,20 @@ fn get_tags_json_impl(env: &mut JNIEnv<'_>, file_path: JString<'_>) -> jstring {
     };
     let path = PathBuf::from(path_str);
 
-    let file = match File::open(&path) {
-        Ok(f) => f,
-        Err(_) => return to_jstring(env, ""),
-    };
-
+    // 没有 tags 或无法读取时为空串；无效字节已替换为 U+FFFD，替换后仍无法解析时
+    // 返回只有 schema 的空结构，不把乱码交给应用
     let master_key = MasterKey::default_key();
-    let mut reader = match FurryReader::open(file, &master_key) {
-        Ok(r) => r,
-        Err(_) => return to_jstring(env, ""),
-    };
-
-    let bytes = match reader.read_latest_meta(MetaKind::Tags) {
-        Ok(Some(b)) => b,
-        _ => Vec::new(),
+    let json = match furry_converter::read_tags_json(&path, &master_key) {
+        Ok(Some(json)) => json,
+        Err(ConverterError::InvalidMeta(_)) => TagsJsonV1 {
+            schema: TAGS_SCHEMA_V1.to_string(),
+            ..Default::default()
+        }
+        .to_json(),
+        _ => String::new(),
     };
 
-    let s = String::from_utf8(bytes).unwrap_or_default();
-
-    to_jstring(env, &s)
+    to_jstring(env, &json)
 }
 
 /// JNI: 获取封面字节（payload: mime\\0<bytes>）(com.furry_player.NativeLib)
@@ -437,25 +435,12 @@ fn get_cover_art_impl(env: &mut JNIEnv<'_>, file_path: JString<'_>) -> jbyteArra
     };
     let path = PathBuf::from(path_str);
 
-    let file = match File::open(&path) {
-        Ok(f) => f,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
     let master_key = MasterKey::default_key();
-    let mut reader = match FurryReader::open(file, &master_key) {
-        Ok(r) => r,
-        Err(_) => return std::ptr::null_mut(),
+    let bytes = match furry_converter::read_cover(&path, &master_key) {
+        Ok(Some((mime, image))) => [mime.as_bytes(), &[0], &image].concat(),
+        _ => return std::ptr::null_mut(),
     };
 
-    let bytes = match reader.read_latest_meta(MetaKind::CoverArt) {
-        Ok(Some(b)) => b,
-        _ => Vec::new(),
-    };
-    if bytes.is_empty() {
-        return std::ptr::null_mut();
-    }
-
     let len_i32 = match i32::try_from(bytes.len()) {
         Ok(v) => v,
         Err(_) => return std::ptr::null_mut(),
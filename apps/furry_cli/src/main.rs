@@ -4,23 +4,46 @@
 
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use furry_converter::{detect_format, pack_to_furry, unpack_from_furry, PackOptions};
+use furry_converter::{
+    create_output, detect_format, finish_output, pack_album, pack_and_verify, pack_stream_to_furry,
+    pack_to_furry, space_report, unpack_from_furry, verify_furry, ConverterError, OverwritePolicy,
+    PackOptions, PaddingTarget, TagsJsonV1,
+};
 use furry_crypto::MasterKey;
-use furry_format::FurryReader;
+use furry_format::{ChunkType, FurryReader, OriginalFormat, VerifyOptions};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() < 3 {
         eprintln!("Usage:");
-        eprintln!("  {} pack <input.mp3> <output.furry> [padding_kb]", args[0]);
-        eprintln!("  {} unpack <input.furry> <output.mp3>", args[0]);
+        eprintln!(
+            "  {} pack <input.mp3|-> <output.furry> [padding_kb] [--long-salt] [--index-mac] [--meta-key] [--format <ext>] [--tag-encoding <label>] [--tags-json <file>] [--compress-meta] [--chunk-crc] [--verify] [--force]",
+            args[0]
+        );
+        eprintln!("  {} unpack <input.furry> <output.mp3> [--force]", args[0]);
+        eprintln!(
+            "  {} album <output.furry> <track1> <track2> ... [--force]  # one file, one track table",
+            args[0]
+        );
         eprintln!(
             "  {} info <input.furry>   # prints JSON (valid/original_format)",
             args[0]
         );
+        eprintln!(
+            "  {} verify <input.furry> [--progress] [--audio-only]",
+            args[0]
+        );
+        eprintln!(
+            "  {} digest <input.furry>  # BLAKE3 of decrypted audio (not of the file)",
+            args[0]
+        );
+        eprintln!(
+            "  {} space <dir> [top_n]  # padding/meta/overhead per file, most padding first",
+            args[0]
+        );
         std::process::exit(1);
     }
 
@@ -31,36 +54,130 @@ fn main() {
         "pack" => {
             if args.len() < 4 {
                 eprintln!(
-                    "Usage: {} pack <input> <output.furry> [padding_kb]",
+                    "Usage: {} pack <input|-> <output.furry> [padding_kb] [--long-salt] [--index-mac] [--meta-key] [--format <ext>] [--tag-encoding <label>] [--tags-json <file>] [--compress-meta] [--chunk-crc] [--verify] [--force]",
                     args[0]
                 );
                 std::process::exit(1);
             }
 
-            let input_path = PathBuf::from(&args[2]);
+            let input_arg = &args[2];
             let output_path = PathBuf::from(&args[3]);
-            let padding_kb: u64 = args.get(4).and_then(|s| s.parse().ok()).unwrap_or(0);
-
-            let format = detect_format(&input_path);
-            println!("Detected format: {:?}", format);
+            let mut padding_kb: u64 = 0;
+            let mut long_salt = false;
+            let mut index_mac = false;
+            let mut meta_key = false;
+            let mut compress_meta = false;
+            let mut chunk_crc = false;
+            let mut verify = false;
+            let mut force = false;
+            let mut format_arg: Option<&str> = None;
+            let mut tag_encoding: Option<&str> = None;
+            let mut tags_json: Option<&str> = None;
+            let mut rest = args[4..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--long-salt" => long_salt = true,
+                    "--index-mac" => index_mac = true,
+                    "--meta-key" => meta_key = true,
+                    "--compress-meta" => compress_meta = true,
+                    "--chunk-crc" => chunk_crc = true,
+                    "--verify" => verify = true,
+                    "--force" => force = true,
+                    "--format" => format_arg = rest.next().map(String::as_str),
+                    "--tag-encoding" => tag_encoding = rest.next().map(String::as_str),
+                    "--tags-json" => tags_json = rest.next().map(String::as_str),
+                    other => padding_kb = other.parse().unwrap_or(padding_kb),
+                }
+            }
 
-            let mut input = File::open(&input_path).expect("Failed to open input file");
-            let mut output = File::create(&output_path).expect("Failed to create output file");
+            // sidecar 标签：覆盖源文件中提取的同名字段
+            let tags_override = tags_json.map(|path| {
+                let data = std::fs::read(path).expect("Failed to read tags JSON");
+                TagsJsonV1::from_json(&data).unwrap_or_else(|e| {
+                    eprintln!("{}: {}", path, e);
+                    std::process::exit(1);
+                })
+            });
 
             let options = PackOptions {
-                padding_bytes: padding_kb * 1024,
+                padding: PaddingTarget::Bytes(padding_kb * 1024),
+                format_hint: format_arg.map(str::to_string),
+                tag_encoding: tag_encoding.map(str::to_string),
+                tags_override,
+                compress_meta,
+                chunk_crc,
+                long_salt,
+                index_mac,
+                meta_key,
+                overwrite: overwrite_policy(force),
+                sync_on_finish: true,
                 ..Default::default()
             };
+            let (mut output, output_path) = open_output(&output_path, options.overwrite);
+
+            // `-` 表示从 stdin 读取：流式封装，不提取 META，格式需由 --format 指定
+            if input_arg == "-" {
+                let Some(ext) = format_arg else {
+                    eprintln!("Reading from stdin requires --format <ext>");
+                    std::process::exit(1);
+                };
+                if verify {
+                    eprintln!("--verify needs a seekable input file, not stdin");
+                    std::process::exit(1);
+                }
+                let format = OriginalFormat::from_extension(ext);
+                println!("Format: {:?}", format);
+
+                pack_stream_to_furry(
+                    &mut std::io::stdin().lock(),
+                    &mut output,
+                    format,
+                    &master_key,
+                    &options,
+                )
+                .expect("Failed to pack");
+                finish_output(&mut output, &options).expect("Failed to sync output file");
 
-            pack_to_furry(
-                &mut input,
-                &mut output,
-                Some(&input_path),
-                format,
-                &master_key,
-                &options,
-            )
-            .expect("Failed to pack");
+                let output_size = std::fs::metadata(&output_path).unwrap().len();
+                println!("Packed successfully!");
+                println!("  Input:  stdin");
+                println!("  Output: {} bytes", output_size);
+                return;
+            }
+
+            let input_path = PathBuf::from(input_arg);
+            let format = match format_arg {
+                Some(ext) => OriginalFormat::from_extension(ext),
+                None => detect_format(&input_path),
+            };
+            println!("Detected format: {:?}", format);
+
+            let mut input = File::open(&input_path).expect("Failed to open input file");
+
+            if verify {
+                let report = pack_and_verify(
+                    &mut input,
+                    &mut output,
+                    Some(&input_path),
+                    format,
+                    &master_key,
+                    &options,
+                    true,
+                )
+                .expect("Failed to pack and verify");
+                println!("Verified {} chunks", report.checked_chunks);
+            } else {
+                pack_to_furry(
+                    &mut input,
+                    &mut output,
+                    Some(&input_path),
+                    format,
+                    &master_key,
+                    &options,
+                )
+                .expect("Failed to pack");
+            }
+            finish_output(&mut output, &options).expect("Failed to sync output file");
 
             let input_size = std::fs::metadata(&input_path).unwrap().len();
             let output_size = std::fs::metadata(&output_path).unwrap().len();
@@ -72,21 +189,63 @@ fn main() {
         }
         "unpack" => {
             if args.len() < 4 {
-                eprintln!("Usage: {} unpack <input.furry> <output>", args[0]);
+                eprintln!("Usage: {} unpack <input.furry> <output> [--force]", args[0]);
                 std::process::exit(1);
             }
 
             let input_path = PathBuf::from(&args[2]);
-            let output_path = PathBuf::from(&args[3]);
+            let mut output_path = PathBuf::from(&args[3]);
 
             let mut input = File::open(&input_path).expect("Failed to open input file");
-            let mut output = File::create(&output_path).expect("Failed to create output file");
+
+            // 输出路径未带扩展名时补上原始扩展名
+            if output_path.extension().is_none() {
+                let mut reader =
+                    FurryReader::open(&mut input, &master_key).expect("Failed to parse");
+                let ext = reader.original_extension();
+                if !ext.is_empty() {
+                    output_path.set_extension(ext);
+                }
+            }
+
+            let force = args[4..].iter().any(|arg| arg == "--force");
+            let (mut output, output_path) = open_output(&output_path, overwrite_policy(force));
 
             let format =
                 unpack_from_furry(&mut input, &mut output, &master_key).expect("Failed to unpack");
 
             println!("Unpacked successfully!");
             println!("  Original format: {:?}", format);
+            println!("  Output: {}", output_path.display());
+        }
+        "album" => {
+            let force = args.iter().any(|arg| arg == "--force");
+            let inputs: Vec<PathBuf> = args[3..]
+                .iter()
+                .filter(|arg| *arg != "--force")
+                .map(PathBuf::from)
+                .collect();
+            if inputs.is_empty() {
+                eprintln!(
+                    "Usage: {} album <output.furry> <track1> <track2> ... [--force]",
+                    args[0]
+                );
+                std::process::exit(1);
+            }
+            let (mut output, output_path) =
+                open_output(Path::new(&args[2]), overwrite_policy(force));
+
+            let table = pack_album(&inputs, &mut output, &master_key, &PackOptions::default())
+                .expect("Failed to pack album");
+
+            println!("Packed {} tracks!", table.tracks.len());
+            for track in &table.tracks {
+                println!(
+                    "  #{} {} ({} bytes)",
+                    track.index, track.title, track.virtual_len
+                );
+            }
+            println!("  Output: {}", output_path.display());
         }
         "info" => {
             let input_path = PathBuf::from(&args[2]);
@@ -105,7 +264,7 @@ fn main() {
                 std::process::exit(3);
             }
 
-            let reader = match FurryReader::open(file, &master_key) {
+            let mut reader = match FurryReader::open(file, &master_key) {
                 Ok(r) => r,
                 Err(_) => {
                     println!(r#"{{"valid":false,"error":"parse_failed"}}"#);
@@ -113,15 +272,113 @@ fn main() {
                 }
             };
 
-            let ext = match reader.index.header.original_format {
-                furry_format::OriginalFormat::Mp3 => "mp3",
-                furry_format::OriginalFormat::Wav => "wav",
-                furry_format::OriginalFormat::Ogg => "ogg",
-                furry_format::OriginalFormat::Flac => "flac",
-                furry_format::OriginalFormat::Unknown => "",
+            let ext = reader.original_extension();
+
+            // 按类型拆分磁盘占用，便于定位超大封面等膨胀来源
+            let by_type = reader.index.size_by_chunk_type();
+            let type_bytes = |t: ChunkType| by_type.get(&t).copied().unwrap_or(0);
+            let mut meta: Vec<_> = reader.index.meta_size_by_kind().into_iter().collect();
+            meta.sort_by_key(|(kind, _)| kind.id());
+            let meta_json = meta
+                .iter()
+                .map(|(kind, bytes)| format!(r#""{}":{}"#, kind, bytes))
+                .collect::<Vec<_>>()
+                .join(",");
+            let file_size = std::fs::metadata(&input_path).map(|m| m.len()).unwrap_or(0);
+            let chunk_total: u64 = by_type.values().sum();
+
+            println!(
+                r#"{{"valid":true,"original_format":"{}","file_bytes":{},"audio_bytes":{},"padding_bytes":{},"meta_bytes":{{{}}},"overhead_bytes":{},"kdf":"{:?}","aead":"{:?}","flags":{}}}"#,
+                ext,
+                file_size,
+                type_bytes(ChunkType::Audio),
+                type_bytes(ChunkType::Padding),
+                meta_json,
+                file_size.saturating_sub(chunk_total),
+                reader.kdf(),
+                reader.aead(),
+                reader.flags().bits(),
+            );
+        }
+        "verify" => {
+            let input_path = PathBuf::from(&args[2]);
+            let show_progress = args[3..].iter().any(|a| a == "--progress");
+            let options = VerifyOptions {
+                audio_only: args[3..].iter().any(|a| a == "--audio-only"),
+                ..Default::default()
             };
 
-            println!(r#"{{"valid":true,"original_format":"{}"}}"#, ext);
+            let mut input = File::open(&input_path).expect("Failed to open input file");
+            let report = verify_furry(&mut input, &master_key, &options, |done, total| {
+                if show_progress {
+                    eprint!("\rVerifying chunk {}/{}", done, total);
+                }
+                true
+            })
+            .expect("Failed to verify");
+            if show_progress {
+                eprintln!();
+            }
+
+            println!("Checked {} chunks", report.checked_chunks);
+            let census = &report.census;
+            if !census.is_empty() {
+                println!(
+                    "warning: {:.1} MB of padding in {} chunks, {} unknown chunk(s)",
+                    census.padding_bytes as f64 / (1024.0 * 1024.0),
+                    census.padding_chunks,
+                    census.unknown_chunks.len()
+                );
+                if !census.unknown_chunks.is_empty() {
+                    println!("Unknown chunks (chunk_seq): {:?}", census.unknown_chunks);
+                }
+            }
+            if report.is_ok() {
+                println!("OK");
+            } else {
+                println!("Failed chunks (chunk_seq): {:?}", report.failed_chunks);
+                for (seq, fault) in report.failed_chunks.iter().zip(&report.faults) {
+                    println!("  chunk {}: {:?}", seq, fault);
+                }
+                std::process::exit(2);
+            }
+        }
+        "digest" => {
+            let input_path = PathBuf::from(&args[2]);
+            let input = File::open(&input_path).expect("Failed to open input file");
+            let mut reader = FurryReader::open(input, &master_key).expect("Failed to parse");
+            let digest = reader
+                .audio_plaintext_digest()
+                .expect("Failed to decrypt audio");
+            let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            println!("{}", hex);
+        }
+        "space" => {
+            let dir = PathBuf::from(&args[2]);
+            let top: usize = args.get(3).and_then(|n| n.parse().ok()).unwrap_or(20);
+            let report = space_report(&dir, &master_key).expect("Failed to scan library");
+
+            println!(
+                "{} files, {} bytes total: audio {}, padding {}, meta {}, overhead {}",
+                report.files.len(),
+                report.total_file_bytes,
+                report.total_audio_bytes,
+                report.total_padding_bytes,
+                report.total_meta_bytes,
+                report.total_overhead_bytes,
+            );
+            println!("Most padding-heavy files:");
+            for usage in report.files.iter().take(top) {
+                match &usage.error {
+                    Some(err) => println!("  {}  error: {}", usage.path.display(), err),
+                    None => println!(
+                        "  {:>12} padding / {:>12} bytes  {}",
+                        usage.padding_bytes,
+                        usage.file_bytes,
+                        usage.path.display()
+                    ),
+                }
+            }
         }
         _ => {
             eprintln!("Unknown command: {}", command);
@@ -129,3 +386,26 @@ fn main() {
         }
     }
 }
+
+/// `--force` 时覆盖已存在的输出，否则拒绝
+fn overwrite_policy(force: bool) -> OverwritePolicy {
+    if force {
+        OverwritePolicy::Overwrite
+    } else {
+        OverwritePolicy::Fail
+    }
+}
+
+fn open_output(path: &Path, policy: OverwritePolicy) -> (File, PathBuf) {
+    match create_output(path, policy) {
+        Ok(opened) => opened,
+        Err(ConverterError::OutputExists(path)) => {
+            eprintln!(
+                "Output already exists: {} (use --force to overwrite)",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        Err(e) => panic!("Failed to create output file: {}", e),
+    }
+}
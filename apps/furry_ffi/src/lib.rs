@@ -2,13 +2,15 @@
 
 use std::ffi::{CStr, CString};
 use std::fs::File;
-use std::io::Read;
 use std::os::raw::{c_char, c_int, c_uchar};
 use std::path::PathBuf;
 
-use furry_converter::{detect_format, pack_to_furry, unpack_from_furry, PackOptions};
+use furry_converter::{
+    detect_format, pack_to_furry, read_tags_json, unpack_from_furry, ConverterError, PackOptions,
+    PaddingTarget,
+};
 use furry_crypto::MasterKey;
-use furry_format::{FurryReader, MetaKind};
+use furry_format::{FurryHeaderV1, FurryReader, MetaKind};
 
 fn cstr_to_path(ptr: *const c_char) -> Result<PathBuf, c_int> {
     if ptr.is_null() {
@@ -48,7 +50,7 @@ pub extern "C" fn furry_pack_to_furry(
     let format = detect_format(&input_path);
     let master_key = MasterKey::default_key();
     let options = PackOptions {
-        padding_bytes: padding_kb * 1024,
+        padding: PaddingTarget::Bytes(padding_kb * 1024),
         ..Default::default()
     };
 
@@ -78,23 +80,20 @@ pub extern "C" fn furry_is_valid_furry_file(file_path: *const c_char) -> bool {
     };
 
     let mut reader = std::io::BufReader::new(file);
-    let mut magic = [0u8; 8];
-    if reader.read_exact(&mut magic).is_err() {
-        return false;
-    }
-    &magic == b"FURRYFMT"
+    FurryHeaderV1::validate_quick(&mut reader).is_ok()
 }
 
-fn original_ext(path: &PathBuf, master_key: &MasterKey) -> Result<&'static str, ()> {
-    let file = File::open(path).map_err(|_| ())?;
-    let reader = FurryReader::open(file, master_key).map_err(|_| ())?;
-    Ok(match reader.index.header.original_format {
-        furry_format::OriginalFormat::Mp3 => "mp3",
-        furry_format::OriginalFormat::Wav => "wav",
-        furry_format::OriginalFormat::Ogg => "ogg",
-        furry_format::OriginalFormat::Flac => "flac",
-        furry_format::OriginalFormat::Unknown => "",
-    })
+fn original_ext(path: &PathBuf, master_key: &MasterKey) -> Result<String, ()> {
+    let mut file = File::open(path).map_err(|_| ())?;
+    // 头部带格式提示时无需派生密钥、解密索引
+    if let Some(format) = FurryHeaderV1::validate_quick(&mut file)
+        .ok()
+        .and_then(|header| header.original_format_hint())
+    {
+        return Ok(format.extension().to_string());
+    }
+    let mut reader = FurryReader::open(file, master_key).map_err(|_| ())?;
+    Ok(reader.original_extension())
 }
 
 /// Writes original format extension (without dot) into `out_buf` (NUL-terminated).
@@ -223,7 +222,9 @@ pub unsafe extern "C" fn furry_unpack_from_furry_to_file(
     }
 }
 
-/// Returns embedded tags JSON (UTF-8) from `.furry` META chunk.
+/// Returns embedded tags JSON (UTF-8) from `.furry` META chunk, validated against the
+/// `furry.tags.v1` schema and passed through verbatim (invalid UTF-8 is replaced, unknown
+/// fields are kept). No tags yields 0 with an empty buffer; unparseable tags yield -34.
 /// On success returns 0 and sets `*out_ptr`/`*out_len`. Caller must call `furry_free_bytes`.
 ///
 /// # Safety
@@ -244,20 +245,13 @@ pub unsafe extern "C" fn furry_get_tags_json_to_bytes(
         Err(e) => return e,
     };
 
-    let file = match File::open(&input_path) {
-        Ok(f) => f,
-        Err(_) => return -31,
-    };
-
     let master_key = MasterKey::default_key();
-    let mut reader = match FurryReader::open(file, &master_key) {
-        Ok(r) => r,
-        Err(_) => return -32,
-    };
-
-    let bytes = match reader.read_latest_meta(MetaKind::Tags) {
-        Ok(Some(b)) => b,
+    let bytes = match read_tags_json(&input_path, &master_key) {
+        Ok(Some(json)) => json.into_bytes(),
         Ok(None) => Vec::new(),
+        Err(ConverterError::Io(_)) => return -31,
+        Err(ConverterError::Format(_)) => return -32,
+        Err(ConverterError::InvalidMeta(_)) => return -34,
         Err(_) => return -33,
     };
 
@@ -274,7 +268,7 @@ pub unsafe extern "C" fn furry_get_tags_json_to_bytes(
 }
 
 /// Returns embedded cover art payload bytes from `.furry` META chunk.
-/// Payload format: `mime\\0<image-bytes>`.
+/// Payload format: `mime\\0<image-bytes>` (normalized; `mime` is empty if unknown).
 /// On success returns 0 and sets `*out_ptr`/`*out_len`. Caller must call `furry_free_bytes`.
 ///
 /// # Safety
@@ -307,7 +301,7 @@ pub unsafe extern "C" fn furry_get_cover_art_to_bytes(
     };
 
     let bytes = match reader.read_latest_meta(MetaKind::CoverArt) {
-        Ok(Some(b)) => b,
+        Ok(Some(b)) => furry_format::normalize_cover_payload(&b),
         Ok(None) => Vec::new(),
         Err(_) => return -43,
     };
@@ -5,11 +5,14 @@ mod ui;
 
 use eframe::egui;
 use furry_crypto::MasterKey;
-use furry_player::spawn_player;
+use furry_player::{spawn_player, PlayerController, PlayerEvent};
 
 use state::AppState;
 use ui::{ConverterWindow, FurryTheme, LibrarySidebar, PlayerDeck};
 
+/// 播放时的重绘间隔（与引擎进度事件间隔保持一致）
+const REPAINT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
 fn main() -> eframe::Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -29,8 +32,10 @@ fn main() -> eframe::Result<()> {
             // 启动播放引擎
             let master_key = MasterKey::default_key();
             let handle = spawn_player(master_key);
+            let controller = handle.controller();
+            controller.set_position_update_interval(REPAINT_INTERVAL);
 
-            Ok(Box::new(FurryApp::new(handle.cmd_tx, handle.evt_rx)))
+            Ok(Box::new(FurryApp::new(controller, handle.evt_rx)))
         }),
     )
 }
@@ -40,12 +45,9 @@ struct FurryApp {
 }
 
 impl FurryApp {
-    fn new(
-        cmd_tx: crossbeam_channel::Sender<furry_player::PlayerCommand>,
-        evt_rx: crossbeam_channel::Receiver<furry_player::PlayerEvent>,
-    ) -> Self {
+    fn new(controller: PlayerController, evt_rx: crossbeam_channel::Receiver<PlayerEvent>) -> Self {
         Self {
-            state: AppState::new(cmd_tx, evt_rx),
+            state: AppState::new(controller, evt_rx),
         }
     }
 }
@@ -97,7 +99,7 @@ impl eframe::App for FurryApp {
 
         // 如果正在播放，请求重绘以更新进度
         if self.state.is_playing {
-            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            ctx.request_repaint_after(REPAINT_INTERVAL);
         }
     }
 }
@@ -1,12 +1,17 @@
 //! 应用状态
 
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 
 use crossbeam_channel::{Receiver, Sender};
-use furry_converter::{detect_format, pack_to_furry, unpack_from_furry, PackOptions};
+use furry_converter::{
+    create_output, detect_format, finish_output, pack_and_verify, pack_to_furry,
+    unpack_from_furry_with_progress, OverwritePolicy, PackOptions, PaddingTarget,
+};
 use furry_crypto::MasterKey;
-use furry_player::{PlayerCommand, PlayerEvent};
+use furry_player::{PlayerController, PlayerEvent};
 
 /// 曲目信息
 #[derive(Debug, Clone)]
@@ -23,6 +28,10 @@ pub struct AppState {
     pub is_playing: bool,
     pub position: f64,
     pub duration: f64,
+    /// 当前曲目能否拖动进度条
+    pub seekable: bool,
+    /// 当前曲目的格式质量描述（如 `16-bit · 44.1 kHz · 1411 kbps`）
+    pub track_quality: Option<String>,
     pub volume: f32,
 
     // 播放列表
@@ -39,6 +48,8 @@ pub struct AppState {
     pub pack_input_path: Option<PathBuf>,
     pub pack_output_path: Option<PathBuf>,
     pub pack_padding_kb: u64,
+    /// 打包后重新读取输出校验，并比对音频摘要
+    pub pack_verify: bool,
     pub unpack_input_path: Option<PathBuf>,
     pub unpack_output_path: Option<PathBuf>,
     pub converter_running: bool,
@@ -46,12 +57,13 @@ pub struct AppState {
     pub converter_last_ok: bool,
 
     // 播放引擎通信
-    cmd_tx: Option<Sender<PlayerCommand>>,
+    controller: Option<PlayerController>,
     evt_rx: Option<Receiver<PlayerEvent>>,
 
     // 转换器任务通信
     converter_evt_tx: Sender<ConverterEvent>,
     converter_evt_rx: Receiver<ConverterEvent>,
+    converter_cancel: Option<Arc<AtomicBool>>,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -63,6 +75,7 @@ pub enum ConverterTab {
 
 #[derive(Debug, Clone)]
 enum ConverterEvent {
+    Progress { done: u64, total: u64 },
     Finished { ok: bool, message: String },
 }
 
@@ -73,6 +86,8 @@ impl Default for AppState {
             is_playing: false,
             position: 0.0,
             duration: 0.0,
+            seekable: false,
+            track_quality: None,
             volume: 0.8,
             playlist: Vec::new(),
             current_index: None,
@@ -83,23 +98,25 @@ impl Default for AppState {
             pack_input_path: None,
             pack_output_path: None,
             pack_padding_kb: 0,
+            pack_verify: true,
             unpack_input_path: None,
             unpack_output_path: None,
             converter_running: false,
             converter_last_message: None,
             converter_last_ok: true,
-            cmd_tx: None,
+            controller: None,
             evt_rx: None,
             converter_evt_tx,
             converter_evt_rx,
+            converter_cancel: None,
         }
     }
 }
 
 impl AppState {
-    pub fn new(cmd_tx: Sender<PlayerCommand>, evt_rx: Receiver<PlayerEvent>) -> Self {
+    pub fn new(controller: PlayerController, evt_rx: Receiver<PlayerEvent>) -> Self {
         Self {
-            cmd_tx: Some(cmd_tx),
+            controller: Some(controller),
             evt_rx: Some(evt_rx),
             ..Default::default()
         }
@@ -114,8 +131,6 @@ impl AppState {
             Vec::new()
         };
 
-        let mut should_next = false;
-
         for event in events {
             match event {
                 PlayerEvent::StateChanged(state) => {
@@ -127,8 +142,33 @@ impl AppState {
                 PlayerEvent::Duration(dur) => {
                     self.duration = dur.as_secs_f64();
                 }
-                PlayerEvent::TrackEnded => {
-                    should_next = true;
+                PlayerEvent::TrackInfo(info) => {
+                    if let Some(warning) = &info.format_warning {
+                        eprintln!("Warning: {}: {}", info.path.display(), warning);
+                    }
+                    self.seekable = info.seekable;
+                    self.track_quality = Some(quality_label(&info));
+                }
+                PlayerEvent::VolumeChanged(volume) => {
+                    self.volume = volume;
+                }
+                PlayerEvent::StateSnapshot {
+                    state,
+                    track,
+                    position,
+                    duration,
+                    volume,
+                } => {
+                    self.is_playing = state == furry_player::PlaybackState::Playing;
+                    self.position = position.as_secs_f64();
+                    self.duration = duration.as_secs_f64();
+                    self.volume = volume;
+                    self.seekable = track.as_ref().is_some_and(|info| info.seekable);
+                    self.track_quality = track.as_ref().map(quality_label);
+                }
+                PlayerEvent::TrackChanged { index, .. } => {
+                    self.current_index = Some(index);
+                    self.current_track = self.playlist.get(index).cloned();
                 }
                 PlayerEvent::Error(e) => {
                     eprintln!("Player error: {}", e);
@@ -136,10 +176,6 @@ impl AppState {
                 _ => {}
             }
         }
-
-        if should_next {
-            self.next_track();
-        }
     }
 
     /// 处理转换器后台任务事件
@@ -147,8 +183,23 @@ impl AppState {
         let events: Vec<_> = self.converter_evt_rx.try_iter().collect();
         for event in events {
             match event {
+                ConverterEvent::Progress { done, total } => {
+                    // 取消请求后保留"正在取消..."提示
+                    let cancelling = self
+                        .converter_cancel
+                        .as_ref()
+                        .is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+                    if self.converter_running && !cancelling {
+                        let percent = done as f64 * 100.0 / total.max(1) as f64;
+                        self.converter_last_message = Some(format!(
+                            "正在解包... {:.0}% ({}/{} bytes)",
+                            percent, done, total
+                        ));
+                    }
+                }
                 ConverterEvent::Finished { ok, message } => {
                     self.converter_running = false;
+                    self.converter_cancel = None;
                     self.converter_last_ok = ok;
                     self.converter_last_message = Some(message);
                 }
@@ -156,57 +207,64 @@ impl AppState {
         }
     }
 
-    /// 发送命令到播放引擎
-    fn send_command(&self, cmd: PlayerCommand) {
-        if let Some(tx) = &self.cmd_tx {
-            let _ = tx.send(cmd);
-        }
-    }
-
     pub fn toggle_play(&mut self) {
-        if self.is_playing {
-            self.send_command(PlayerCommand::Pause);
-        } else {
-            self.send_command(PlayerCommand::Play);
+        if let Some(controller) = &self.controller {
+            if self.is_playing {
+                controller.pause();
+            } else {
+                controller.play();
+            }
         }
     }
 
+    /// 播放列表中的第 `index` 首；当前曲目以引擎的 `TrackChanged` 为准
     pub fn play_track(&mut self, index: usize) {
-        if let Some(track) = self.playlist.get(index) {
-            self.current_index = Some(index);
-            self.current_track = Some(track.clone());
-            self.send_command(PlayerCommand::Load(track.path.clone()));
-            self.send_command(PlayerCommand::Play);
+        if let Some(controller) = &self.controller {
+            controller.play_index(index);
         }
     }
 
     pub fn next_track(&mut self) {
-        if let Some(idx) = self.current_index {
-            let next = (idx + 1) % self.playlist.len().max(1);
-            if next < self.playlist.len() {
-                self.play_track(next);
-            }
+        if let Some(controller) = &self.controller {
+            controller.next();
         }
     }
 
     pub fn previous_track(&mut self) {
-        if let Some(idx) = self.current_index {
-            let prev = if idx == 0 {
-                self.playlist.len().saturating_sub(1)
-            } else {
-                idx - 1
-            };
-            if prev < self.playlist.len() {
-                self.play_track(prev);
-            }
+        if let Some(controller) = &self.controller {
+            controller.previous();
+        }
+    }
+
+    /// 按进度条比例跳转，由引擎按曲目时长换算
+    pub fn seek_fraction(&mut self, fraction: f64) {
+        self.position = fraction.clamp(0.0, 1.0) * self.duration;
+        if let Some(controller) = &self.controller {
+            controller.seek_fraction(fraction);
         }
     }
 
-    pub fn seek(&mut self, position: f64) {
-        self.position = position;
-        self.send_command(PlayerCommand::Seek(std::time::Duration::from_secs_f64(
-            position,
-        )));
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if let Some(controller) = &self.controller {
+            controller.set_volume(volume);
+        }
+    }
+
+    /// 直接试听打包输入的源文件
+    pub fn preview_pack_input(&mut self) {
+        if let (Some(controller), Some(path)) = (&self.controller, &self.pack_input_path) {
+            controller.load_raw(path.clone());
+            controller.play();
+        }
+    }
+
+    /// 试听打包输出的 .furry 文件（用于与源文件对比）
+    pub fn preview_pack_output(&mut self) {
+        if let (Some(controller), Some(path)) = (&self.controller, &self.pack_output_path) {
+            controller.load(path.clone());
+            controller.play();
+        }
     }
 
     pub fn open_file_dialog(&mut self) {
@@ -234,6 +292,9 @@ impl AppState {
             artist: "Unknown Artist".to_string(),
             duration_str: "--:--".to_string(),
         });
+        if let Some(controller) = &self.controller {
+            controller.set_queue(self.playlist.iter().map(|t| t.path.clone()).collect());
+        }
     }
 
     pub fn pick_pack_input(&mut self) {
@@ -274,6 +335,14 @@ impl AppState {
         }
     }
 
+    /// 请求取消正在进行的打包/解包
+    pub fn cancel_converter(&mut self) {
+        if let Some(cancel) = &self.converter_cancel {
+            cancel.store(true, Ordering::Relaxed);
+            self.converter_last_message = Some("正在取消...".to_string());
+        }
+    }
+
     pub fn start_pack(&mut self) {
         if self.converter_running {
             return;
@@ -290,8 +359,14 @@ impl AppState {
             return;
         };
 
+        let Some(overwrite) = confirm_overwrite(&output_path) else {
+            return;
+        };
         let padding_kb = self.pack_padding_kb;
+        let verify = self.pack_verify;
         let tx = self.converter_evt_tx.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.converter_cancel = Some(cancel.clone());
 
         self.converter_running = true;
         self.converter_last_ok = true;
@@ -307,21 +382,39 @@ impl AppState {
                 let format = detect_format(&input_path);
                 let master_key = MasterKey::default_key();
                 let options = PackOptions {
-                    padding_bytes: padding_kb * 1024,
+                    padding: PaddingTarget::Bytes(padding_kb * 1024),
+                    overwrite,
+                    cancel: Some(cancel.clone()),
+                    sync_on_finish: true,
                     ..Default::default()
                 };
 
                 let mut input = std::fs::File::open(&input_path).map_err(|e| e.to_string())?;
-                let mut output = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
-                pack_to_furry(
-                    &mut input,
-                    &mut output,
-                    Some(&input_path),
-                    format,
-                    &master_key,
-                    &options,
-                )
-                .map_err(|e| e.to_string())?;
+                let (mut output, _) =
+                    create_output(&output_path, options.overwrite).map_err(|e| e.to_string())?;
+                if verify {
+                    pack_and_verify(
+                        &mut input,
+                        &mut output,
+                        Some(&input_path),
+                        format,
+                        &master_key,
+                        &options,
+                        true,
+                    )
+                    .map_err(|e| e.to_string())?;
+                } else {
+                    pack_to_furry(
+                        &mut input,
+                        &mut output,
+                        Some(&input_path),
+                        format,
+                        &master_key,
+                        &options,
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+                finish_output(&mut output, &options).map_err(|e| e.to_string())?;
 
                 let input_size = std::fs::metadata(&input_path)
                     .map(|m| m.len())
@@ -331,7 +424,8 @@ impl AppState {
                     .map_err(|e| e.to_string())?;
 
                 Ok(format!(
-                    "打包完成：\n- 格式: {:?}\n- 输入: {} bytes\n- 输出: {} bytes\n- 比例: {:.2}x\n- 耗时: {:?}\n- 输出文件: {}",
+                    "打包完成{}：\n- 格式: {:?}\n- 输入: {} bytes\n- 输出: {} bytes\n- 比例: {:.2}x\n- 耗时: {:?}\n- 输出文件: {}",
+                    if verify { "（已校验）" } else { "" },
                     format,
                     input_size,
                     output_size,
@@ -343,6 +437,13 @@ impl AppState {
 
             let _ = match result {
                 Ok(message) => tx.send(ConverterEvent::Finished { ok: true, message }),
+                Err(_) if cancel.load(Ordering::Relaxed) => {
+                    let _ = std::fs::remove_file(&output_path);
+                    tx.send(ConverterEvent::Finished {
+                        ok: false,
+                        message: "打包已取消，未完成的输出已删除".to_string(),
+                    })
+                }
                 Err(err) => tx.send(ConverterEvent::Finished {
                     ok: false,
                     message: format!("打包失败：{}", err),
@@ -366,8 +467,13 @@ impl AppState {
             self.converter_last_message = Some("请选择输出文件路径".to_string());
             return;
         };
+        let Some(overwrite) = confirm_overwrite(&output_path) else {
+            return;
+        };
 
         let tx = self.converter_evt_tx.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.converter_cancel = Some(cancel.clone());
 
         self.converter_running = true;
         self.converter_last_ok = true;
@@ -383,9 +489,19 @@ impl AppState {
                 let master_key = MasterKey::default_key();
 
                 let mut input = std::fs::File::open(&input_path).map_err(|e| e.to_string())?;
-                let mut output = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
-                let format = unpack_from_furry(&mut input, &mut output, &master_key)
-                    .map_err(|e| e.to_string())?;
+                let (mut output, _) =
+                    create_output(&output_path, overwrite).map_err(|e| e.to_string())?;
+                let format = unpack_from_furry_with_progress(
+                    &mut input,
+                    &mut output,
+                    &master_key,
+                    Some(&cancel),
+                    // 通道满时丢弃中间进度，避免阻塞解包
+                    |done, total| {
+                        let _ = tx.try_send(ConverterEvent::Progress { done, total });
+                    },
+                )
+                .map_err(|e| e.to_string())?;
 
                 let output_size = std::fs::metadata(&output_path)
                     .map(|m| m.len())
@@ -402,6 +518,13 @@ impl AppState {
 
             let _ = match result {
                 Ok(message) => tx.send(ConverterEvent::Finished { ok: true, message }),
+                Err(_) if cancel.load(Ordering::Relaxed) => {
+                    let _ = std::fs::remove_file(&output_path);
+                    tx.send(ConverterEvent::Finished {
+                        ok: false,
+                        message: "解包已取消，未完成的输出已删除".to_string(),
+                    })
+                }
                 Err(err) => tx.send(ConverterEvent::Finished {
                     ok: false,
                     message: format!("解包失败：{}", err),
@@ -410,3 +533,29 @@ impl AppState {
         });
     }
 }
+
+/// 输出已存在时弹窗确认；取消返回 `None`
+fn confirm_overwrite(path: &std::path::Path) -> Option<OverwritePolicy> {
+    if !path.exists() {
+        return Some(OverwritePolicy::Fail);
+    }
+    let confirmed = rfd::MessageDialog::new()
+        .set_title("文件已存在")
+        .set_description(format!("{} 已存在，是否覆盖？", path.display()))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    matches!(confirmed, rfd::MessageDialogResult::Yes).then_some(OverwritePolicy::Overwrite)
+}
+
+/// 位深/采样率/码率，未知项省略
+fn quality_label(info: &furry_player::TrackInfo) -> String {
+    let mut parts = Vec::new();
+    if let Some(bits) = info.bits_per_sample {
+        parts.push(format!("{}-bit", bits));
+    }
+    parts.push(format!("{:.1} kHz", info.sample_rate as f64 / 1000.0));
+    if let Some(rate) = info.bit_rate {
+        parts.push(format!("{} kbps", rate / 1000));
+    }
+    parts.join(" · ")
+}
@@ -23,6 +23,9 @@ impl ConverterWindow {
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if state.converter_running {
+                            if ui.button("Cancel").clicked() {
+                                state.cancel_converter();
+                            }
                             ui.add(egui::Spinner::new());
                         }
                     });
@@ -92,6 +95,37 @@ impl ConverterWindow {
             );
         });
 
+        ui.checkbox(
+            &mut state.pack_verify,
+            RichText::new("Verify after packing (reads the output and source again)")
+                .color(FurryTheme::TEXT_MUTED),
+        );
+
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            let has_input = state.pack_input_path.is_some();
+            let has_output = state
+                .pack_output_path
+                .as_deref()
+                .is_some_and(|p| p.is_file());
+            if ui
+                .add_enabled(has_input, egui::Button::new("Preview source"))
+                .clicked()
+            {
+                state.preview_pack_input();
+            }
+            if ui
+                .add_enabled(
+                    has_output && !state.converter_running,
+                    egui::Button::new("Preview .furry"),
+                )
+                .clicked()
+            {
+                state.preview_pack_output();
+            }
+        });
+
         ui.add_space(12.0);
 
         let can_start = !state.converter_running
@@ -72,6 +72,13 @@ impl PlayerDeck {
                         .color(FurryTheme::TEXT_MUTED)
                         .size(12.0),
                 );
+                if let Some(quality) = &state.track_quality {
+                    ui.label(
+                        RichText::new(quality)
+                            .color(FurryTheme::TEXT_MUTED)
+                            .size(10.0),
+                    );
+                }
             } else {
                 ui.label(
                     RichText::new("No track loaded")
@@ -130,10 +137,16 @@ impl PlayerDeck {
                 .show_value(false)
                 .trailing_fill(true);
 
-            let response = ui.add_sized([slider_width, 16.0], slider);
+            // 不可 seek 的曲目仍显示进度，但禁止拖动
+            let response = ui
+                .add_enabled_ui(state.seekable, |ui| {
+                    ui.add_sized([slider_width, 16.0], slider)
+                })
+                .inner
+                .on_disabled_hover_text("This track does not support seeking");
 
             if response.changed() {
-                state.seek(progress * state.duration);
+                state.seek_fraction(progress);
             }
 
             // 总时长
@@ -156,8 +169,11 @@ impl PlayerDeck {
             };
             ui.label(icon);
 
-            let slider = egui::Slider::new(&mut state.volume, 0.0..=1.0).show_value(false);
-            ui.add_sized([80.0, 16.0], slider);
+            let mut volume = state.volume;
+            let slider = egui::Slider::new(&mut volume, 0.0..=1.0).show_value(false);
+            if ui.add_sized([80.0, 16.0], slider).changed() {
+                state.set_volume(volume);
+            }
         });
     }
 }
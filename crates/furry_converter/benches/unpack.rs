@@ -0,0 +1,79 @@
+//! 整文件解包：顺序读取与逐 chunk seek 的吞吐对比
+//!
+//! 运行：`cargo bench -p furry_converter --bench unpack`
+
+use std::fs::File;
+use std::io::{Cursor, Write};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use furry_converter::{pack_to_furry, PackOptions};
+use furry_crypto::MasterKey;
+use furry_format::{FurryReader, OriginalFormat};
+
+/// 32 MiB 音频，64 KiB chunk（512 个 chunk）
+const AUDIO_LEN: usize = 32 * 1024 * 1024;
+const CHUNK_SIZE: usize = 64 * 1024;
+
+fn bench_unpack(c: &mut Criterion) {
+    let master_key = MasterKey::default_key();
+    let audio: Vec<u8> = (0..AUDIO_LEN).map(|i| (i % 251) as u8).collect();
+    let options = PackOptions {
+        chunk_size: CHUNK_SIZE,
+        include_meta: false,
+        ..Default::default()
+    };
+    let mut packed = Cursor::new(Vec::new());
+    pack_to_furry(
+        &mut Cursor::new(&audio),
+        &mut packed,
+        None,
+        OriginalFormat::Mp3,
+        &master_key,
+        &options,
+    )
+    .unwrap();
+
+    // 落盘后用无缓冲的 File 读取，seek/read 均为系统调用
+    let path = std::env::temp_dir().join(format!("furry_bench_{}.furry", std::process::id()));
+    File::create(&path)
+        .unwrap()
+        .write_all(packed.get_ref())
+        .unwrap();
+
+    let mut group = c.benchmark_group("unpack");
+    group.throughput(Throughput::Bytes(AUDIO_LEN as u64));
+    group.sample_size(20);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let mut reader = FurryReader::open(File::open(&path).unwrap(), &master_key).unwrap();
+            assert!(reader.audio_is_contiguous());
+            let mut total = 0usize;
+            reader
+                .for_each_audio_chunk(|data| {
+                    total += data.len();
+                    Ok::<(), furry_format::FormatError>(())
+                })
+                .unwrap();
+            assert_eq!(total, AUDIO_LEN);
+        })
+    });
+
+    group.bench_function("seek_per_chunk", |b| {
+        b.iter(|| {
+            let mut reader = FurryReader::open(File::open(&path).unwrap(), &master_key).unwrap();
+            let entries: Vec<_> = reader.index.audio_entries().into_iter().cloned().collect();
+            let mut total = 0usize;
+            for entry in &entries {
+                total += reader.read_chunk(entry).unwrap().len();
+            }
+            assert_eq!(total, AUDIO_LEN);
+        })
+    });
+
+    group.finish();
+    std::fs::remove_file(&path).ok();
+}
+
+criterion_group!(benches, bench_unpack);
+criterion_main!(benches);
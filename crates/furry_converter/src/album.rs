@@ -0,0 +1,100 @@
+//! 多曲目专辑封装
+//!
+//! 多个音频文件原样首尾相接写入同一个 .furry，并写入曲目表
+//! （[`furry_format::TrackTable`]）记录每首的范围与 tags，播放器把它们当作独立条目。
+
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use furry_crypto::MasterKey;
+use furry_format::{TrackTable, TrackTableEntry};
+
+use crate::{
+    detect_format, extract_meta_from_path, pack_impl, ConverterError, PackOptions, PackSource,
+};
+
+/// 将多个同格式的音频文件封装为一个多曲目 .furry，返回写入的曲目表
+///
+/// 各曲目保留完整的文件头，可以单独解码；`include_meta` 时每首的 tags 写入曲目表，
+/// 专辑级 tags 只来自 `options.tags_override`。
+pub fn pack_album<P, W>(
+    inputs: &[P],
+    output: &mut W,
+    master_key: &MasterKey,
+    options: &PackOptions,
+) -> Result<TrackTable, ConverterError>
+where
+    P: AsRef<Path>,
+    W: Write + Seek,
+{
+    let Some(first) = inputs.first() else {
+        return Err(ConverterError::UnsupportedFormat(
+            "album has no tracks".to_string(),
+        ));
+    };
+    let original_format = detect_format(first.as_ref());
+    if let Some(other) = inputs
+        .iter()
+        .find(|path| detect_format(path.as_ref()) != original_format)
+    {
+        return Err(ConverterError::UnsupportedFormat(format!(
+            "album tracks must share one format: {} is not {}",
+            other.as_ref().display(),
+            original_format
+        )));
+    }
+    let tag_encoding = options
+        .tag_encoding
+        .as_deref()
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()));
+
+    let mut table = TrackTable::default();
+    let mut input: Box<dyn Read> = Box::new(std::io::empty());
+    let mut virtual_start = 0u64;
+    for (index, path) in inputs.iter().enumerate() {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let virtual_len = file.metadata()?.len();
+
+        let tags = options
+            .include_meta
+            .then(|| extract_meta_from_path(path, original_format, tag_encoding))
+            .flatten()
+            .map(|meta| meta.tags);
+        let title = tags
+            .as_ref()
+            .and_then(|tags| tags.title.clone())
+            .or_else(|| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        table.tracks.push(TrackTableEntry {
+            index: index as u32,
+            virtual_start,
+            virtual_len,
+            title,
+            tags_json: tags.and_then(|tags| serde_json::to_string(&tags).ok()),
+        });
+
+        input = Box::new(input.chain(file));
+        virtual_start += virtual_len;
+    }
+
+    pack_impl(
+        &mut input,
+        output,
+        PackSource {
+            path: None,
+            audio_len: Some(virtual_start),
+            track_table: Some(&table),
+        },
+        original_format,
+        master_key,
+        options,
+    )?;
+    Ok(table)
+}
+
+/// 专辑中某一首的 tags（曲目表中没有或无法解析时为 `None`）
+pub fn album_track_tags(track: &TrackTableEntry) -> Option<crate::TagsJsonV1> {
+    crate::TagsJsonV1::from_json(track.tags_json.as_deref()?.as_bytes()).ok()
+}
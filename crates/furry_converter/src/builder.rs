@@ -0,0 +1,264 @@
+//! 封装/解包的链式构建 API
+//!
+//! 底层 [`pack_to_furry`] / [`unpack_from_furry_with_progress`] 保持不变；
+//! 新选项优先加到构建器上，避免调用方随函数签名变动。
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use furry_crypto::MasterKey;
+use furry_format::OriginalFormat;
+
+use crate::{
+    detect_format, pack_impl, unpack_from_furry_with_progress, ConverterError, PackOptions,
+    PackSource, PaddingTarget, TagsJsonV1,
+};
+
+type ProgressFn<'a> = Box<dyn FnMut(u64, u64) + 'a>;
+
+/// 封装构建器
+///
+/// 格式优先取 [`format_override`](Self::format_override)，其次按
+/// [`input_path`](Self::input_path) 的扩展名检测，都没有时为 `Unknown`。
+pub struct PackBuilder<'a> {
+    master_key: &'a MasterKey,
+    options: PackOptions,
+    input_path: Option<PathBuf>,
+    format: Option<OriginalFormat>,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> PackBuilder<'a> {
+    pub fn new(master_key: &'a MasterKey) -> Self {
+        Self {
+            master_key,
+            options: PackOptions::default(),
+            input_path: None,
+            format: None,
+            progress: None,
+        }
+    }
+
+    /// 以已有选项（如 [`PackOptions::privacy`]）为基础，之后的设置覆盖其字段
+    pub fn options(mut self, options: PackOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.options.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn padding(mut self, padding: PaddingTarget) -> Self {
+        self.options.padding = padding;
+        self
+    }
+
+    pub fn include_meta(mut self, include_meta: bool) -> Self {
+        self.options.include_meta = include_meta;
+        self
+    }
+
+    pub fn cover_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.cover_path = Some(path.into());
+        self
+    }
+
+    pub fn tags_override(mut self, tags: TagsJsonV1) -> Self {
+        self.options.tags_override = Some(tags);
+        self
+    }
+
+    pub fn compress_meta(mut self, compress_meta: bool) -> Self {
+        self.options.compress_meta = compress_meta;
+        self
+    }
+
+    pub fn chunk_crc(mut self, chunk_crc: bool) -> Self {
+        self.options.chunk_crc = chunk_crc;
+        self
+    }
+
+    pub fn meta_key(mut self, meta_key: bool) -> Self {
+        self.options.meta_key = meta_key;
+        self
+    }
+
+    /// 未启用 `insecure-plaintext` feature 时忽略，始终加密
+    #[allow(unused_mut, unused_variables)]
+    pub fn encrypt(mut self, encrypt: bool) -> Self {
+        #[cfg(feature = "insecure-plaintext")]
+        {
+            self.options.encrypt = encrypt;
+        }
+        self
+    }
+
+    /// 源文件路径：用于提取 META 与检测格式
+    pub fn input_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.input_path = Some(path.into());
+        self
+    }
+
+    pub fn format_override(mut self, format: OriginalFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// 每读入一段源数据调用 `progress(read_bytes, total_bytes)`；流式封装时 `total_bytes` 为 0
+    pub fn progress(mut self, progress: impl FnMut(u64, u64) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.options.cancel = Some(cancel);
+        self
+    }
+
+    fn original_format(&self) -> OriginalFormat {
+        self.format
+            .or_else(|| self.input_path.as_deref().map(detect_format))
+            .unwrap_or(OriginalFormat::Unknown)
+    }
+
+    /// 从可 seek 的输入封装（见 [`pack_to_furry`](crate::pack_to_furry)）
+    pub fn pack<R, W>(self, input: &mut R, output: &mut W) -> Result<(), ConverterError>
+    where
+        R: Read + Seek,
+        W: Write + Seek,
+    {
+        let start = input.stream_position()?;
+        let end = input.seek(SeekFrom::End(0))?;
+        input.seek(SeekFrom::Start(start))?;
+        let len = end.saturating_sub(start);
+        self.run(input, output, Some(len))
+    }
+
+    /// 从只读流封装（见 [`pack_stream_to_furry`](crate::pack_stream_to_furry)），不提取 META
+    pub fn pack_stream<R, W>(mut self, input: &mut R, output: &mut W) -> Result<(), ConverterError>
+    where
+        R: Read,
+        W: Write + Seek,
+    {
+        self.input_path = None;
+        self.run(input, output, None)
+    }
+
+    fn run<R, W>(
+        mut self,
+        input: &mut R,
+        output: &mut W,
+        audio_len: Option<u64>,
+    ) -> Result<(), ConverterError>
+    where
+        R: Read,
+        W: Write + Seek,
+    {
+        let original_format = self.original_format();
+        let source = PackSource {
+            path: self.input_path.as_deref(),
+            audio_len,
+            track_table: None,
+        };
+        match self.progress.as_mut() {
+            Some(progress) => {
+                let mut input = ProgressReader {
+                    inner: input,
+                    read: 0,
+                    total: audio_len.unwrap_or(0),
+                    progress,
+                };
+                pack_impl(
+                    &mut input,
+                    output,
+                    source,
+                    original_format,
+                    self.master_key,
+                    &self.options,
+                )
+            }
+            None => pack_impl(
+                input,
+                output,
+                source,
+                original_format,
+                self.master_key,
+                &self.options,
+            ),
+        }
+    }
+}
+
+/// 解包构建器
+pub struct UnpackBuilder<'a> {
+    master_key: &'a MasterKey,
+    cancel: Option<Arc<AtomicBool>>,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> UnpackBuilder<'a> {
+    pub fn new(master_key: &'a MasterKey) -> Self {
+        Self {
+            master_key,
+            cancel: None,
+            progress: None,
+        }
+    }
+
+    /// 每写出一个 AUDIO chunk 调用 `progress(written_bytes, total_bytes)`
+    pub fn progress(mut self, progress: impl FnMut(u64, u64) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn unpack<R, W>(
+        mut self,
+        input: &mut R,
+        output: &mut W,
+    ) -> Result<OriginalFormat, ConverterError>
+    where
+        R: Read + Seek,
+        W: Write,
+    {
+        let mut progress = self.progress.take();
+        unpack_from_furry_with_progress(
+            input,
+            output,
+            self.master_key,
+            self.cancel.as_deref(),
+            |written, total| {
+                if let Some(progress) = progress.as_mut() {
+                    progress(written, total);
+                }
+            },
+        )
+    }
+}
+
+/// 统计已读字节并回调进度
+struct ProgressReader<'r, R, F: ?Sized> {
+    inner: &'r mut R,
+    read: u64,
+    total: u64,
+    progress: &'r mut F,
+}
+
+impl<R: Read, F: FnMut(u64, u64) + ?Sized> Read for ProgressReader<'_, R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.read += n as u64;
+            (self.progress)(self.read, self.total);
+        }
+        Ok(n)
+    }
+}
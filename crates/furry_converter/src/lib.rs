@@ -2,13 +2,21 @@
 //!
 //! 提供音频文件与 .furry 格式之间的转换功能。
 
-use std::io::{Read, Seek, Write};
-use std::path::Path;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use furry_crypto::MasterKey;
-use furry_format::{FurryReader, FurryWriter, MetaKind, OriginalFormat};
-use serde::Serialize;
-use symphonia::core::codecs::CODEC_TYPE_NULL;
+use furry_crypto::{MasterKey, OsRng, Rng, TestRng};
+pub use furry_format::sniff_image_mime;
+use furry_format::{
+    FurryReader, FurryWriter, MetaKind, OriginalFormat, SourceInfo, VerifyOptions, VerifyReport,
+};
+use serde::{Deserialize, Serialize};
+use symphonia::core::codecs::{
+    CODEC_TYPE_AAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP1, CODEC_TYPE_MP2, CODEC_TYPE_MP3,
+    CODEC_TYPE_NULL, CODEC_TYPE_OPUS, CODEC_TYPE_VORBIS,
+};
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::{
@@ -16,6 +24,22 @@ use symphonia::core::meta::{
 };
 use symphonia::core::probe::Hint;
 
+mod album;
+mod builder;
+mod library;
+mod meta;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod pcm;
+
+pub use album::*;
+pub use builder::*;
+pub use library::*;
+pub use meta::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+pub use pcm::*;
+
 /// 转换器错误
 #[derive(thiserror::Error, Debug)]
 pub enum ConverterError {
@@ -25,32 +49,239 @@ pub enum ConverterError {
     #[error("Format error: {0}")]
     Format(#[from] furry_format::FormatError),
 
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] furry_crypto::CryptoError),
+
     #[error("Unsupported format: {0}")]
     UnsupportedFormat(String),
+
+    #[error("Invalid cover art: {0}")]
+    InvalidCover(String),
+
+    #[error("Invalid META: {0}")]
+    InvalidMeta(String),
+
+    #[error("Verification failed: {0}")]
+    VerifyFailed(String),
+
+    #[error("Output already exists: {0}")]
+    OutputExists(PathBuf),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+/// 写入 SourceInfo 的 producer 字符串
+pub const PRODUCER: &str = concat!("furry_converter ", env!("CARGO_PKG_VERSION"));
+
+/// padding 目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingTarget {
+    /// 追加固定字节数的 padding 负载（负压缩率）
+    Bytes(u64),
+    /// 填充到恰好指定的文件总大小；内容本身已超过目标时报错
+    TotalSize(u64),
+    /// 追加 `min..=max` 之间随机字节数的 padding 负载，每次封装重新抽取
+    ///
+    /// 随机数取自穿插位置的随机来源，设置 `deterministic_seed` 时可复现。
+    Random { min: u64, max: u64 },
+}
+
+/// 输出路径已存在时的处理方式（见 [`create_output`]）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// 返回 `ConverterError::OutputExists`
+    #[default]
+    Fail,
+    /// 截断并覆盖
+    Overwrite,
+    /// 改写到 `name (1).ext`、`name (2).ext` ... 中第一个不存在的路径
+    Rename,
 }
 
 /// 封装选项
 #[derive(Debug, Clone)]
 pub struct PackOptions {
-    /// AUDIO chunk 目标大小（字节）
+    /// AUDIO chunk 目标大小（字节），上限 `furry_format::MAX_CHUNK_PLAIN_LEN`（约 4 GiB）
     pub chunk_size: usize,
-    /// 总 padding 字节数（负压缩率）
-    pub padding_bytes: u64,
+    /// padding 目标
+    pub padding: PaddingTarget,
     /// 单个 padding chunk 大小
     pub padding_chunk_size: usize,
+    /// 将 padding chunk 随机穿插在 AUDIO chunk 之间（否则全部追加在音频之后）
+    pub interleave_padding: bool,
     /// 尝试写入 META（tags/cover 等），需要 `input_path` 可用
     pub include_meta: bool,
+    /// 外部封面文件（如 `folder.jpg`），源文件无内嵌封面时使用
+    pub cover_path: Option<PathBuf>,
+    /// 即使源文件有内嵌封面，也强制使用 `cover_path`
+    pub force_cover_path: bool,
+    /// 源格式提示（扩展名，如 `aac`），`OriginalFormat::Unknown` 时写入 SourceInfo 供解码器使用
+    ///
+    /// 未设置时取 `input_path` 的扩展名；流式封装没有路径，需显式提供。
+    pub format_hint: Option<String>,
+    /// 源文件标签的旧编码（encoding_rs 标签，如 `shift_jis`/`gbk`）
+    ///
+    /// ID3v1/ID3v2 Latin-1 帧里常存放其他代码页的字节，解析后成了乱码；设置后按此编码
+    /// 重新解码，解码失败的字段保持原样。无法识别的标签名被忽略。
+    /// 未设置时只修复"Latin-1 帧里实为 UTF-8"这一种情况。
+    pub tag_encoding: Option<String>,
+    /// 外部提供的权威标签（如来自数据库的 sidecar JSON），需 `include_meta`
+    ///
+    /// 写入前校验 schema（见 [`TagsJsonV1::validate`]），不合法时返回 `InvalidMeta`。
+    /// 与源文件提取的标签合并：这里已设置的字段优先，未设置的回退到提取值，
+    /// 见 [`TagsJsonV1::merge_over`]。
+    pub tags_override: Option<TagsJsonV1>,
+    /// gzip 压缩 tags 与歌词 META（`FLAG_META_GZIP`），旧版本读取器无法解析这些 META
+    pub compress_meta: bool,
+    /// 使用 32 字节 HKDF salt（`kdf_id = 2`），旧版本读取器无法打开
+    pub long_salt: bool,
+    /// 写入头部 + 索引 MAC（`FLAG_INDEX_MAC`），与 `long_salt` 互斥；旧版本读取器无法打开
+    pub index_mac: bool,
+    /// META 使用独立派生的密钥加密（`FLAG_META_KEY`），旧版本读取器无法打开
+    pub meta_key: bool,
+    /// 纯音频（无 META/padding）时使用紧凑索引，旧版本读取器无法打开
+    pub compact_index: bool,
+    /// 在索引中记录每个 chunk 密文的 CRC32（`INDEX_FLAG_CHUNK_CRC`），`verify` 可据此区分位翻转与头部篡改
+    pub chunk_crc: bool,
+    /// 在明文头部写入原始格式提示（`FLAG_FORMAT_HINT`），无需密钥即可判断文件类型
+    ///
+    /// 提示未经认证，加密索引中的值仍是权威值；旧版本读取器无法打开。
+    pub header_format_hint: bool,
+    /// INDEX 之后追加的随机字节数（fake footer），计入 `PaddingTarget::TotalSize`
+    pub fake_footer_len: u32,
+    /// 以固定种子代替系统随机数（file_id/salt/padding 内容与穿插位置），输出逐字节可复现
+    ///
+    /// **仅用于测试/golden 文件**：相同种子会得到相同的文件密钥，见 [`furry_crypto::TestRng`]。
+    pub deterministic_seed: Option<u64>,
+    /// 输出已存在时的处理方式；仅 [`create_output`] 使用，写入 `Write` 的接口不受影响
+    pub overwrite: OverwritePolicy,
+    /// 打包完成后由 [`finish_output`] 对输出文件调用 `sync_all`；默认只 flush 到操作系统
+    pub sync_on_finish: bool,
+    /// 取消标志：每个 chunk 检查一次，置位后返回 `ConverterError::Cancelled`
+    ///
+    /// 输出不完整（缺少 INDEX），调用方应删除。
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// 是否加密；`false` 写出明文调试文件（`FLAG_PLAINTEXT`），**不安全**，仅用于排查容器布局
+    #[cfg(feature = "insecure-plaintext")]
+    pub encrypt: bool,
 }
 
 impl Default for PackOptions {
     fn default() -> Self {
         Self {
             chunk_size: 256 * 1024, // 256KB
-            padding_bytes: 0,
+            padding: PaddingTarget::Bytes(0),
             padding_chunk_size: 64 * 1024, // 64KB
+            interleave_padding: false,
             include_meta: true,
+            cover_path: None,
+            force_cover_path: false,
+            format_hint: None,
+            tag_encoding: None,
+            tags_override: None,
+            compress_meta: false,
+            long_salt: false,
+            index_mac: false,
+            meta_key: false,
+            compact_index: false,
+            chunk_crc: false,
+            header_format_hint: false,
+            fake_footer_len: 0,
+            deterministic_seed: None,
+            overwrite: OverwritePolicy::default(),
+            sync_on_finish: false,
+            cancel: None,
+            #[cfg(feature = "insecure-plaintext")]
+            encrypt: true,
+        }
+    }
+}
+
+impl PackOptions {
+    /// 隐私预设：不写入任何 META，追加 256 KiB - 1 MiB 的随机 padding 并随机穿插
+    ///
+    /// 隐藏的内容：tags/封面/歌词、SourceInfo（producer）等全部 META；
+    /// 原始格式仅存在于加密索引中。
+    ///
+    /// 仍然可见的内容：文件头魔数与头部字段、文件总大小，以及每个 chunk 的明文记录头
+    /// （类型、长度、序号）——因此 padding chunk 依然可被识别，音频总长度也可由
+    /// AUDIO chunk 记录头求和得到。如需掩盖大小，请配合 `PaddingTarget::TotalSize` 使用。
+    pub fn privacy() -> Self {
+        Self {
+            include_meta: false,
+            padding: PaddingTarget::Random {
+                min: 256 * 1024,
+                max: 1024 * 1024,
+            },
+            // 较小的 padding chunk 使其分散到更多位置
+            padding_chunk_size: 16 * 1024,
+            interleave_padding: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// `Rename` 策略最多尝试的候选数
+const MAX_RENAME_ATTEMPTS: u32 = 1000;
+
+/// 按 `policy` 创建打包/解包输出文件（可读写，便于 [`pack_and_verify`] 回读）
+///
+/// 返回打开的文件及实际路径（`Rename` 时可能与 `path` 不同）。
+/// `Fail`/`Rename` 使用 `create_new`，检查与创建之间不存在竞态。
+pub fn create_output(
+    path: &Path,
+    policy: OverwritePolicy,
+) -> Result<(std::fs::File, PathBuf), ConverterError> {
+    let mut open = std::fs::OpenOptions::new();
+    open.read(true).write(true);
+    if policy == OverwritePolicy::Overwrite {
+        let file = open.create(true).truncate(true).open(path)?;
+        return Ok((file, path.to_path_buf()));
+    }
+    open.create_new(true);
+
+    let mut candidate = path.to_path_buf();
+    for n in 1..=MAX_RENAME_ATTEMPTS {
+        match open.open(&candidate) {
+            Ok(file) => return Ok((file, candidate)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if policy == OverwritePolicy::Fail {
+                    return Err(ConverterError::OutputExists(candidate));
+                }
+                candidate = numbered_path(path, n);
+            }
+            Err(e) => return Err(e.into()),
         }
     }
+    Err(ConverterError::OutputExists(path.to_path_buf()))
+}
+
+/// 结束对 [`create_output`] 所建文件的写入：flush，并在 `sync_on_finish` 时 `sync_all`
+///
+/// 打包接口只要求 `Write + Seek`，落盘与否由持有 `File` 的调用方在此统一处理。
+pub fn finish_output(
+    output: &mut std::fs::File,
+    options: &PackOptions,
+) -> Result<(), ConverterError> {
+    output.flush()?;
+    if options.sync_on_finish {
+        output.sync_all()?;
+    }
+    Ok(())
+}
+
+/// `dir/name.ext` -> `dir/name (n).ext`
+fn numbered_path(path: &Path, n: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+    path.with_file_name(name)
 }
 
 /// 从文件扩展名检测格式
@@ -61,6 +292,55 @@ pub fn detect_format(path: &Path) -> OriginalFormat {
         .unwrap_or(OriginalFormat::Unknown)
 }
 
+/// 检查文件实际 codec 是否与声明的格式一致
+///
+/// 用于在打包前发现扩展名错误或手动指定了错误格式；`Unknown` 总是通过，
+/// 无法探测（非音频/损坏）时返回 `false`。
+pub fn validate_format(path: &Path, claimed: OriginalFormat) -> bool {
+    if claimed == OriginalFormat::Unknown {
+        return true;
+    }
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let Ok(probed) = symphonia::default::get_probe().format(
+        &Hint::new(),
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    ) else {
+        return false;
+    };
+    let Some(codec) = probed
+        .format
+        .tracks()
+        .iter()
+        .map(|t| t.codec_params.codec)
+        .find(|c| *c != CODEC_TYPE_NULL)
+    else {
+        return false;
+    };
+    match claimed {
+        OriginalFormat::Mp3 => matches!(codec, CODEC_TYPE_MP1 | CODEC_TYPE_MP2 | CODEC_TYPE_MP3),
+        OriginalFormat::Flac => codec == CODEC_TYPE_FLAC,
+        OriginalFormat::Ogg => {
+            matches!(codec, CODEC_TYPE_VORBIS | CODEC_TYPE_OPUS | CODEC_TYPE_FLAC)
+        }
+        OriginalFormat::Wav => !matches!(
+            codec,
+            CODEC_TYPE_MP1
+                | CODEC_TYPE_MP2
+                | CODEC_TYPE_MP3
+                | CODEC_TYPE_VORBIS
+                | CODEC_TYPE_OPUS
+                | CODEC_TYPE_AAC
+                | CODEC_TYPE_FLAC
+        ),
+        OriginalFormat::Unknown => true,
+    }
+}
+
 /// 透传封装：将原始音频文件封装为 .furry
 ///
 /// 不重编码，直接将原始字节流切分加密封装。
@@ -76,51 +356,275 @@ where
     R: Read + Seek,
     W: Write + Seek,
 {
+    let start = input.stream_position()?;
+    let end = input.seek(SeekFrom::End(0))?;
+    input.seek(SeekFrom::Start(start))?;
+    pack_impl(
+        input,
+        output,
+        PackSource {
+            path: input_path,
+            audio_len: Some(end.saturating_sub(start)),
+            track_table: None,
+        },
+        original_format,
+        master_key,
+        options,
+    )
+}
+
+/// 流式封装：输入只需 `Read`（如 stdin 管道）
+///
+/// 没有源文件路径，因此不提取 tags/封面等 META（仍写入 SourceInfo）。
+/// 音频总长度未知：`interleave_padding` 时 padding 全部追加在音频之后，
+/// `PaddingTarget::TotalSize` 仍可精确补齐。
+pub fn pack_stream_to_furry<R, W>(
+    input: &mut R,
+    output: &mut W,
+    original_format: OriginalFormat,
+    master_key: &MasterKey,
+    options: &PackOptions,
+) -> Result<(), ConverterError>
+where
+    R: Read,
+    W: Write + Seek,
+{
+    pack_impl(
+        input,
+        output,
+        PackSource::default(),
+        original_format,
+        master_key,
+        options,
+    )
+}
+
+/// [`pack_impl`] 的输入描述
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PackSource<'a> {
+    /// 源文件路径，用于提取 META
+    pub(crate) path: Option<&'a Path>,
+    /// 输入长度；`None` 表示未知（不可 seek）
+    pub(crate) audio_len: Option<u64>,
+    /// 多曲目专辑的曲目表
+    pub(crate) track_table: Option<&'a furry_format::TrackTable>,
+}
+
+fn pack_impl<R, W>(
+    input: &mut R,
+    output: &mut W,
+    source: PackSource<'_>,
+    original_format: OriginalFormat,
+    master_key: &MasterKey,
+    options: &PackOptions,
+) -> Result<(), ConverterError>
+where
+    R: Read,
+    W: Write + Seek,
+{
+    let PackSource {
+        path: input_path,
+        audio_len,
+        track_table,
+    } = source;
+
     // 创建 writer
-    let mut writer = FurryWriter::create(output, master_key, original_format)?;
+    let kdf_id = if options.long_salt {
+        furry_format::KDF_HKDF_SHA256_SALT32
+    } else {
+        furry_format::KDF_HKDF_SHA256
+    };
+    // 穿插位置与写入器各用一个随机来源，固定种子时两者都可复现
+    let (writer_rng, mut placement_rng): (Box<dyn Rng>, Box<dyn Rng>) =
+        match options.deterministic_seed {
+            Some(seed) => (Box::new(TestRng::new(seed)), Box::new(TestRng::new(!seed))),
+            None => (Box::new(OsRng), Box::new(OsRng)),
+        };
+    #[cfg(feature = "insecure-plaintext")]
+    let mut writer = if options.encrypt {
+        FurryWriter::create_with_rng(output, master_key, original_format, kdf_id, writer_rng)?
+    } else {
+        FurryWriter::create_plaintext(output, master_key, original_format)?
+    };
+    #[cfg(not(feature = "insecure-plaintext"))]
+    let mut writer =
+        FurryWriter::create_with_rng(output, master_key, original_format, kdf_id, writer_rng)?;
+    if options.index_mac {
+        writer = writer.with_index_mac()?;
+    }
+    if options.meta_key {
+        writer = writer.with_meta_key()?;
+    }
+    writer.set_compact_index(options.compact_index);
+    writer.set_chunk_crc(options.chunk_crc);
+    writer.set_fake_footer_len(options.fake_footer_len);
+    writer.set_header_format_hint(options.header_format_hint);
 
     if options.include_meta {
-        if let Some(path) = input_path {
-            if let Some(meta) = extract_meta_from_path(path, original_format) {
-                if let Some(tags_json) = meta.tags_json {
-                    let _ = writer.write_meta_chunk(MetaKind::Tags, tags_json.as_bytes(), 0);
-                }
-                if let Some(cover) = meta.cover {
-                    let mut payload = Vec::with_capacity(cover.mime.len() + 1 + cover.bytes.len());
-                    payload.extend_from_slice(cover.mime.as_bytes());
-                    payload.push(0);
-                    payload.extend_from_slice(&cover.bytes);
-                    let _ = writer.write_meta_chunk(MetaKind::CoverArt, &payload, 0);
-                }
-                if let Some(lyrics) = meta.lyrics {
-                    let _ = writer.write_meta_chunk(MetaKind::Lyrics, lyrics.as_bytes(), 0);
-                }
+        let tag_encoding = options
+            .tag_encoding
+            .as_deref()
+            .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()));
+        let meta =
+            input_path.and_then(|path| extract_meta_from_path(path, original_format, tag_encoding));
+        let (tags, embedded_cover, lyrics, gapless) = match meta {
+            Some(meta) => (Some(meta.tags), meta.cover, meta.lyrics, meta.gapless),
+            None => (None, None, Vec::new(), None),
+        };
+        let tags = match &options.tags_override {
+            Some(tags_override) => {
+                tags_override.validate()?;
+                let mut merged = tags_override.merge_over(tags);
+                merged.original_format = original_format.to_string();
+                Some(merged)
+            }
+            None => tags,
+        };
+
+        // 内嵌封面优先，除非显式强制使用外部封面
+        let sidecar_cover = match &options.cover_path {
+            Some(path) if options.force_cover_path || embedded_cover.is_none() => {
+                Some(load_cover_sidecar(path)?)
+            }
+            _ => None,
+        };
+
+        // 文本类 META 可选压缩
+        let write_text_meta = |writer: &mut FurryWriter<_>, kind: MetaKind, payload: &[u8]| {
+            let _ = if options.compress_meta {
+                writer.write_meta_chunk_compressed(kind, payload)
+            } else {
+                writer.write_meta_chunk(kind, payload, 0)
+            };
+        };
+        if let Some(tags_json) = tags.and_then(|tags| serde_json::to_string(&tags).ok()) {
+            write_text_meta(&mut writer, MetaKind::Tags, tags_json.as_bytes());
+        }
+        if let Some(cover) = sidecar_cover.or(embedded_cover) {
+            let mut payload = Vec::with_capacity(cover.mime.len() + 1 + cover.bytes.len());
+            payload.extend_from_slice(cover.mime.as_bytes());
+            payload.push(0);
+            payload.extend_from_slice(&cover.bytes);
+            let _ = writer.write_meta_chunk(MetaKind::CoverArt, &payload, 0);
+        }
+        for (lang, text) in &lyrics {
+            let payload = furry_format::encode_lyrics_payload(lang, text);
+            write_text_meta(&mut writer, MetaKind::Lyrics, &payload);
+        }
+        if let Some(payload) = input_path.and_then(meta::extract_raw_tag_payload) {
+            let _ = writer.write_meta_chunk(MetaKind::RawTagBlock, &payload, 0);
+        }
+        if let Some(gapless) = gapless {
+            let _ = writer.write_meta_chunk(MetaKind::Gapless, &gapless.to_bytes(), 0);
+        }
+    }
+
+    // 曲目表描述音频流结构，与 include_meta 无关
+    if let Some(table) = track_table {
+        writer.write_meta_chunk(MetaKind::TrackTable, &table.to_bytes(), 0)?;
+    }
+
+    // SourceInfo 在音频之后写入；摘要先占位，便于预估其大小
+    let mut source_info = options.include_meta.then(|| SourceInfo {
+        producer: Some(PRODUCER.to_string()),
+        audio_digest: Some([0u8; 32]),
+        // 枚举未覆盖的格式（如 m4a）保留源扩展名，供解包/播放提示使用
+        extension: options
+            .format_hint
+            .as_deref()
+            .or_else(|| input_path.and_then(|path| path.extension()?.to_str()))
+            .filter(|_| original_format == OriginalFormat::Unknown)
+            .and_then(furry_format::sanitize_extension),
+    });
+
+    // (音频长度, AUDIO chunk 数)；流式输入时未知
+    let known_len = audio_len.map(|len| (len, len.div_ceil(options.chunk_size as u64)));
+
+    // 规划 padding chunk 大小
+    let split_padding = |total: u64| {
+        let mut sizes = Vec::new();
+        let mut remaining = total;
+        while remaining > 0 {
+            let size = remaining.min(options.padding_chunk_size.max(1) as u64) as usize;
+            sizes.push(size);
+            remaining -= size as u64;
+        }
+        sizes
+    };
+    let padding_sizes: Vec<usize> = match options.padding {
+        PaddingTarget::Bytes(total) => split_padding(total),
+        PaddingTarget::Random { min, max } => {
+            let span = max.saturating_sub(min).saturating_add(1);
+            split_padding(min + random_u64(placement_rng.as_mut())? % span)
+        }
+        PaddingTarget::TotalSize(target) => match known_len {
+            Some((audio_len, audio_chunks)) if options.interleave_padding => {
+                // 预估不含 padding 的最终大小：大部分预算随机穿插，
+                // 剩余部分留给音频之后的 `pad_to_total_size` 精确补齐
+                let overhead = furry_format::PADDING_RECORD_OVERHEAD;
+                let post_meta = source_info
+                    .as_ref()
+                    .map_or(0, |info| info.to_bytes().len() as u64 + overhead);
+                let projected =
+                    writer.projected_total_size() + audio_len + audio_chunks * overhead + post_meta;
+                let reserve = options.padding_chunk_size as u64 + 2 * overhead;
+                let budget = target.saturating_sub(projected).saturating_sub(reserve);
+                furry_format::padding_chunk_sizes(budget, options.padding_chunk_size)
+                    .unwrap_or_default()
             }
+            _ => Vec::new(),
+        },
+    };
+
+    // (插入位置, 大小)，插入位置为其后的 AUDIO chunk 序号
+    let mut padding_plan: Vec<(u64, usize)> = padding_sizes
+        .into_iter()
+        .map(|size| (u64::MAX, size))
+        .collect();
+    if let (true, Some((_, audio_chunks))) = (options.interleave_padding, known_len) {
+        for (slot, _) in &mut padding_plan {
+            *slot = random_u64(placement_rng.as_mut())? % (audio_chunks + 1);
         }
+        padding_plan.sort_by_key(|(slot, _)| *slot);
     }
+    let mut padding_iter = padding_plan.into_iter().peekable();
 
     // 分块读取并写入
     let mut buffer = vec![0u8; options.chunk_size];
     let mut virtual_offset: u64 = 0;
+    let mut audio_index: u64 = 0;
+    let mut audio_hasher = blake3::Hasher::new();
 
     loop {
+        check_cancel(options.cancel.as_deref())?;
+
         let bytes_read = read_full(input, &mut buffer)?;
         if bytes_read == 0 {
             break;
         }
 
+        while let Some((_, size)) = padding_iter.next_if(|(slot, _)| *slot <= audio_index) {
+            writer.write_padding_chunk(size)?;
+        }
+
         writer.write_audio_chunk(&buffer[..bytes_read], virtual_offset)?;
+        audio_hasher.update(&buffer[..bytes_read]);
         virtual_offset += bytes_read as u64;
+        audio_index += 1;
     }
 
-    // 写入 padding chunks（负压缩率）
-    if options.padding_bytes > 0 {
-        let mut remaining = options.padding_bytes;
-        while remaining > 0 {
-            let chunk_size = remaining.min(options.padding_chunk_size as u64) as usize;
-            writer.write_padding_chunk(chunk_size)?;
-            remaining -= chunk_size as u64;
-        }
+    // 依赖完整音频的 META 在音频之后写入（读取端按索引定位，与物理顺序无关）
+    if let Some(source_info) = &mut source_info {
+        source_info.audio_digest = Some(*audio_hasher.finalize().as_bytes());
+        let _ = writer.write_meta_chunk(MetaKind::SourceInfo, &source_info.to_bytes(), 0);
+    }
+
+    // 写入剩余 padding chunks（负压缩率）
+    for (_, size) in padding_iter {
+        writer.write_padding_chunk(size)?;
+    }
+    if let PaddingTarget::TotalSize(target) = options.padding {
+        writer.pad_to_total_size(target, options.padding_chunk_size)?;
     }
 
     // 完成写入
@@ -138,19 +642,257 @@ pub fn unpack_from_furry<R, W>(
 where
     R: Read + Seek,
     W: Write,
+{
+    unpack_from_furry_with_progress(input, output, master_key, None, |_, _| {})
+}
+
+/// 可取消的解包：`cancel` 置位后返回 `ConverterError::Cancelled`，输出不完整
+pub fn unpack_from_furry_cancellable<R, W>(
+    input: &mut R,
+    output: &mut W,
+    master_key: &MasterKey,
+    cancel: Option<&AtomicBool>,
+) -> Result<OriginalFormat, ConverterError>
+where
+    R: Read + Seek,
+    W: Write,
+{
+    unpack_from_furry_with_progress(input, output, master_key, cancel, |_, _| {})
+}
+
+/// 带进度的解包：每写出一个 AUDIO chunk 调用一次 `progress(written_bytes, total_bytes)`
+///
+/// `total_bytes` 为 [`FurryReader::total_audio_bytes`]，只计音频，不含 padding。
+pub fn unpack_from_furry_with_progress<R, W, F>(
+    input: &mut R,
+    output: &mut W,
+    master_key: &MasterKey,
+    cancel: Option<&AtomicBool>,
+    mut progress: F,
+) -> Result<OriginalFormat, ConverterError>
+where
+    R: Read + Seek,
+    W: Write,
+    F: FnMut(u64, u64),
 {
     let mut reader = FurryReader::open(input, master_key)?;
 
     let original_format = reader.index.header.original_format;
+    let total = reader.total_audio_bytes();
+
+    // 按 virtual_offset 顺序读取所有 AUDIO chunks；物理连续时顺序读取，不逐块 seek
+    let mut written = 0u64;
+    reader.for_each_audio_chunk(|data| {
+        check_cancel(cancel)?;
+        output.write_all(data)?;
+        written += data.len() as u64;
+        progress(written, total);
+        Ok::<(), ConverterError>(())
+    })?;
+
+    Ok(original_format)
+}
+
+/// 逐字节比对 .furry 解包结果与原始文件
+///
+/// 返回 `None` 表示完全一致，否则为第一个不同字节的偏移（长度不同时为较短一方的长度）。
+/// 按 chunk 流式解密比对，明文不落盘。仅适用于透传封装（[`pack_to_furry`]）：
+/// 本库目前不做重编码，所有 .furry 的音频流都应与源文件一致。
+pub fn verify_roundtrip(
+    original: &Path,
+    furry: &Path,
+    master_key: &MasterKey,
+) -> Result<Option<u64>, ConverterError> {
+    let mut reader = FurryReader::open(std::fs::File::open(furry)?, master_key)?;
+    let mut source = std::io::BufReader::new(std::fs::File::open(original)?);
 
-    // 按 virtual_offset 顺序读取所有 AUDIO chunks
     let audio_entries: Vec<_> = reader.index.audio_entries().into_iter().cloned().collect();
+    let mut offset = 0u64;
+    let mut expected = Vec::new();
     for entry in &audio_entries {
         let data = reader.read_chunk(entry)?;
-        output.write_all(&data)?;
+        expected.resize(data.len(), 0);
+        let n = read_full(&mut source, &mut expected)?;
+        if let Some(i) = (0..n).find(|&i| data[i] != expected[i]) {
+            return Ok(Some(offset + i as u64));
+        }
+        if n < data.len() {
+            return Ok(Some(offset + n as u64));
+        }
+        offset += n as u64;
     }
 
-    Ok(original_format)
+    // 源文件比解包结果更长
+    let mut probe = [0u8; 1];
+    if read_full(&mut source, &mut probe)? != 0 {
+        return Ok(Some(offset));
+    }
+    Ok(None)
+}
+
+/// 以新的 AUDIO chunk 大小重新封装（不重编码、不落盘明文）
+///
+/// 使用同一主密钥与 KDF，但生成新的 file_id/salt；META（按原顺序）、原始格式（及头部提示）、
+/// fake footer 长度与 padding 总量保持不变，padding 统一追加在音频之后。
+pub fn rechunk<R, W>(
+    input: &mut R,
+    output: &mut W,
+    master_key: &MasterKey,
+    new_chunk_size: usize,
+) -> Result<(), ConverterError>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    let new_chunk_size = new_chunk_size.max(1);
+    let mut reader = FurryReader::open(input, master_key)?;
+    let mut writer = FurryWriter::create_with_kdf(
+        output,
+        master_key,
+        reader.index.header.original_format,
+        reader.header.kdf_id,
+    )?;
+    if reader.flags().has_index_mac() {
+        writer = writer.with_index_mac()?;
+    }
+    if reader.flags().has_meta_key() {
+        writer = writer.with_meta_key()?;
+    }
+    writer
+        .set_compact_index(reader.index.header.flags & furry_format::INDEX_FLAG_COMPACT_AUDIO != 0);
+    writer.set_chunk_crc(reader.index.has_chunk_crc());
+    writer.set_fake_footer_len(reader.header.fake_footer_len);
+    writer.set_header_format_hint(reader.header.original_format_hint().is_some());
+
+    let meta_entries: Vec<_> = reader.index.meta_entries().into_iter().cloned().collect();
+    for entry in &meta_entries {
+        let data = reader.read_chunk(entry)?;
+        writer.write_meta_chunk(
+            MetaKind::from_u16(entry.meta_kind),
+            &data,
+            entry.chunk_flags,
+        )?;
+    }
+
+    // 拼接连续明文，按新大小切分
+    let audio_entries: Vec<_> = reader.index.audio_entries().into_iter().cloned().collect();
+    let mut pending: Vec<u8> = Vec::with_capacity(new_chunk_size * 2);
+    let mut virtual_offset = 0u64;
+    for entry in &audio_entries {
+        pending.extend_from_slice(&reader.read_chunk(entry)?);
+        let mut consumed = 0;
+        while pending.len() - consumed >= new_chunk_size {
+            writer.write_audio_chunk(
+                &pending[consumed..consumed + new_chunk_size],
+                virtual_offset,
+            )?;
+            virtual_offset += new_chunk_size as u64;
+            consumed += new_chunk_size;
+        }
+        pending.drain(..consumed);
+    }
+    if !pending.is_empty() {
+        writer.write_audio_chunk(&pending, virtual_offset)?;
+    }
+
+    for entry in reader.index.entries.iter() {
+        if entry.chunk_type == furry_format::ChunkType::Padding {
+            writer.write_padding_chunk(entry.plain_len as usize)?;
+        }
+    }
+
+    writer.finish()?;
+    Ok(())
+}
+
+/// 校验 .furry 文件所有 chunk 的完整性
+///
+/// `progress(done, total)` 返回 `false` 可中止；中止后可用报告中的
+/// `checked_chunks` 作为 `VerifyOptions::resume_from` 继续。
+pub fn verify_furry<R, F>(
+    input: &mut R,
+    master_key: &MasterKey,
+    options: &VerifyOptions,
+    progress: F,
+) -> Result<VerifyReport, ConverterError>
+where
+    R: Read + Seek,
+    F: FnMut(usize, usize) -> bool,
+{
+    let mut reader = FurryReader::open(input, master_key)?;
+    Ok(reader.verify(options, progress)?)
+}
+
+/// 封装后重新打开输出并校验，确认可读后再删除源文件
+///
+/// 在 [`pack_to_furry`] 之后对全部 chunk 做 AEAD 校验；`compare_digest` 为 `true` 时
+/// 还会重读输入并比对解密音频的 BLAKE3 摘要。比 `pack_to_furry` 多一遍读取，
+/// 适合"转存为 .furry"一类会删除源文件的流程。任何不一致返回 `ConverterError::VerifyFailed`。
+pub fn pack_and_verify<R, W>(
+    input: &mut R,
+    output: &mut W,
+    input_path: Option<&Path>,
+    original_format: OriginalFormat,
+    master_key: &MasterKey,
+    options: &PackOptions,
+    compare_digest: bool,
+) -> Result<VerifyReport, ConverterError>
+where
+    R: Read + Seek,
+    W: Read + Write + Seek,
+{
+    let start = input.stream_position()?;
+    pack_to_furry(
+        input,
+        output,
+        input_path,
+        original_format,
+        master_key,
+        options,
+    )?;
+    output.flush()?;
+
+    let mut reader = FurryReader::open(&mut *output, master_key)?;
+    let report = reader.verify(&VerifyOptions::default(), |_, _| true)?;
+    if !report.is_ok() {
+        return Err(ConverterError::VerifyFailed(format!(
+            "{} of {} chunks failed authentication",
+            report.failed_chunks.len(),
+            report.total_chunks
+        )));
+    }
+
+    if compare_digest {
+        input.seek(SeekFrom::Start(start))?;
+        let mut hasher = blake3::Hasher::new();
+        let mut buffer = vec![0u8; options.chunk_size.max(1)];
+        loop {
+            let n = read_full(input, &mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+        if reader.audio_plaintext_digest()? != *hasher.finalize().as_bytes() {
+            return Err(ConverterError::VerifyFailed(
+                "decrypted audio does not match source".to_string(),
+            ));
+        }
+    }
+    Ok(report)
+}
+
+fn check_cancel(cancel: Option<&AtomicBool>) -> Result<(), ConverterError> {
+    match cancel {
+        Some(flag) if flag.load(Ordering::Relaxed) => Err(ConverterError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+fn random_u64(rng: &mut dyn Rng) -> Result<u64, ConverterError> {
+    let mut bytes = [0u8; 8];
+    rng.fill_bytes(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
 }
 
 /// 读取尽可能多的字节（处理短读）
@@ -167,6 +909,29 @@ fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize>
     Ok(total)
 }
 
+/// 读取外部封面文件并校验类型与大小
+fn load_cover_sidecar(path: &Path) -> Result<CoverArt, ConverterError> {
+    let len = std::fs::metadata(path)?.len();
+    let mime = {
+        let mut head = [0u8; 12];
+        let mut file = std::fs::File::open(path)?;
+        let n = read_full(&mut file, &mut head)?;
+        sniff_image_mime(&head[..n])
+            .ok_or_else(|| ConverterError::InvalidCover("unsupported image type".to_string()))?
+    };
+    if len + mime.len() as u64 + 1 > furry_format::MAX_COVER_BYTES as u64 {
+        return Err(ConverterError::InvalidCover(format!(
+            "{} bytes exceeds cover size cap",
+            len
+        )));
+    }
+    let bytes = std::fs::read(path)?;
+    Ok(CoverArt {
+        mime: mime.to_string(),
+        bytes,
+    })
+}
+
 #[derive(Debug)]
 struct CoverArt {
     mime: String,
@@ -175,33 +940,119 @@ struct CoverArt {
 
 #[derive(Debug)]
 struct ExtractedMeta {
-    tags_json: Option<String>,
+    tags: TagsJsonV1,
     cover: Option<CoverArt>,
-    lyrics: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-struct TagsJsonV1 {
-    schema: &'static str,
-    original_format: String,
-    title: Option<String>,
-    artist: Option<String>,
-    album: Option<String>,
-    album_artist: Option<String>,
-    genre: Option<String>,
-    track: Option<u32>,
-    disc: Option<u32>,
-    year: Option<i32>,
-    comment: Option<String>,
-    duration_ms: Option<u64>,
-    sample_rate: Option<u32>,
-    channels: Option<u16>,
-    codec: Option<String>,
-    raw: Vec<(String, String)>,
-}
-
-fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Option<ExtractedMeta> {
-    let file = std::fs::File::open(path).ok()?;
+    /// 每种语言一份：`(lang, text)`
+    lyrics: Vec<(String, String)>,
+    /// 编码器 delay/padding（LAME 头或 iTunSMPB）
+    gapless: Option<furry_format::GaplessInfo>,
+}
+
+/// tags META 载荷（`furry.tags.v1`）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagsJsonV1 {
+    pub schema: String,
+    pub original_format: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub genre: Option<String>,
+    pub track: Option<u32>,
+    pub disc: Option<u32>,
+    pub year: Option<i32>,
+    pub comment: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub codec: Option<String>,
+    /// 未映射到上面字段的原始标签 `(key, value)`
+    pub raw: Vec<(String, String)>,
+}
+
+/// 当前写入的 tags schema
+pub const TAGS_SCHEMA_V1: &str = "furry.tags.v1";
+
+/// 可识别的 tags schema
+pub const SUPPORTED_TAGS_SCHEMAS: &[&str] = &[TAGS_SCHEMA_V1];
+
+impl TagsJsonV1 {
+    /// 解析 tags JSON 并校验 schema
+    pub fn from_json(data: &[u8]) -> Result<Self, ConverterError> {
+        let tags: Self = serde_json::from_slice(data)
+            .map_err(|e| ConverterError::InvalidMeta(format!("tags: {}", e)))?;
+        tags.validate()?;
+        Ok(tags)
+    }
+
+    /// 序列化为 tags JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// 校验 `schema` 为 [`SUPPORTED_TAGS_SCHEMAS`] 之一
+    pub fn validate(&self) -> Result<(), ConverterError> {
+        if SUPPORTED_TAGS_SCHEMAS.contains(&self.schema.as_str()) {
+            Ok(())
+        } else {
+            Err(ConverterError::InvalidMeta(format!(
+                "tags: unsupported schema '{}'",
+                self.schema
+            )))
+        }
+    }
+
+    /// 以 `self` 覆盖 `base`
+    ///
+    /// 可选字段取 `self` 中已设置的值，否则回退到 `base`；`raw` 按键合并，同键取 `self`。
+    /// `schema` 固定为 [`TAGS_SCHEMA_V1`]，`original_format` 取 `self` 的非空值。
+    pub fn merge_over(&self, base: Option<TagsJsonV1>) -> TagsJsonV1 {
+        let base = base.unwrap_or_default();
+        let mut raw: Vec<(String, String)> = base
+            .raw
+            .into_iter()
+            .filter(|(key, _)| !self.raw.iter().any(|(k, _)| k == key))
+            .collect();
+        raw.extend(self.raw.iter().cloned());
+        TagsJsonV1 {
+            schema: TAGS_SCHEMA_V1.to_string(),
+            original_format: if self.original_format.is_empty() {
+                base.original_format
+            } else {
+                self.original_format.clone()
+            },
+            title: self.title.clone().or(base.title),
+            artist: self.artist.clone().or(base.artist),
+            album: self.album.clone().or(base.album),
+            album_artist: self.album_artist.clone().or(base.album_artist),
+            genre: self.genre.clone().or(base.genre),
+            track: self.track.or(base.track),
+            disc: self.disc.or(base.disc),
+            year: self.year.or(base.year),
+            comment: self.comment.clone().or(base.comment),
+            duration_ms: self.duration_ms.or(base.duration_ms),
+            sample_rate: self.sample_rate.or(base.sample_rate),
+            channels: self.channels.or(base.channels),
+            codec: self.codec.clone().or(base.codec),
+            raw,
+        }
+    }
+}
+
+fn extract_meta_from_path(
+    path: &Path,
+    original_format: OriginalFormat,
+    tag_encoding: Option<&'static encoding_rs::Encoding>,
+) -> Option<ExtractedMeta> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let stream_info = if original_format == OriginalFormat::Flac {
+        let info = read_flac_stream_info(&mut file);
+        file.seek(SeekFrom::Start(0)).ok()?;
+        info
+    } else {
+        None
+    };
 
     let mut hint = Hint::new();
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
@@ -220,7 +1071,7 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
 
     let mut raw_tags: Vec<(String, String)> = Vec::new();
     let mut cover: Option<CoverArt> = None;
-    let mut lyrics: Option<String> = None;
+    let mut lyrics: Vec<(String, String)> = Vec::new();
 
     let mut title: Option<String> = None;
     let mut artist: Option<String> = None;
@@ -236,6 +1087,7 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
     let mut sample_rate: Option<u32> = None;
     let mut channels: Option<u16> = None;
     let mut codec: Option<String> = None;
+    let mut gapless: Option<furry_format::GaplessInfo> = None;
 
     // Track info (duration/sample_rate/channels/codec)
     if let Some(t) = probed
@@ -250,6 +1102,28 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
         if let (Some(frames), Some(sr)) = (t.codec_params.n_frames, t.codec_params.sample_rate) {
             duration_ms = Some(((frames as f64 / sr as f64) * 1000.0) as u64);
         }
+        // 未启用 gapless 时 n_frames 包含 delay 与 padding
+        let delay = t.codec_params.delay.unwrap_or(0);
+        let padding = t.codec_params.padding.unwrap_or(0);
+        if delay != 0 || padding != 0 {
+            gapless = Some(furry_format::GaplessInfo {
+                delay,
+                padding,
+                valid_frames: t
+                    .codec_params
+                    .n_frames
+                    .and_then(|n| n.checked_sub(delay as u64 + padding as u64)),
+            });
+        }
+    }
+
+    // FLAC 以 STREAMINFO 为准，探测结果只作后备
+    if let Some(info) = stream_info {
+        sample_rate = Some(info.sample_rate);
+        channels = Some(info.channels);
+        if let Some(ms) = info.duration_ms() {
+            duration_ms = Some(ms);
+        }
     }
 
     // Tags/visuals from both metadata blocks (best-effort)
@@ -259,7 +1133,7 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
                 .std_key
                 .map(|k| format!("{:?}", k))
                 .unwrap_or_else(|| tag.key.to_string());
-            let val = meta_value_to_string(&tag.value);
+            let val = meta_value_to_string(&tag.value, tag_encoding);
             raw_tags.push((key.clone(), val.clone()));
 
             match tag.std_key {
@@ -291,7 +1165,15 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
                     year = year.or_else(|| parse_year(&val));
                 }
                 Some(StandardTagKey::Lyrics) => {
-                    lyrics.get_or_insert(val);
+                    // ID3 USLT 的语言编码在 key 中，如 `USLT!eng`
+                    let lang = tag
+                        .key
+                        .split_once('!')
+                        .and_then(|(_, lang)| furry_format::sanitize_lang(lang))
+                        .unwrap_or_else(|| furry_format::LYRICS_LANG_UNDETERMINED.to_string());
+                    if !lyrics.iter().any(|(l, _)| *l == lang) {
+                        lyrics.push((lang, val));
+                    }
                 }
                 _ => {}
             };
@@ -328,9 +1210,18 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
         }
     }
 
+    // AAC 等没有 LAME 头的格式：iTunes 将 delay/padding 写在 iTunSMPB 注释中
+    if gapless.is_none() {
+        gapless = raw_tags
+            .iter()
+            .filter(|(key, _)| key.to_ascii_lowercase().ends_with("itunsmpb"))
+            .find_map(|(_, value)| furry_format::GaplessInfo::from_itunsmpb(value))
+            .filter(|info| !info.is_empty());
+    }
+
     let tags = TagsJsonV1 {
-        schema: "furry.tags.v1",
-        original_format: format!("{:?}", original_format),
+        schema: TAGS_SCHEMA_V1.to_string(),
+        original_format: original_format.to_string(),
         title,
         artist,
         album,
@@ -347,11 +1238,11 @@ fn extract_meta_from_path(path: &Path, original_format: OriginalFormat) -> Optio
         raw: raw_tags,
     };
 
-    let tags_json = serde_json::to_string(&tags).ok();
     Some(ExtractedMeta {
-        tags_json,
+        tags,
         cover,
         lyrics,
+        gapless,
     })
 }
 
@@ -361,29 +1252,119 @@ fn parse_year(s: &str) -> Option<i32> {
     digits.parse().ok()
 }
 
-fn meta_value_to_string(v: &MetaValue) -> String {
+fn meta_value_to_string(v: &MetaValue, legacy: Option<&'static encoding_rs::Encoding>) -> String {
     match v {
         MetaValue::Binary(b) => format!("(binary:{} bytes)", b.len()),
         MetaValue::Boolean(b) => b.to_string(),
         MetaValue::Float(f) => f.to_string(),
         MetaValue::Flag => "true".to_string(),
         MetaValue::SignedInt(i) => i.to_string(),
-        MetaValue::String(s) => s.to_string(),
+        MetaValue::String(s) => repair_tag_text(s, legacy),
         MetaValue::UnsignedInt(u) => u.to_string(),
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Cursor;
+/// 修复被当作 Latin-1 解析的标签文本
+///
+/// symphonia 把 ID3 Latin-1 帧逐字节映射为 U+0000..=U+00FF。若文本全部落在该范围内且含
+/// 非 ASCII 字符，还原出原始字节：是合法 UTF-8 则按 UTF-8，否则按 `legacy` 无错误解码，
+/// 两者都不成立时保持原样（真正的 Latin-1 文本如 "café" 不受影响）。
+fn repair_tag_text(s: &str, legacy: Option<&'static encoding_rs::Encoding>) -> String {
+    if s.is_ascii() || s.chars().any(|c| c as u32 > 0xFF) {
+        return s.to_string();
+    }
+    let bytes: Vec<u8> = s.chars().map(|c| c as u8).collect();
+    if let Ok(utf8) = String::from_utf8(bytes.clone()) {
+        return utf8;
+    }
+    legacy
+        .and_then(|encoding| {
+            encoding
+                .decode_without_bom_handling_and_without_replacement(&bytes)
+                .map(|text| text.into_owned())
+        })
+        .unwrap_or_else(|| s.to_string())
+}
 
-    #[test]
-    fn test_pack_unpack_roundtrip() {
-        let master_key = MasterKey::default_key();
-        let original_data = b"This is fake MP3 audio data for testing purposes. ".repeat(100);
+/// FLAC STREAMINFO 中与时长相关的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FlacStreamInfo {
+    sample_rate: u32,
+    channels: u16,
+    /// 0 表示编码器未写入
+    total_samples: u64,
+}
 
-        // Pack
+impl FlacStreamInfo {
+    fn duration_ms(&self) -> Option<u64> {
+        if self.total_samples == 0 || self.sample_rate == 0 {
+            return None;
+        }
+        Some(self.total_samples * 1000 / self.sample_rate as u64)
+    }
+}
+
+/// 读取 FLAC 的 STREAMINFO 块（跳过前置 ID3v2）；不是 FLAC 或块损坏时返回 `None`
+fn read_flac_stream_info<R: Read>(reader: &mut R) -> Option<FlacStreamInfo> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).ok()?;
+    if magic.starts_with(b"ID3") {
+        // ID3v2：10 字节头，长度为 4 字节 syncsafe 整数，可选 10 字节尾
+        let mut rest = [0u8; 6];
+        reader.read_exact(&mut rest).ok()?;
+        let size = rest[2..6]
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 7) | (b & 0x7F) as u64);
+        let footer = if rest[1] & 0x10 != 0 { 10 } else { 0 };
+        std::io::copy(&mut reader.take(size + footer), &mut std::io::sink()).ok()?;
+        reader.read_exact(&mut magic).ok()?;
+    }
+    if &magic != b"fLaC" {
+        return None;
+    }
+
+    // STREAMINFO 必须是第一个元数据块，固定 34 字节
+    let mut block_header = [0u8; 4];
+    reader.read_exact(&mut block_header).ok()?;
+    let block_len = u32::from_be_bytes([0, block_header[1], block_header[2], block_header[3]]);
+    if block_header[0] & 0x7F != 0 || block_len < 34 {
+        return None;
+    }
+    let mut body = [0u8; 34];
+    reader.read_exact(&mut body).ok()?;
+
+    // 采样率 20 bit | 声道数-1 3 bit | 位深-1 5 bit | 总样本数 36 bit
+    let packed = u64::from_be_bytes(body[10..18].try_into().ok()?);
+    let sample_rate = (packed >> 44) as u32;
+    if sample_rate == 0 {
+        return None;
+    }
+    Some(FlacStreamInfo {
+        sample_rate,
+        channels: ((packed >> 41) & 0x7) as u16 + 1,
+        total_samples: packed & 0xF_FFFF_FFFF,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// 篡改头部后重算 CRC，模拟能改写 CRC 的攻击者
+    fn refresh_header_crc(data: &mut [u8]) {
+        let len = furry_format::FURRY_HEADER_LEN as usize;
+        let mut header = furry_format::FurryHeaderV1::read_from(&mut &data[..len]).unwrap();
+        header.header_crc32 = header.compute_crc32();
+        header.write_to(&mut &mut data[..len]).unwrap();
+    }
+
+    #[test]
+    fn test_pack_unpack_roundtrip() {
+        let master_key = MasterKey::default_key();
+        let original_data = b"This is fake MP3 audio data for testing purposes. ".repeat(100);
+
+        // Pack
         let mut input = Cursor::new(&original_data);
         let mut furry_output = Cursor::new(Vec::new());
 
@@ -414,6 +1395,35 @@ mod tests {
         assert_eq!(unpacked_output.into_inner(), original_data);
     }
 
+    #[test]
+    fn test_pack_stream_without_seek() {
+        let master_key = MasterKey::default_key();
+        let original_data = vec![0x5Au8; 5000];
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_stream_to_furry(
+            &mut original_data.as_slice(),
+            &mut furry_output,
+            OriginalFormat::Flac,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                padding: PaddingTarget::TotalSize(16 * 1024),
+                interleave_padding: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let furry_data = furry_output.into_inner();
+        assert_eq!(furry_data.len(), 16 * 1024);
+        let mut unpacked = Vec::new();
+        let format =
+            unpack_from_furry(&mut Cursor::new(furry_data), &mut unpacked, &master_key).unwrap();
+        assert_eq!(format, OriginalFormat::Flac);
+        assert_eq!(unpacked, original_data);
+    }
+
     #[test]
     fn test_pack_with_padding() {
         let master_key = MasterKey::default_key();
@@ -430,9 +1440,10 @@ mod tests {
             &master_key,
             &PackOptions {
                 chunk_size: 1024,
-                padding_bytes: 10000, // 添加 10KB padding
+                padding: PaddingTarget::Bytes(10000), // 添加 10KB padding
                 padding_chunk_size: 2000,
                 include_meta: true,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -450,4 +1461,1729 @@ mod tests {
 
         assert_eq!(unpacked_output.into_inner(), original_data);
     }
+
+    #[test]
+    fn test_pack_with_cover_sidecar() {
+        let master_key = MasterKey::default_key();
+        let cover_path = std::env::temp_dir().join(format!(
+            "furry_test_cover_sidecar_{}.png",
+            std::process::id()
+        ));
+        let mut cover = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        cover.extend_from_slice(b"fake png body");
+        std::fs::write(&cover_path, &cover).unwrap();
+
+        let mut input = Cursor::new(&b"audio"[..]);
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut input,
+            &mut furry_output,
+            None,
+            OriginalFormat::Flac,
+            &master_key,
+            &PackOptions {
+                cover_path: Some(cover_path.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        std::fs::remove_file(&cover_path).unwrap();
+
+        let mut reader =
+            FurryReader::open(Cursor::new(furry_output.into_inner()), &master_key).unwrap();
+        let payload = reader
+            .read_latest_meta(MetaKind::CoverArt)
+            .unwrap()
+            .unwrap();
+        let mut expected = b"image/png\0".to_vec();
+        expected.extend_from_slice(&cover);
+        assert_eq!(payload, expected);
+        assert_eq!(reader.producer().as_deref(), Some(PRODUCER));
+    }
+
+    #[test]
+    fn test_validate_format_detects_mismatch() {
+        // 最小 16-bit 单声道 PCM WAV
+        let samples = [0u8; 800];
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes()); // channels
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&samples);
+
+        let path = std::env::temp_dir().join(format!(
+            "furry_test_validate_format_{}.mp3",
+            std::process::id()
+        ));
+        std::fs::write(&path, &wav).unwrap();
+        let as_wav = validate_format(&path, OriginalFormat::Wav);
+        let as_mp3 = validate_format(&path, OriginalFormat::Mp3);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(as_wav);
+        assert!(!as_mp3);
+    }
+
+    #[test]
+    fn test_create_output_policies() {
+        let dir =
+            std::env::temp_dir().join(format!("furry_test_create_output_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("song.furry");
+        std::fs::write(&path, b"existing").unwrap();
+
+        let fail = create_output(&path, OverwritePolicy::Fail);
+        assert!(matches!(fail, Err(ConverterError::OutputExists(_))));
+        let (_, renamed) = create_output(&path, OverwritePolicy::Rename).unwrap();
+        assert_eq!(renamed, dir.join("song (1).furry"));
+        let (_, renamed) = create_output(&path, OverwritePolicy::Rename).unwrap();
+        assert_eq!(renamed, dir.join("song (2).furry"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"existing");
+        let (_, same) = create_output(&path, OverwritePolicy::Overwrite).unwrap();
+        assert_eq!(same, path);
+        let truncated = std::fs::metadata(&path).unwrap().len();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(truncated, 0);
+    }
+
+    #[test]
+    fn test_finish_output_syncs_packed_file() {
+        let master_key = MasterKey::default_key();
+        let original_data = b"synced audio ".repeat(200);
+        let path = std::env::temp_dir().join(format!(
+            "furry_test_sync_on_finish_{}.furry",
+            std::process::id()
+        ));
+        let options = PackOptions {
+            chunk_size: 1024,
+            overwrite: OverwritePolicy::Overwrite,
+            sync_on_finish: true,
+            ..Default::default()
+        };
+
+        let (mut output, _) = create_output(&path, options.overwrite).unwrap();
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &options,
+        )
+        .unwrap();
+        finish_output(&mut output, &options).unwrap();
+        drop(output);
+
+        let mut unpacked = Cursor::new(Vec::new());
+        let mut input = std::fs::File::open(&path).unwrap();
+        unpack_from_furry(&mut input, &mut unpacked, &master_key).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(unpacked.into_inner(), original_data);
+    }
+
+    #[test]
+    fn test_repair_latin1_tag_text() {
+        let as_latin1 = |bytes: &[u8]| bytes.iter().map(|&b| b as char).collect::<String>();
+        let sjis = encoding_rs::Encoding::for_label(b"shift_jis");
+
+        // Latin-1 帧里的 UTF-8
+        assert_eq!(repair_tag_text(&as_latin1("日本".as_bytes()), None), "日本");
+        // Latin-1 帧里的 Shift-JIS：需要显式指定编码
+        let raw = as_latin1(&[0x93, 0xFA, 0x96, 0x7B]);
+        assert_eq!(repair_tag_text(&raw, sjis), "日本");
+        assert_eq!(repair_tag_text(&raw, None), raw);
+        // 真正的 Latin-1 与已是 Unicode 的文本不变
+        assert_eq!(repair_tag_text("café", sjis), "café");
+        assert_eq!(repair_tag_text("日本", sjis), "日本");
+    }
+
+    #[test]
+    fn test_tags_override_merges_and_validates() {
+        let master_key = MasterKey::default_key();
+        let extracted = TagsJsonV1 {
+            schema: TAGS_SCHEMA_V1.to_string(),
+            title: Some("Embedded".to_string()),
+            artist: Some("Artist".to_string()),
+            raw: vec![("TXXX".into(), "a".into()), ("TPUB".into(), "b".into())],
+            ..Default::default()
+        };
+        let tags_override = TagsJsonV1::from_json(
+            br#"{"schema":"furry.tags.v1","title":"Authoritative","year":2024,"raw":[["TXXX","c"]]}"#,
+        )
+        .unwrap();
+
+        // 覆盖值优先，未设置的字段回退到提取值
+        let merged = tags_override.merge_over(Some(extracted));
+        assert_eq!(merged.title.as_deref(), Some("Authoritative"));
+        assert_eq!(merged.artist.as_deref(), Some("Artist"));
+        assert_eq!(merged.year, Some(2024));
+        assert_eq!(
+            merged.raw,
+            vec![("TPUB".into(), "b".into()), ("TXXX".into(), "c".into())]
+        );
+
+        // 流式封装没有提取值，写入的就是覆盖值
+        let pack = |tags_override: TagsJsonV1| {
+            let mut output = Cursor::new(Vec::new());
+            pack_stream_to_furry(
+                &mut [0u8; 1000].as_slice(),
+                &mut output,
+                OriginalFormat::Flac,
+                &master_key,
+                &PackOptions {
+                    tags_override: Some(tags_override),
+                    ..Default::default()
+                },
+            )
+            .map(|_| output.into_inner())
+        };
+        let packed = pack(tags_override).unwrap();
+        let mut reader = FurryReader::open(Cursor::new(packed), &master_key).unwrap();
+        let data = reader.read_latest_meta(MetaKind::Tags).unwrap().unwrap();
+        let written = TagsJsonV1::from_json(&data).unwrap();
+        assert_eq!(written.title.as_deref(), Some("Authoritative"));
+        assert_eq!(written.original_format, OriginalFormat::Flac.to_string());
+
+        let bad = TagsJsonV1 {
+            schema: "furry.tags.v9".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(pack(bad), Err(ConverterError::InvalidMeta(_))));
+        assert!(TagsJsonV1::from_json(b"[]").is_err());
+    }
+
+    fn flac_stream_info_header(sample_rate: u32, channels: u8, total_samples: u64) -> Vec<u8> {
+        let packed = ((sample_rate as u64) << 44)
+            | (((channels - 1) as u64) << 41)
+            | (15u64 << 36)
+            | total_samples;
+        let mut data = b"fLaC".to_vec();
+        data.extend_from_slice(&[0x80, 0, 0, 34]);
+        data.extend_from_slice(&[0u8; 10]);
+        data.extend_from_slice(&packed.to_be_bytes());
+        data.extend_from_slice(&[0u8; 16]);
+        data
+    }
+
+    #[test]
+    fn test_flac_stream_info() {
+        let data = flac_stream_info_header(44100, 2, 44100 * 90 + 22050);
+        let info = read_flac_stream_info(&mut data.as_slice()).unwrap();
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.duration_ms(), Some(90_500));
+
+        // 前置 ID3v2 标签
+        let mut tagged = b"ID3\x04\x00\x00\x00\x00\x01\x00".to_vec();
+        tagged.extend_from_slice(&[0u8; 128]);
+        tagged.extend_from_slice(&data);
+        assert_eq!(read_flac_stream_info(&mut tagged.as_slice()), Some(info));
+
+        // 未知总样本数：时长交给探测结果
+        let unknown = flac_stream_info_header(48000, 1, 0);
+        let info = read_flac_stream_info(&mut unknown.as_slice()).unwrap();
+        assert_eq!(info.duration_ms(), None);
+
+        assert_eq!(read_flac_stream_info(&mut &b"OggS\0\0\0\0"[..]), None);
+    }
+
+    #[test]
+    fn test_gapless_info_payload() {
+        let info = furry_format::GaplessInfo::from_itunsmpb(
+            " 00000000 00000840 000001CA 00000000003F31F6 00000000 00000000",
+        )
+        .unwrap();
+        assert_eq!(info.delay, 0x840);
+        assert_eq!(info.padding, 0x1CA);
+        assert_eq!(info.valid_frames, Some(0x3F31F6));
+        assert_eq!(
+            furry_format::GaplessInfo::parse(&info.to_bytes()),
+            Some(info)
+        );
+        assert_eq!(furry_format::GaplessInfo::parse(b"delay=576\n"), None);
+    }
+
+    #[test]
+    fn test_verify_roundtrip_reports_first_difference() {
+        let master_key = MasterKey::default_key();
+        let dir = std::env::temp_dir().join(format!(
+            "furry_test_verify_roundtrip_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("song.mp3");
+        let furry = dir.join("song.furry");
+        let audio: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&original, &audio).unwrap();
+
+        let options = PackOptions {
+            chunk_size: 1024,
+            include_meta: false,
+            ..Default::default()
+        };
+        let (mut output, _) = create_output(&furry, OverwritePolicy::Overwrite).unwrap();
+        pack_to_furry(
+            &mut std::fs::File::open(&original).unwrap(),
+            &mut output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &options,
+        )
+        .unwrap();
+        let identical = verify_roundtrip(&original, &furry, &master_key).unwrap();
+
+        let mut changed = audio.clone();
+        changed[3000] ^= 0xFF;
+        std::fs::write(&original, &changed).unwrap();
+        let differs = verify_roundtrip(&original, &furry, &master_key).unwrap();
+
+        changed.truncate(2048);
+        std::fs::write(&original, &changed).unwrap();
+        let shorter = verify_roundtrip(&original, &furry, &master_key).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(identical, None);
+        assert_eq!(differs, Some(3000));
+        assert_eq!(shorter, Some(2048));
+    }
+
+    #[test]
+    fn test_validate_quick_rejects_truncated_file() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![7u8; 4096]),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions::default(),
+        )
+        .unwrap();
+        let mut data = furry_output.into_inner();
+        assert!(furry_format::FurryHeaderV1::validate_quick(&mut Cursor::new(&data)).is_ok());
+
+        let mut truncated = data.clone();
+        truncated.truncate(data.len() - 1);
+        assert!(furry_format::FurryHeaderV1::validate_quick(&mut Cursor::new(&truncated)).is_err());
+
+        data[24] ^= 1; // file_id
+        assert!(matches!(
+            furry_format::FurryHeaderV1::validate_quick(&mut Cursor::new(&data)),
+            Err(furry_format::FormatError::HeaderCrcMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_truncated_file_reports_format_errors() {
+        let master_key = MasterKey::default_key();
+        let mut packed = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![9u8; 4000]),
+            &mut packed,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                include_meta: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let data = packed.into_inner();
+
+        for len in [0, 10, 95, 96, 120, 1500, data.len() - 20, data.len() - 1] {
+            let err = FurryReader::open(Cursor::new(&data[..len]), &master_key)
+                .err()
+                .unwrap();
+            assert!(
+                !matches!(err, furry_format::FormatError::Io(_)),
+                "len {len}: {err:?}"
+            );
+        }
+
+        // 打开后文件被截断（下载中断/被覆盖）
+        let path =
+            std::env::temp_dir().join(format!("furry_truncated_{}.furry", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let mut reader =
+            FurryReader::open(std::fs::File::open(&path).unwrap(), &master_key).unwrap();
+        let last = reader
+            .index
+            .audio_entries()
+            .into_iter()
+            .last()
+            .cloned()
+            .unwrap();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(last.file_offset + 50)
+            .unwrap();
+        let err = reader.read_chunk(&last).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(
+            err,
+            furry_format::FormatError::CorruptIndex("chunk record truncated")
+        ));
+    }
+
+    #[test]
+    fn test_overflowing_chunk_offsets_are_rejected() {
+        let master_key = MasterKey::default_key();
+        let mut packed = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![9u8; 4000]),
+            &mut packed,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                include_meta: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let data = packed.into_inner();
+        let first_audio_seq = |reader: &FurryReader<_>| reader.index.audio_entries()[0].chunk_seq;
+
+        // 索引可被任何持有默认密钥的人构造，偏移量相加不能溢出
+        let mut reader = FurryReader::open(Cursor::new(&data), &master_key).unwrap();
+        let first = first_audio_seq(&reader);
+        for entry in reader.index.entries.iter_mut() {
+            if entry.chunk_seq == first {
+                entry.file_offset = u64::MAX - 8;
+            }
+        }
+        assert!(!reader.audio_is_contiguous());
+        let err = reader
+            .for_each_audio_chunk(|_| Ok::<(), furry_format::FormatError>(()))
+            .unwrap_err();
+        assert!(matches!(err, furry_format::FormatError::CorruptIndex(_)));
+
+        let mut reader = FurryReader::open(Cursor::new(&data), &master_key).unwrap();
+        let first = first_audio_seq(&reader);
+        reader
+            .index
+            .entries
+            .retain(|e| e.chunk_type != furry_format::ChunkType::Audio || e.chunk_seq == first);
+        for entry in reader.index.entries.iter_mut() {
+            if entry.chunk_seq == first {
+                entry.file_offset = u64::MAX - 8;
+            }
+        }
+        assert!(reader.audio_is_contiguous());
+        let err = reader
+            .for_each_audio_chunk(|_| Ok::<(), furry_format::FormatError>(()))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            furry_format::FormatError::CorruptIndex("chunk record beyond end of file")
+        ));
+    }
+
+    #[test]
+    fn test_header_format_hint() {
+        let master_key = MasterKey::default_key();
+        let pack = |header_format_hint| {
+            let mut packed = Cursor::new(Vec::new());
+            pack_to_furry(
+                &mut Cursor::new(vec![1u8; 2000]),
+                &mut packed,
+                None,
+                OriginalFormat::Flac,
+                &master_key,
+                &PackOptions {
+                    header_format_hint,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            packed.into_inner()
+        };
+        let quick = |data: &[u8]| {
+            furry_format::FurryHeaderV1::validate_quick(&mut Cursor::new(data))
+                .unwrap()
+                .original_format_hint()
+        };
+
+        let hinted = pack(true);
+        assert_eq!(quick(&hinted), Some(OriginalFormat::Flac));
+        assert_eq!(quick(&pack(false)), None);
+        // 权威值仍在索引中
+        let reader = FurryReader::open(Cursor::new(&hinted), &master_key).unwrap();
+        assert_eq!(reader.index.header.original_format, OriginalFormat::Flac);
+    }
+
+    #[test]
+    fn test_fake_footer_is_ignored() {
+        let master_key = MasterKey::default_key();
+        let audio = vec![3u8; 3000];
+        let options = PackOptions {
+            chunk_size: 1024,
+            fake_footer_len: 777,
+            padding: PaddingTarget::TotalSize(8192),
+            ..Default::default()
+        };
+        let mut packed = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(audio.clone()),
+            &mut packed,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &options,
+        )
+        .unwrap();
+        let data = packed.into_inner();
+        assert_eq!(data.len(), 8192);
+        let header = furry_format::FurryHeaderV1::validate_quick(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(header.fake_footer_len, 777);
+
+        let mut unpacked = Vec::new();
+        unpack_from_furry(&mut Cursor::new(&data), &mut unpacked, &master_key).unwrap();
+        assert_eq!(unpacked, audio);
+
+        // 未完成的文件：扫描器不应把 footer 当作 chunk
+        let reader = FurryReader::open(Cursor::new(&data), &master_key).unwrap();
+        let index_offset = reader.header.index_offset as usize;
+        let mut unfinalized = data[..index_offset].to_vec();
+        unfinalized.extend_from_slice(&data[data.len() - 777..]);
+        let mut header = reader.header.clone();
+        header.index_offset = 0;
+        header.header_crc32 = 0;
+        header.write_to(&mut &mut unfinalized[..96]).unwrap();
+        let recovered =
+            FurryReader::open_with_recovery(Cursor::new(&unfinalized), &master_key).unwrap();
+        assert_eq!(
+            recovered.index.audio_entries().len(),
+            reader.index.audio_entries().len()
+        );
+    }
+
+    #[test]
+    fn test_deterministic_seed_reproduces_bytes() {
+        let master_key = MasterKey::default_key();
+        let pack = |seed| {
+            let options = PackOptions {
+                chunk_size: 512,
+                padding: PaddingTarget::Bytes(3000),
+                interleave_padding: true,
+                deterministic_seed: seed,
+                ..Default::default()
+            };
+            let mut output = Cursor::new(Vec::new());
+            pack_to_furry(
+                &mut Cursor::new(vec![9u8; 4000]),
+                &mut output,
+                None,
+                OriginalFormat::Mp3,
+                &master_key,
+                &options,
+            )
+            .unwrap();
+            output.into_inner()
+        };
+
+        assert_eq!(pack(Some(42)), pack(Some(42)));
+        assert_ne!(pack(Some(42)), pack(Some(43)));
+        assert_ne!(pack(None), pack(None));
+    }
+
+    #[test]
+    fn test_space_report_sorts_by_padding() {
+        let master_key = MasterKey::default_key();
+        let dir =
+            std::env::temp_dir().join(format!("furry_test_space_report_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, padding) in [("light.furry", 1000), ("heavy.furry", 50_000)] {
+            let options = PackOptions {
+                padding: PaddingTarget::Bytes(padding),
+                include_meta: false,
+                ..Default::default()
+            };
+            let (mut output, _) =
+                create_output(&dir.join(name), OverwritePolicy::Overwrite).unwrap();
+            pack_to_furry(
+                &mut Cursor::new(vec![1u8; 2000]),
+                &mut output,
+                None,
+                OriginalFormat::Mp3,
+                &master_key,
+                &options,
+            )
+            .unwrap();
+        }
+        let report = space_report(&dir, &master_key).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.files.len(), 2);
+        assert!(report.files[0].path.ends_with("heavy.furry"));
+        assert!(report.files[0].padding_bytes > 50_000);
+        let usage = &report.files[1];
+        assert_eq!(
+            usage.file_bytes,
+            usage.audio_bytes + usage.padding_bytes + usage.meta_bytes + usage.overhead_bytes
+        );
+    }
+
+    #[test]
+    fn test_meta_written_after_audio_is_found() {
+        let master_key = MasterKey::default_key();
+        let original_data = vec![0x42u8; 4096];
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut reader =
+            FurryReader::open(Cursor::new(furry_output.into_inner()), &master_key).unwrap();
+        let last_audio_offset = reader
+            .index
+            .audio_entries()
+            .iter()
+            .map(|e| e.file_offset)
+            .max()
+            .unwrap();
+        let source_entry = reader.index.meta_entries_by_kind(MetaKind::SourceInfo)[0].clone();
+        assert!(source_entry.file_offset > last_audio_offset);
+
+        let info = reader.read_source_info().unwrap().unwrap();
+        assert_eq!(
+            info.audio_digest,
+            Some(reader.audio_plaintext_digest().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_custom_meta_kind_roundtrip() {
+        let master_key = MasterKey::default_key();
+        let custom = MetaKind::Custom(0x1000);
+        let mut output = Cursor::new(Vec::new());
+        let mut writer =
+            FurryWriter::create(&mut output, &master_key, OriginalFormat::Mp3).unwrap();
+        writer.write_meta_chunk(custom, b"first", 0).unwrap();
+        writer.write_meta_chunk(MetaKind::Tags, b"{}", 0).unwrap();
+        writer.write_meta_chunk(custom, b"second", 0).unwrap();
+        // 与内置 id 冲突的自定义类型被拒绝，不会遮蔽 Tags
+        assert!(matches!(
+            writer.write_meta_chunk(MetaKind::Custom(3), b"not tags", 0),
+            Err(furry_format::FormatError::ReservedMetaKind(3))
+        ));
+        writer.write_audio_chunk(b"audio", 0).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = FurryReader::open(Cursor::new(output.into_inner()), &master_key).unwrap();
+        assert_eq!(
+            reader.read_all_meta(custom).unwrap(),
+            vec![b"first".to_vec(), b"second".to_vec()]
+        );
+        assert_eq!(
+            reader.read_latest_meta(custom).unwrap(),
+            Some(b"second".to_vec())
+        );
+        assert!(reader
+            .read_all_meta(MetaKind::Custom(0x1001))
+            .unwrap()
+            .is_empty());
+        assert!(reader.index.meta_size_by_kind().contains_key(&custom));
+        // seq 按写入顺序分配
+        assert_eq!(
+            reader.read_chunk_by_seq(2).unwrap(),
+            Some(b"second".to_vec())
+        );
+        assert_eq!(
+            reader.read_chunk_by_seq(3).unwrap(),
+            Some(b"audio".to_vec())
+        );
+        assert_eq!(reader.read_chunk_by_seq(99).unwrap(), None);
+
+        // 交出底层流后重新组装，无需重新解析
+        let (inner, header, keys, index) = reader.into_parts();
+        let mut reader = FurryReader::from_parts(inner, header, keys, index).unwrap();
+        assert_eq!(
+            reader.read_latest_meta(custom).unwrap(),
+            Some(b"second".to_vec())
+        );
+        assert_eq!(
+            reader.read_latest_meta(MetaKind::Tags).unwrap(),
+            Some(b"{}".to_vec())
+        );
+        assert_eq!(MetaKind::from_u16(0x1000), custom);
+        assert_eq!(MetaKind::custom(0x1000), Some(custom));
+        assert_eq!(MetaKind::custom(0), None);
+        assert_eq!(MetaKind::custom(3), None);
+        assert_eq!(custom.to_string(), "custom_4096");
+    }
+
+    #[test]
+    fn test_compressed_meta_roundtrip_and_size_cap() {
+        let master_key = MasterKey::default_key();
+        let tags = format!(
+            r#"{{"schema":"furry.tags.v1","comment":"{}"}}"#,
+            "la ".repeat(20_000)
+        );
+        let lyrics = furry_format::encode_lyrics_payload("ja", &"[00:01.00]ラ\n".repeat(5_000));
+        // 解压后超过歌词上限的压缩炸弹
+        let bomb = vec![0u8; furry_format::MAX_LYRICS_BYTES as usize + 1];
+
+        let mut output = Cursor::new(Vec::new());
+        let mut writer =
+            FurryWriter::create(&mut output, &master_key, OriginalFormat::Mp3).unwrap();
+        writer
+            .write_meta_chunk_compressed(MetaKind::Tags, tags.as_bytes())
+            .unwrap();
+        writer
+            .write_meta_chunk_compressed(MetaKind::Lyrics, &lyrics)
+            .unwrap();
+        writer
+            .write_meta_chunk_compressed(MetaKind::Lyrics, &bomb)
+            .unwrap();
+        writer.write_audio_chunk(b"audio", 0).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = FurryReader::open(Cursor::new(output.into_inner()), &master_key).unwrap();
+        let stored = reader.index.meta_entries_by_kind(MetaKind::Tags)[0].plain_len;
+        assert!((stored as usize) < tags.len() / 10);
+        assert_eq!(
+            reader.read_latest_meta(MetaKind::Tags).unwrap(),
+            Some(tags.into_bytes())
+        );
+        // 炸弹按解压后大小被跳过，先前的歌词仍可读
+        assert_eq!(reader.read_latest_meta(MetaKind::Lyrics).unwrap(), None);
+        let read = reader.read_lyrics().unwrap();
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0], furry_format::parse_lyrics_payload(&lyrics));
+        assert_eq!(
+            reader.read_all_meta(MetaKind::Lyrics).unwrap(),
+            vec![lyrics]
+        );
+    }
+
+    #[test]
+    fn test_xor_meta_flag_fails_closed() {
+        let master_key = MasterKey::default_key();
+        let mut writer =
+            FurryWriter::create(Cursor::new(Vec::new()), &master_key, OriginalFormat::Mp3).unwrap();
+        writer
+            .write_meta_chunk(
+                MetaKind::Tags,
+                b"obfuscated",
+                furry_format::chunk_flags::FLAG_META_XOR,
+            )
+            .unwrap();
+        writer.write_audio_chunk(b"audio", 0).unwrap();
+        let packed = writer.finish().unwrap().into_inner();
+
+        let mut reader = FurryReader::open(Cursor::new(packed), &master_key).unwrap();
+        let entry = reader.index.meta_entries_by_kind(MetaKind::Tags)[0].clone();
+        assert!(matches!(
+            reader.read_chunk(&entry),
+            Err(furry_format::FormatError::UnsupportedChunkFlag(0x01))
+        ));
+        assert!(matches!(
+            reader.read_latest_meta(MetaKind::Tags),
+            Err(furry_format::FormatError::UnsupportedChunkFlag(0x01))
+        ));
+        // 音频不受影响
+        let mut audio = Vec::new();
+        reader
+            .for_each_audio_chunk(|data| {
+                audio.extend_from_slice(data);
+                Ok::<(), furry_format::FormatError>(())
+            })
+            .unwrap();
+        assert_eq!(audio, b"audio");
+    }
+
+    #[test]
+    fn test_open_with_candidate_keys() {
+        let old_key = MasterKey::new([0x11; 32]);
+        let new_key = MasterKey::new([0x22; 32]);
+        let stranger = MasterKey::new([0x33; 32]);
+        let mut packed = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![7u8; 3000]),
+            &mut packed,
+            None,
+            OriginalFormat::Mp3,
+            &old_key,
+            &PackOptions {
+                index_mac: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let packed = packed.into_inner();
+
+        let mut reader =
+            FurryReader::open_with_keys_try(Cursor::new(&packed), &[&new_key, &old_key]).unwrap();
+        assert_eq!(reader.total_audio_bytes(), 3000);
+        assert!(reader.read_chunk_by_seq(0).unwrap().is_some());
+
+        let err = FurryReader::open_with_keys_try(Cursor::new(&packed), &[&new_key, &stranger]);
+        assert!(matches!(err, Err(furry_format::FormatError::NoMatchingKey)));
+        let err = FurryReader::open_with_keys_try(Cursor::new(&packed), &[]);
+        assert!(matches!(err, Err(furry_format::FormatError::NoMatchingKey)));
+        let too_many = vec![&stranger; furry_format::MAX_CANDIDATE_KEYS + 1];
+        let err = FurryReader::open_with_keys_try(Cursor::new(&packed), &too_many);
+        assert!(matches!(
+            err,
+            Err(furry_format::FormatError::TooManyCandidateKeys(9))
+        ));
+    }
+
+    #[test]
+    fn test_read_lyrics_by_language() {
+        let master_key = MasterKey::default_key();
+        let mut writer =
+            FurryWriter::create(Cursor::new(Vec::new()), &master_key, OriginalFormat::Mp3).unwrap();
+        // 旧文件：无语言前缀
+        writer
+            .write_meta_chunk(MetaKind::Lyrics, "legacy".as_bytes(), 0)
+            .unwrap();
+        for (lang, text) in [("eng", "hello"), ("ZHO", "你好"), ("eng", "hello v2")] {
+            let payload = furry_format::encode_lyrics_payload(lang, text);
+            writer
+                .write_meta_chunk(MetaKind::Lyrics, &payload, 0)
+                .unwrap();
+        }
+        let data = writer.finish().unwrap().into_inner();
+
+        let mut reader = FurryReader::open(Cursor::new(data), &master_key).unwrap();
+        let lyrics = reader.read_lyrics().unwrap();
+        assert_eq!(
+            lyrics,
+            vec![
+                ("und".to_string(), "legacy".to_string()),
+                ("zho".to_string(), "你好".to_string()),
+                ("eng".to_string(), "hello v2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pack_and_verify() {
+        let master_key = MasterKey::default_key();
+        let audio: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let options = PackOptions {
+            chunk_size: 1024,
+            ..Default::default()
+        };
+        let mut output = Cursor::new(Vec::new());
+        let report = pack_and_verify(
+            &mut Cursor::new(audio),
+            &mut output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &options,
+            true,
+        )
+        .unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.total_chunks, report.checked_chunks);
+    }
+
+    #[test]
+    fn test_unpack_progress_excludes_padding() {
+        let master_key = MasterKey::default_key();
+        let audio = vec![0x5Au8; 5000];
+        let options = PackOptions {
+            chunk_size: 1024,
+            padding: PaddingTarget::Bytes(4096),
+            ..Default::default()
+        };
+        let mut packed = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(audio.clone()),
+            &mut packed,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &options,
+        )
+        .unwrap();
+
+        // padding 追加在音频之后：走顺序读取路径
+        let reader = FurryReader::open(Cursor::new(packed.get_ref()), &master_key).unwrap();
+        assert!(reader.audio_is_contiguous());
+
+        let mut updates = Vec::new();
+        let mut output = Vec::new();
+        unpack_from_furry_with_progress(
+            &mut packed,
+            &mut output,
+            &master_key,
+            None,
+            |done, total| updates.push((done, total)),
+        )
+        .unwrap();
+        assert_eq!(output, audio);
+        assert_eq!(updates.len(), 5);
+        assert_eq!(updates.last(), Some(&(5000, 5000)));
+
+        let report = verify_furry(
+            &mut packed,
+            &master_key,
+            &VerifyOptions::default(),
+            |_, _| true,
+        )
+        .unwrap();
+        assert!(report.census.padding_chunks > 0);
+        assert!(report.census.padding_bytes >= 4096);
+        assert!(report.census.unknown_chunks.is_empty());
+    }
+
+    #[test]
+    fn test_verify_detects_corruption_and_resumes() {
+        let master_key = MasterKey::default_key();
+        let original_data = vec![0x5Au8; 4096];
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Wav,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut furry_data = furry_output.into_inner();
+
+        let options = VerifyOptions {
+            audio_only: true,
+            ..Default::default()
+        };
+        let report = verify_furry(
+            &mut Cursor::new(&furry_data),
+            &master_key,
+            &options,
+            |_, _| true,
+        )
+        .unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.total_chunks, 4);
+
+        // 取消后从中断处继续
+        let partial = verify_furry(
+            &mut Cursor::new(&furry_data),
+            &master_key,
+            &options,
+            |done, _| done < 2,
+        )
+        .unwrap();
+        assert!(partial.cancelled);
+        assert_eq!(partial.checked_chunks, 2);
+        let resumed = verify_furry(
+            &mut Cursor::new(&furry_data),
+            &master_key,
+            &VerifyOptions {
+                resume_from: partial.checked_chunks,
+                ..options.clone()
+            },
+            |_, _| true,
+        )
+        .unwrap();
+        assert!(resumed.is_ok());
+        assert_eq!(resumed.checked_chunks, 4);
+
+        // 篡改第一个 AUDIO chunk 的密文
+        let first_audio = FurryReader::open(Cursor::new(&furry_data), &master_key)
+            .unwrap()
+            .index
+            .audio_entries()[0]
+            .clone();
+        let first_ciphertext =
+            first_audio.file_offset as usize + furry_format::CHUNK_HEADER_LEN as usize;
+        furry_data[first_ciphertext] ^= 0xFF;
+        let report = verify_furry(
+            &mut Cursor::new(&furry_data),
+            &master_key,
+            &options,
+            |_, _| true,
+        )
+        .unwrap();
+        assert_eq!(report.failed_chunks, vec![first_audio.chunk_seq]);
+        assert_eq!(report.faults, vec![furry_format::ChunkFault::Unknown]);
+    }
+
+    #[test]
+    fn test_chunk_crc_localizes_corruption() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![0x5Au8; 4096]),
+            &mut furry_output,
+            None,
+            OriginalFormat::Wav,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                include_meta: false,
+                // 紧凑索引放不下 CRC，自动退回完整布局
+                compact_index: true,
+                chunk_crc: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut furry_data = furry_output.into_inner();
+
+        let reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        assert!(reader.index.has_chunk_crc());
+        let audio: Vec<_> = reader.index.audio_entries().into_iter().cloned().collect();
+        assert!(audio.iter().all(|e| e.reserved2 != 0));
+
+        // chunk 0：密文位翻转；chunk 1：chunk 头的 virtual_offset（AAD）被改动
+        furry_data[audio[0].file_offset as usize + furry_format::CHUNK_HEADER_LEN as usize] ^= 1;
+        furry_data[audio[1].file_offset as usize + 20] ^= 1;
+        let report = verify_furry(
+            &mut Cursor::new(&furry_data),
+            &master_key,
+            &VerifyOptions::default(),
+            |_, _| true,
+        )
+        .unwrap();
+        assert_eq!(
+            report.failed_chunks,
+            vec![audio[0].chunk_seq, audio[1].chunk_seq]
+        );
+        assert_eq!(
+            report.faults,
+            vec![
+                furry_format::ChunkFault::Ciphertext,
+                furry_format::ChunkFault::Header
+            ]
+        );
+    }
+
+    #[test]
+    fn test_privacy_preset_interleaves_padding_without_meta() {
+        let master_key = MasterKey::default_key();
+        // 默认 256 KiB 的 AUDIO chunk：8 个
+        let original_data = vec![0x11u8; 2 * 1024 * 1024];
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions::privacy(),
+        )
+        .unwrap();
+        let furry_data = furry_output.into_inner();
+
+        let reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        assert!(reader.index.meta_entries().is_empty());
+        let padding: u64 = reader
+            .index
+            .entries
+            .iter()
+            .filter(|e| e.chunk_type == furry_format::ChunkType::Padding)
+            .map(|e| e.plain_len as u64)
+            .sum();
+        assert!((256 * 1024..=1024 * 1024).contains(&padding));
+
+        // 按文件位置排列，至少一个 PADDING 前后都有 AUDIO
+        let mut entries = reader.index.entries.clone();
+        entries.sort_by_key(|e| e.file_offset);
+        let is_audio =
+            |e: &furry_format::IndexEntryV1| e.chunk_type == furry_format::ChunkType::Audio;
+        let first_audio = entries.iter().position(is_audio).unwrap();
+        let last_audio = entries.iter().rposition(is_audio).unwrap();
+        assert!(entries[first_audio..last_audio]
+            .iter()
+            .any(|e| e.chunk_type == furry_format::ChunkType::Padding));
+
+        let mut unpacked = Vec::new();
+        unpack_from_furry(&mut Cursor::new(&furry_data), &mut unpacked, &master_key).unwrap();
+        assert_eq!(unpacked, original_data);
+    }
+
+    #[test]
+    #[cfg(not(feature = "insecure-plaintext"))]
+    fn test_plaintext_flag_rejected_by_default() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![1u8; 100]),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions::default(),
+        )
+        .unwrap();
+        let mut data = furry_output.into_inner();
+        // flags 位于 magic(8) + version(2) + header_size(2) 之后
+        data[12] |= furry_format::FLAG_PLAINTEXT as u8;
+        refresh_header_crc(&mut data);
+        assert!(matches!(
+            FurryReader::open(Cursor::new(data), &master_key),
+            Err(furry_format::FormatError::PlaintextNotEnabled)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "insecure-plaintext")]
+    fn test_plaintext_debug_roundtrip() {
+        let master_key = MasterKey::default_key();
+        let original_data = b"plain audio bytes visible in a hex editor".repeat(10);
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                encrypt: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let data = furry_output.into_inner();
+        assert!(data
+            .windows(original_data.len())
+            .any(|w| w == &original_data[..]));
+
+        let mut unpacked = Vec::new();
+        unpack_from_furry(&mut Cursor::new(data), &mut unpacked, &master_key).unwrap();
+        assert_eq!(unpacked, original_data);
+    }
+
+    #[test]
+    fn test_unknown_aead_id_rejected() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![1u8; 100]),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions::default(),
+        )
+        .unwrap();
+        let mut data = furry_output.into_inner();
+        // aead_id 位于头部偏移 58
+        data[58..60].copy_from_slice(&9u16.to_le_bytes());
+        assert!(matches!(
+            FurryReader::open(Cursor::new(data), &master_key),
+            Err(furry_format::FormatError::UnsupportedAead(9))
+        ));
+    }
+
+    #[test]
+    fn test_rechunk_preserves_audio_and_meta() {
+        let master_key = MasterKey::default_key();
+        let original_data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Ogg,
+            &master_key,
+            &PackOptions {
+                chunk_size: 300,
+                padding: PaddingTarget::Bytes(2048),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut rechunked = Cursor::new(Vec::new());
+        rechunk(
+            &mut Cursor::new(furry_output.into_inner()),
+            &mut rechunked,
+            &master_key,
+            4096,
+        )
+        .unwrap();
+        let rechunked = rechunked.into_inner();
+
+        let mut reader = FurryReader::open(Cursor::new(&rechunked), &master_key).unwrap();
+        assert_eq!(reader.index.audio_entries().len(), 3);
+        assert_eq!(reader.index.header.audio_stream_len, 10_000);
+        let padding: u64 = reader
+            .index
+            .entries
+            .iter()
+            .filter(|e| e.chunk_type == furry_format::ChunkType::Padding)
+            .map(|e| e.plain_len as u64)
+            .sum();
+        assert_eq!(padding, 2048);
+        let info = reader.read_source_info().unwrap().unwrap();
+        assert_eq!(
+            info.audio_digest,
+            Some(reader.audio_plaintext_digest().unwrap())
+        );
+
+        let mut unpacked = Vec::new();
+        let format =
+            unpack_from_furry(&mut Cursor::new(&rechunked), &mut unpacked, &master_key).unwrap();
+        assert_eq!(format, OriginalFormat::Ogg);
+        assert_eq!(unpacked, original_data);
+    }
+
+    #[test]
+    fn test_long_salt_roundtrip() {
+        let master_key = MasterKey::default_key();
+        let original_data = b"long salt audio ".repeat(64);
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                long_salt: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let furry_data = furry_output.into_inner();
+
+        let reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        assert_eq!(reader.header.kdf_id, furry_format::KDF_HKDF_SHA256_SALT32);
+        assert_eq!(reader.kdf(), furry_format::KdfAlgorithm::HkdfSha256Salt32);
+        assert_eq!(reader.aead(), furry_format::AeadAlgorithm::Aes256Gcm);
+        assert_eq!(reader.header.kdf_salt().unwrap().len(), 32);
+
+        let mut unpacked = Vec::new();
+        unpack_from_furry(&mut Cursor::new(&furry_data), &mut unpacked, &master_key).unwrap();
+        assert_eq!(unpacked, original_data);
+    }
+
+    #[test]
+    fn test_index_mac_detects_header_tampering() {
+        let master_key = MasterKey::default_key();
+        let original_data = b"index mac audio ".repeat(64);
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                index_mac: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut furry_data = furry_output.into_inner();
+
+        let reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        assert!(reader.flags().has_index_mac());
+        let mut unpacked = Vec::new();
+        unpack_from_furry(&mut Cursor::new(&furry_data), &mut unpacked, &master_key).unwrap();
+        assert_eq!(unpacked, original_data);
+
+        // fake_header_len（偏移 16）不参与任何 chunk 的 AAD；CRC 可被重算，只有 MAC 能发现篡改
+        furry_data[16] ^= 1;
+        refresh_header_crc(&mut furry_data);
+        assert!(matches!(
+            FurryReader::open(Cursor::new(&furry_data), &master_key),
+            Err(furry_format::FormatError::IndexMacMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_meta_key_separates_meta_encryption() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_stream_to_furry(
+            &mut [0x11u8; 3000].as_slice(),
+            &mut furry_output,
+            OriginalFormat::Flac,
+            &master_key,
+            &PackOptions {
+                chunk_size: 1024,
+                meta_key: true,
+                tags_override: Some(TagsJsonV1 {
+                    schema: TAGS_SCHEMA_V1.to_string(),
+                    title: Some("Private".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let furry_data = furry_output.into_inner();
+
+        let mut reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        assert!(reader.flags().has_meta_key());
+        let data = reader.read_latest_meta(MetaKind::Tags).unwrap().unwrap();
+        assert_eq!(
+            TagsJsonV1::from_json(&data).unwrap().title.as_deref(),
+            Some("Private")
+        );
+
+        // META 只能用 meta_aead_key 解开，音频密钥不行
+        let entry = reader.index.meta_entries()[0].clone();
+        let record = reader.read_raw_chunk(&entry).unwrap();
+        let chunk_header = furry_format::ChunkRecordHeaderV1::read_from(&mut &record[..]).unwrap();
+        let body = &record[furry_format::CHUNK_HEADER_LEN as usize..];
+        let (ciphertext, tag) = body.split_at(entry.plain_len as usize);
+        let tag: [u8; furry_crypto::TAG_LEN] = tag.try_into().unwrap();
+        let aad = furry_crypto::build_aad_v1(
+            &reader.header.file_id,
+            reader.header.version,
+            reader.header.flags,
+            &chunk_header.to_bytes(),
+        );
+        let try_open = |key: &[u8; 32], prefix: &[u8; 4]| {
+            let nonce = furry_crypto::nonce_for_chunk(prefix, chunk_header.chunk_seq);
+            let mut buf = ciphertext.to_vec();
+            furry_crypto::decrypt_in_place_detached(key, &nonce, &aad, &mut buf, &tag)
+        };
+        assert!(try_open(&reader.keys.aead_key, &reader.keys.nonce_prefix).is_err());
+        assert!(try_open(&reader.keys.meta_aead_key, &reader.keys.meta_nonce_prefix).is_ok());
+
+        // rechunk 保留该标志
+        let mut rechunked = Cursor::new(Vec::new());
+        rechunk(
+            &mut Cursor::new(&furry_data),
+            &mut rechunked,
+            &master_key,
+            512,
+        )
+        .unwrap();
+        let mut reader =
+            FurryReader::open(Cursor::new(rechunked.into_inner()), &master_key).unwrap();
+        assert!(reader.flags().has_meta_key());
+        assert!(reader.read_latest_meta(MetaKind::Tags).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_compact_index_roundtrip() {
+        let master_key = MasterKey::default_key();
+        let original_data = b"compact index audio ".repeat(1000);
+        let pack = |compact_index| {
+            let mut furry_output = Cursor::new(Vec::new());
+            pack_to_furry(
+                &mut Cursor::new(&original_data),
+                &mut furry_output,
+                None,
+                OriginalFormat::Flac,
+                &master_key,
+                &PackOptions {
+                    chunk_size: 1024,
+                    include_meta: false,
+                    compact_index,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            furry_output.into_inner()
+        };
+        let full = pack(false);
+        let compact = pack(true);
+
+        let full_reader = FurryReader::open(Cursor::new(&full), &master_key).unwrap();
+        let reader = FurryReader::open(Cursor::new(&compact), &master_key).unwrap();
+        assert_ne!(
+            reader.index.header.flags & furry_format::INDEX_FLAG_COMPACT_AUDIO,
+            0
+        );
+        assert_eq!(
+            full_reader.header.index_total_len - reader.header.index_total_len,
+            (reader.index.entries.len()
+                * (furry_format::INDEX_ENTRY_LEN - furry_format::INDEX_COMPACT_ENTRY_LEN))
+                as u32
+        );
+        let offsets = |r: &FurryReader<_>| {
+            r.index
+                .entries
+                .iter()
+                .map(|e| (e.virtual_offset, e.plain_len))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(offsets(&reader), offsets(&full_reader));
+
+        // 惰性解析与一次性解析结果一致
+        for r in [&reader, &full_reader] {
+            let plain = r.index.to_bytes();
+            let (header, iter) = furry_format::IndexEntryIter::new(&plain).unwrap();
+            assert_eq!(header.entry_count as usize, r.index.entries.len());
+            let streamed: Vec<_> = iter
+                .of_type(furry_format::ChunkType::Audio)
+                .map(|e| e.unwrap())
+                .map(|e| (e.virtual_offset, e.plain_len))
+                .collect();
+            assert_eq!(streamed, offsets(r));
+        }
+
+        let mut unpacked = Vec::new();
+        unpack_from_furry(&mut Cursor::new(&compact), &mut unpacked, &master_key).unwrap();
+        assert_eq!(unpacked, original_data);
+    }
+
+    #[test]
+    fn test_recover_unfinalized_file() {
+        let master_key = MasterKey::default_key();
+        let mut output = Cursor::new(Vec::new());
+        {
+            // 模拟写入中途崩溃：不调用 finish
+            let mut writer =
+                FurryWriter::create(&mut output, &master_key, OriginalFormat::Mp3).unwrap();
+            writer.write_meta_chunk(MetaKind::Tags, b"{}", 0).unwrap();
+            writer.write_audio_chunk(b"hello ", 0).unwrap();
+            writer.write_audio_chunk(b"world", 6).unwrap();
+        }
+        let mut data = output.into_inner();
+        // 末尾残缺的记录
+        data.extend_from_slice(b"FRCK\0\0");
+
+        assert!(matches!(
+            FurryReader::open(Cursor::new(&data), &master_key),
+            Err(furry_format::FormatError::NotFinalized)
+        ));
+
+        let mut reader = FurryReader::open_with_recovery(Cursor::new(&data), &master_key).unwrap();
+        assert!(reader.is_recovered());
+        assert_eq!(reader.index.meta_entries().len(), 1);
+        assert_eq!(reader.index.header.audio_stream_len, 11);
+        let mut audio = Vec::new();
+        for entry in reader
+            .index
+            .audio_entries()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            audio.extend(reader.read_chunk(&entry).unwrap());
+        }
+        assert_eq!(audio, b"hello world");
+    }
+
+    #[test]
+    fn test_read_raw_chunk_is_verbatim() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(b"raw chunk audio ".repeat(100)),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                chunk_size: 512,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let furry_data = furry_output.into_inner();
+
+        let mut reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        for entry in reader.index.entries.clone() {
+            let raw = reader.read_raw_chunk(&entry).unwrap();
+            let start = entry.file_offset as usize;
+            assert_eq!(raw, &furry_data[start..start + entry.record_len as usize]);
+        }
+    }
+
+    #[test]
+    fn test_unknown_format_keeps_source_extension() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(&b"not really aac"[..]),
+            &mut furry_output,
+            Some(Path::new("missing/song.M4A")),
+            OriginalFormat::Unknown,
+            &master_key,
+            &PackOptions::default(),
+        )
+        .unwrap();
+
+        let mut reader =
+            FurryReader::open(Cursor::new(furry_output.into_inner()), &master_key).unwrap();
+        assert_eq!(reader.index.header.original_format, OriginalFormat::Unknown);
+        assert_eq!(reader.original_extension(), "m4a");
+    }
+
+    #[test]
+    fn test_stream_pack_keeps_format_hint() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_stream_to_furry(
+            &mut &b"raw adts stream"[..],
+            &mut furry_output,
+            OriginalFormat::Unknown,
+            &master_key,
+            &PackOptions {
+                format_hint: Some("AAC".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut reader =
+            FurryReader::open(Cursor::new(furry_output.into_inner()), &master_key).unwrap();
+        assert_eq!(reader.original_extension(), "aac");
+    }
+
+    #[test]
+    fn test_format_overhead_matches_packed_size() {
+        let master_key = MasterKey::default_key();
+        let audio = vec![0x42u8; 10_000];
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_stream_to_furry(
+            &mut audio.as_slice(),
+            &mut furry_output,
+            OriginalFormat::Flac,
+            &master_key,
+            &PackOptions {
+                chunk_size: 3000,
+                padding: PaddingTarget::Bytes(2000),
+                padding_chunk_size: 500,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let furry_data = furry_output.into_inner();
+
+        let reader = FurryReader::open(Cursor::new(&furry_data), &master_key).unwrap();
+        let count = |chunk_type: furry_format::ChunkType| {
+            reader
+                .index
+                .entries
+                .iter()
+                .filter(|e| e.chunk_type == chunk_type)
+                .count() as u32
+        };
+        let (audio_chunks, meta_chunks, padding_chunks) = (
+            count(furry_format::ChunkType::Audio),
+            count(furry_format::ChunkType::Meta),
+            count(furry_format::ChunkType::Padding),
+        );
+        assert_eq!(audio_chunks, 4);
+        assert!(meta_chunks > 0 && padding_chunks > 0);
+
+        let payload: u64 = reader
+            .index
+            .entries
+            .iter()
+            .map(|e| e.plain_len as u64)
+            .sum();
+        assert_eq!(
+            furry_data.len() as u64,
+            payload + furry_format::format_overhead(audio_chunks, meta_chunks, padding_chunks)
+        );
+    }
+
+    #[test]
+    fn test_pad_to_total_size() {
+        let master_key = MasterKey::default_key();
+        let original_data = vec![0x33u8; 10_000];
+        let target = 64 * 1024;
+
+        for interleave_padding in [false, true] {
+            let mut furry_output = Cursor::new(Vec::new());
+            pack_to_furry(
+                &mut Cursor::new(&original_data),
+                &mut furry_output,
+                None,
+                OriginalFormat::Mp3,
+                &master_key,
+                &PackOptions {
+                    chunk_size: 1024,
+                    padding: PaddingTarget::TotalSize(target),
+                    padding_chunk_size: 4096,
+                    interleave_padding,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let furry_data = furry_output.into_inner();
+            assert_eq!(furry_data.len() as u64, target);
+
+            let mut unpacked = Vec::new();
+            unpack_from_furry(&mut Cursor::new(&furry_data), &mut unpacked, &master_key).unwrap();
+            assert_eq!(unpacked, original_data);
+        }
+
+        let err = pack_to_furry(
+            &mut Cursor::new(&original_data),
+            &mut Cursor::new(Vec::new()),
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                padding: PaddingTarget::TotalSize(4096),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            err,
+            Err(ConverterError::Format(
+                furry_format::FormatError::PaddingTargetTooSmall { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_cancelled_pack_and_unpack() {
+        let master_key = MasterKey::default_key();
+        let cancel = Arc::new(AtomicBool::new(true));
+
+        let err = pack_to_furry(
+            &mut Cursor::new(vec![0u8; 4096]),
+            &mut Cursor::new(Vec::new()),
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions {
+                cancel: Some(cancel.clone()),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(err, Err(ConverterError::Cancelled)));
+
+        let mut furry_output = Cursor::new(Vec::new());
+        pack_to_furry(
+            &mut Cursor::new(vec![0u8; 4096]),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions::default(),
+        )
+        .unwrap();
+        let err = unpack_from_furry_cancellable(
+            &mut Cursor::new(furry_output.into_inner()),
+            &mut Vec::new(),
+            &master_key,
+            Some(&cancel),
+        );
+        assert!(matches!(err, Err(ConverterError::Cancelled)));
+    }
+
+    #[test]
+    fn test_builder_pack_unpack_with_progress() {
+        let master_key = MasterKey::default_key();
+        let audio = vec![0x3Cu8; 5000];
+
+        let mut pack_updates = Vec::new();
+        let mut packed = Cursor::new(Vec::new());
+        PackBuilder::new(&master_key)
+            .chunk_size(1024)
+            .padding(PaddingTarget::Bytes(512))
+            .input_path("song.mp3")
+            .format_override(OriginalFormat::Flac)
+            .progress(|done, total| pack_updates.push((done, total)))
+            .pack(&mut Cursor::new(audio.clone()), &mut packed)
+            .unwrap();
+        assert!(!pack_updates.is_empty());
+        assert_eq!(pack_updates.last(), Some(&(5000, 5000)));
+
+        let mut unpack_updates = Vec::new();
+        let mut output = Vec::new();
+        let format = UnpackBuilder::new(&master_key)
+            .progress(|done, total| unpack_updates.push((done, total)))
+            .unpack(&mut packed, &mut output)
+            .unwrap();
+        assert_eq!(format, OriginalFormat::Flac);
+        assert_eq!(output, audio);
+        assert_eq!(unpack_updates.len(), 5);
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let err = PackBuilder::new(&master_key)
+            .cancel(cancel.clone())
+            .pack_stream(&mut &audio[..], &mut Cursor::new(Vec::new()));
+        assert!(matches!(err, Err(ConverterError::Cancelled)));
+        let err = UnpackBuilder::new(&master_key)
+            .cancel(cancel)
+            .unpack(&mut packed, &mut Vec::new());
+        assert!(matches!(err, Err(ConverterError::Cancelled)));
+    }
+
+    #[test]
+    fn test_pack_album_records_track_table() {
+        let master_key = MasterKey::default_key();
+        let dir = std::env::temp_dir().join(format!("furry_test_album_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let tracks = [
+            (dir.join("furry_album_one.mp3"), vec![0x11u8; 3000]),
+            (dir.join("furry_album_two.mp3"), vec![0x22u8; 1500]),
+        ];
+        for (path, data) in &tracks {
+            std::fs::write(path, data).unwrap();
+        }
+        let paths: Vec<_> = tracks.iter().map(|(path, _)| path.clone()).collect();
+
+        let mut packed = Cursor::new(Vec::new());
+        let options = PackOptions {
+            chunk_size: 1024,
+            ..Default::default()
+        };
+        let table = pack_album(&paths, &mut packed, &master_key, &options).unwrap();
+        let mixed = pack_album(
+            &[paths[0].clone(), dir.join("furry_album_three.flac")],
+            &mut Cursor::new(Vec::new()),
+            &master_key,
+            &options,
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(mixed, Err(ConverterError::UnsupportedFormat(_))));
+
+        let mut reader = FurryReader::open(Cursor::new(packed.get_ref()), &master_key).unwrap();
+        assert_eq!(reader.read_track_table().unwrap().as_ref(), Some(&table));
+        assert_eq!(table.tracks.len(), 2);
+        let second = table.get(1).unwrap();
+        assert_eq!(second.title, "furry_album_two");
+        assert_eq!((second.virtual_start, second.virtual_len), (3000, 1500));
+        assert_eq!(table.get(0).unwrap().virtual_end(), second.virtual_start);
+        assert_eq!(
+            furry_format::TrackTable::parse(&table.to_bytes()),
+            Some(table.clone())
+        );
+
+        let mut output = Vec::new();
+        unpack_from_furry(&mut packed, &mut output, &master_key).unwrap();
+        assert_eq!(output[..3000], tracks[0].1[..]);
+        assert_eq!(output[3000..], tracks[1].1[..]);
+    }
 }
@@ -0,0 +1,198 @@
+//! 曲库扫描
+//!
+//! 只读取头部、索引与 tags META，不触碰任何 AUDIO chunk。
+
+use std::path::{Path, PathBuf};
+
+use furry_crypto::MasterKey;
+use furry_format::{ChunkType, FurryReader, MetaKind, OriginalFormat};
+use serde::Deserialize;
+
+use crate::ConverterError;
+
+/// 单个 .furry 文件的摘要
+#[derive(Debug, Clone)]
+pub struct FileSummary {
+    pub path: PathBuf,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub format: OriginalFormat,
+    /// 打开或解析失败时的错误信息（其余字段为默认值）
+    pub error: Option<String>,
+}
+
+/// tags JSON 中扫描需要的字段
+#[derive(Debug, Default, Deserialize)]
+struct TagsSummary {
+    title: Option<String>,
+    artist: Option<String>,
+    duration_ms: Option<u64>,
+}
+
+/// 并行扫描目录（递归）下所有 `.furry` 文件，按路径排序返回
+///
+/// 无法读取的文件也会出现在结果中，并在 `error` 中给出原因。
+pub fn scan_library(
+    dir: &Path,
+    master_key: &MasterKey,
+) -> Result<Vec<FileSummary>, ConverterError> {
+    let mut paths = Vec::new();
+    collect_furry_files(dir, &mut paths)?;
+    paths.sort();
+    Ok(par_map(&paths, |p| summarize_file(p, master_key)))
+}
+
+/// 单个文件的磁盘占用拆分（字节）
+#[derive(Debug, Clone, Default)]
+pub struct SpaceUsage {
+    pub path: PathBuf,
+    pub file_bytes: u64,
+    pub audio_bytes: u64,
+    pub padding_bytes: u64,
+    /// 全部 META chunk（tags/封面/歌词等）
+    pub meta_bytes: u64,
+    /// 文件头、INDEX 记录与 fake footer 等其余部分
+    pub overhead_bytes: u64,
+    /// 打开或解析失败时的错误信息（其余字段除 `path` 外为 0）
+    pub error: Option<String>,
+}
+
+/// 曲库空间报告
+#[derive(Debug, Clone, Default)]
+pub struct SpaceReport {
+    /// 按 `padding_bytes` 降序排列
+    pub files: Vec<SpaceUsage>,
+    pub total_file_bytes: u64,
+    pub total_audio_bytes: u64,
+    pub total_padding_bytes: u64,
+    pub total_meta_bytes: u64,
+    pub total_overhead_bytes: u64,
+}
+
+/// 并行统计目录（递归）下所有 `.furry` 文件的空间占用
+///
+/// 只解密索引，不读取任何 chunk；可据此决定是否去除 padding。
+pub fn space_report(dir: &Path, master_key: &MasterKey) -> Result<SpaceReport, ConverterError> {
+    let mut paths = Vec::new();
+    collect_furry_files(dir, &mut paths)?;
+    paths.sort();
+
+    let mut files = par_map(&paths, |p| {
+        space_usage(p, master_key).unwrap_or_else(|e| SpaceUsage {
+            path: p.to_path_buf(),
+            error: Some(e.to_string()),
+            ..Default::default()
+        })
+    });
+    files.sort_by_key(|usage| std::cmp::Reverse(usage.padding_bytes));
+
+    let mut report = SpaceReport::default();
+    for usage in &files {
+        report.total_file_bytes += usage.file_bytes;
+        report.total_audio_bytes += usage.audio_bytes;
+        report.total_padding_bytes += usage.padding_bytes;
+        report.total_meta_bytes += usage.meta_bytes;
+        report.total_overhead_bytes += usage.overhead_bytes;
+    }
+    report.files = files;
+    Ok(report)
+}
+
+fn space_usage(path: &Path, master_key: &MasterKey) -> Result<SpaceUsage, ConverterError> {
+    let file = std::fs::File::open(path)?;
+    let file_bytes = file.metadata()?.len();
+    let reader = FurryReader::open_index_only(file, master_key)?;
+    let by_type = reader.index.size_by_chunk_type();
+    let audio_bytes = by_type.get(&ChunkType::Audio).copied().unwrap_or(0);
+    let padding_bytes = reader.padding_bytes();
+    let meta_bytes: u64 = reader.index.meta_size_by_kind().values().sum();
+    let chunk_total: u64 = by_type.values().sum();
+    Ok(SpaceUsage {
+        path: path.to_path_buf(),
+        file_bytes,
+        audio_bytes,
+        padding_bytes,
+        meta_bytes,
+        overhead_bytes: file_bytes.saturating_sub(chunk_total),
+        error: None,
+    })
+}
+
+/// 将 `paths` 均分给多个线程处理，结果保持输入顺序
+fn par_map<T, F>(paths: &[PathBuf], f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Path) -> T + Sync,
+{
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(paths.len().max(1));
+    let per_worker = paths.len().div_ceil(workers).max(1);
+
+    let mut results = Vec::with_capacity(paths.len());
+    std::thread::scope(|s| {
+        let f = &f;
+        let handles: Vec<_> = paths
+            .chunks(per_worker)
+            .map(|batch| s.spawn(move || batch.iter().map(|p| f(p)).collect::<Vec<_>>()))
+            .collect();
+        for handle in handles {
+            results.extend(handle.join().expect("scan worker panicked"));
+        }
+    });
+    results
+}
+
+fn collect_furry_files(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_furry_files(&path, out)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("furry"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn summarize_file(path: &Path, master_key: &MasterKey) -> FileSummary {
+    let mut summary = FileSummary {
+        path: path.to_path_buf(),
+        title: None,
+        artist: None,
+        duration_ms: None,
+        format: OriginalFormat::Unknown,
+        error: None,
+    };
+    match read_summary(path, master_key) {
+        Ok((format, tags)) => {
+            summary.format = format;
+            summary.title = tags.title;
+            summary.artist = tags.artist;
+            summary.duration_ms = tags.duration_ms;
+        }
+        Err(e) => summary.error = Some(e.to_string()),
+    }
+    summary
+}
+
+fn read_summary(
+    path: &Path,
+    master_key: &MasterKey,
+) -> Result<(OriginalFormat, TagsSummary), ConverterError> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = FurryReader::open_index_only(file, master_key)?;
+    let format = reader.index.header.original_format;
+    // tags 损坏不影响格式信息，按无 tags 处理
+    let tags = reader
+        .read_latest_meta(MetaKind::Tags)?
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+    Ok((format, tags))
+}
@@ -0,0 +1,205 @@
+//! 按路径读取 tags/封面 META 并解析为类型化结果
+
+use std::io::Read;
+use std::path::Path;
+
+use furry_crypto::MasterKey;
+use furry_format::{FurryReader, MetaKind, RawTagFormat};
+
+use crate::{ConverterError, TagsJsonV1};
+
+/// 读取并解析 tags META
+///
+/// 没有 tags 或超过 `MAX_TAGS_BYTES` 时返回 `None`。无效 UTF-8 按替换字符解码，
+/// 个别字段损坏时不丢弃整段 tags；解码后 JSON 仍无法解析或 `schema` 不受支持时
+/// 返回 `InvalidMeta`，调用方据此区分"没有 tags"与"tags 损坏"。
+pub fn read_tags(
+    path: &Path,
+    master_key: &MasterKey,
+) -> Result<Option<TagsJsonV1>, ConverterError> {
+    Ok(read_tags_text(path, master_key)?.map(|(_, tags)| tags))
+}
+
+/// 同 [`read_tags`]，但返回 tags JSON 原文（无效 UTF-8 已替换）
+///
+/// 校验通过后原样返回，保留本库不认识的字段；供 FFI/JNI 直接转交给应用。
+pub fn read_tags_json(
+    path: &Path,
+    master_key: &MasterKey,
+) -> Result<Option<String>, ConverterError> {
+    Ok(read_tags_text(path, master_key)?.map(|(text, _)| text))
+}
+
+fn read_tags_text(
+    path: &Path,
+    master_key: &MasterKey,
+) -> Result<Option<(String, TagsJsonV1)>, ConverterError> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = FurryReader::open_index_only(file, master_key)?;
+    let Some(data) = reader.read_latest_meta(MetaKind::Tags)? else {
+        return Ok(None);
+    };
+    let text = String::from_utf8_lossy(&data).into_owned();
+    let tags = TagsJsonV1::from_json(text.as_bytes())?;
+    Ok(Some((text, tags)))
+}
+
+/// 读取封面，返回 `(mime, 图片字节)`
+///
+/// 兼容带 `mime\0` 前缀与裸图片字节两种载荷；mime 未知时按魔数猜测，仍无法识别则为空串。
+/// 没有封面或超过 `MAX_COVER_BYTES` 时返回 `None`。
+pub fn read_cover(
+    path: &Path,
+    master_key: &MasterKey,
+) -> Result<Option<(String, Vec<u8>)>, ConverterError> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = FurryReader::open_index_only(file, master_key)?;
+    let Some(data) = reader.read_latest_meta(MetaKind::CoverArt)? else {
+        return Ok(None);
+    };
+    let (mime, image) = furry_format::parse_cover_payload(&data);
+    if image.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((mime.unwrap_or_default(), image.to_vec())))
+}
+
+/// 读取原始标签块（ID3v2 / FLAC VORBIS_COMMENT），见 [`MetaKind::RawTagBlock`]
+///
+/// 音频流本身就是源文件原样字节，解包得到的文件已包含这些标签，无需回写；
+/// 此接口供需要逐帧原样访问标签的工具使用。
+pub fn read_raw_tag_block(
+    path: &Path,
+    master_key: &MasterKey,
+) -> Result<Option<(RawTagFormat, Vec<u8>)>, ConverterError> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = FurryReader::open_index_only(file, master_key)?;
+    let Some(data) = reader.read_latest_meta(MetaKind::RawTagBlock)? else {
+        return Ok(None);
+    };
+    let (format, block) = furry_format::parse_raw_tag_payload(&data)
+        .ok_or_else(|| ConverterError::InvalidMeta("raw tag block: unknown kind".to_string()))?;
+    Ok(Some((format, block.to_vec())))
+}
+
+/// 从源文件开头截取原始标签块并编码为 META 载荷；超过大小上限时放弃
+pub(crate) fn extract_raw_tag_payload(path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(furry_format::MAX_RAW_TAG_BLOCK_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    let (format, block) = furry_format::find_raw_tag_block(&head)?;
+    Some(furry_format::encode_raw_tag_payload(format, block))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use furry_format::{FurryWriter, OriginalFormat};
+
+    #[test]
+    fn test_read_tags_and_cover() {
+        let master_key = MasterKey::default_key();
+        let path = std::env::temp_dir().join(format!("furry_meta_{}.furry", std::process::id()));
+
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = FurryWriter::create(file, &master_key, OriginalFormat::Flac).unwrap();
+        writer
+            .write_meta_chunk(
+                MetaKind::Tags,
+                br#"{"schema":"furry.tags.v1","title":"Song","track":3}"#,
+                0,
+            )
+            .unwrap();
+        // 旧文件：裸 PNG 字节，无 mime 前缀
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 1, 2, 3];
+        writer
+            .write_meta_chunk(MetaKind::CoverArt, &png, 0)
+            .unwrap();
+        writer.write_audio_chunk(b"audio", 0).unwrap();
+        writer.finish().unwrap();
+
+        let tags = read_tags(&path, &master_key).unwrap().unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Song"));
+        assert_eq!(tags.track, Some(3));
+        assert!(tags.raw.is_empty());
+
+        let (mime, image) = read_cover(&path, &master_key).unwrap().unwrap();
+        assert_eq!(mime, "image/png");
+        assert_eq!(image, png);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_corrupt_tags_are_invalid_meta() {
+        let master_key = MasterKey::default_key();
+        let path =
+            std::env::temp_dir().join(format!("furry_bad_tags_{}.furry", std::process::id()));
+        let write = |tags: Option<&[u8]>| {
+            let file = std::fs::File::create(&path).unwrap();
+            let mut writer = FurryWriter::create(file, &master_key, OriginalFormat::Mp3).unwrap();
+            if let Some(tags) = tags {
+                writer.write_meta_chunk(MetaKind::Tags, tags, 0).unwrap();
+            }
+            writer.write_audio_chunk(b"audio", 0).unwrap();
+            writer.finish().unwrap();
+        };
+
+        write(None);
+        assert!(read_tags(&path, &master_key).unwrap().is_none());
+        write(Some(b"{\"title\":\"trunc"));
+        let garbage = read_tags(&path, &master_key);
+        write(Some(br#"{"schema":"furry.tags.v9","title":"Song"}"#));
+        let future = read_tags(&path, &master_key);
+
+        // 单个无效字节只影响所在字段，未知字段原样保留
+        write(Some(
+            b"{\"schema\":\"furry.tags.v1\",\"title\":\"So\xffng\",\"mood\":\"calm\"}",
+        ));
+        let lossy = read_tags(&path, &master_key).unwrap().unwrap();
+        let lossy_json = read_tags_json(&path, &master_key).unwrap().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(lossy.title.as_deref(), Some("So\u{FFFD}ng"));
+        assert!(lossy_json.contains(r#""mood":"calm""#));
+
+        assert!(matches!(garbage, Err(ConverterError::InvalidMeta(_))));
+        assert!(matches!(future, Err(ConverterError::InvalidMeta(_))));
+    }
+
+    #[test]
+    fn test_raw_id3_block_preserved() {
+        let master_key = MasterKey::default_key();
+        let dir = std::env::temp_dir();
+        let src = dir.join(format!("furry_raw_tags_{}.mp3", std::process::id()));
+        let dst = dir.join(format!("furry_raw_tags_{}.furry", std::process::id()));
+
+        // ID3v2.4 头 + 一个 TXXX 帧（内容无需合法，只按长度截取）
+        let frames = b"TXXX\0\0\0\x0b\0\0\0replaygain";
+        let mut id3 = b"ID3\x04\0\0\0\0\0".to_vec();
+        id3.push(frames.len() as u8);
+        id3.extend_from_slice(frames);
+        let mut source = id3.clone();
+        source.extend_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        source.extend_from_slice(&[0u8; 256]);
+        std::fs::write(&src, &source).unwrap();
+
+        crate::pack_to_furry(
+            &mut std::io::Cursor::new(&source),
+            &mut std::fs::File::create(&dst).unwrap(),
+            Some(&src),
+            OriginalFormat::Mp3,
+            &master_key,
+            &crate::PackOptions::default(),
+        )
+        .unwrap();
+
+        let (format, block) = read_raw_tag_block(&dst, &master_key).unwrap().unwrap();
+        std::fs::remove_file(&src).ok();
+        std::fs::remove_file(&dst).ok();
+        assert_eq!(format, RawTagFormat::Id3v2);
+        assert_eq!(block, id3);
+    }
+}
@@ -0,0 +1,161 @@
+//! 打包/解包耗时统计（`metrics` feature）
+//!
+//! 通过包装输入/输出流统计 IO 耗时，总耗时减去 IO 即为计算耗时
+//! （主要是 AES-GCM，另含 BLAKE3 摘要与 META 提取）。未启用 feature 时不编译。
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use furry_crypto::MasterKey;
+use furry_format::OriginalFormat;
+
+use crate::{pack_to_furry, unpack_from_furry, ConverterError, PackOptions};
+
+/// 各阶段耗时
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// 读取输入（含 seek）
+    pub read: Duration,
+    /// 写入输出（含 seek）
+    pub write: Duration,
+    /// 非 IO 耗时：加密/解密、摘要、META 提取等
+    pub compute: Duration,
+    /// 总耗时
+    pub total: Duration,
+}
+
+impl Timings {
+    fn from_parts(read: Duration, write: Duration, total: Duration) -> Self {
+        Self {
+            read,
+            write,
+            compute: total.saturating_sub(read + write),
+            total,
+        }
+    }
+}
+
+/// 与 [`pack_to_furry`] 相同，额外返回耗时统计
+pub fn pack_to_furry_timed<R, W>(
+    input: &mut R,
+    output: &mut W,
+    input_path: Option<&Path>,
+    original_format: OriginalFormat,
+    master_key: &MasterKey,
+    options: &PackOptions,
+) -> Result<Timings, ConverterError>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    let start = Instant::now();
+    let mut input = Timed::new(input);
+    let mut output = Timed::new(output);
+    pack_to_furry(
+        &mut input,
+        &mut output,
+        input_path,
+        original_format,
+        master_key,
+        options,
+    )?;
+    Ok(Timings::from_parts(
+        input.elapsed,
+        output.elapsed,
+        start.elapsed(),
+    ))
+}
+
+/// 与 [`unpack_from_furry`] 相同，额外返回耗时统计
+pub fn unpack_from_furry_timed<R, W>(
+    input: &mut R,
+    output: &mut W,
+    master_key: &MasterKey,
+) -> Result<(OriginalFormat, Timings), ConverterError>
+where
+    R: Read + Seek,
+    W: Write,
+{
+    let start = Instant::now();
+    let mut input = Timed::new(input);
+    let mut output = Timed::new(output);
+    let format = unpack_from_furry(&mut input, &mut output, master_key)?;
+    let timings = Timings::from_parts(input.elapsed, output.elapsed, start.elapsed());
+    Ok((format, timings))
+}
+
+/// 累计底层 IO 调用耗时的包装器
+struct Timed<'a, T> {
+    inner: &'a mut T,
+    elapsed: Duration,
+}
+
+impl<'a, T> Timed<'a, T> {
+    fn new(inner: &'a mut T) -> Self {
+        Self {
+            inner,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn time<F: FnOnce(&mut T) -> U, U>(&mut self, f: F) -> U {
+        let start = Instant::now();
+        let result = f(self.inner);
+        self.elapsed += start.elapsed();
+        result
+    }
+}
+
+impl<T: Read> Read for Timed<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.time(|inner| inner.read(buf))
+    }
+}
+
+impl<T: Write> Write for Timed<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.time(|inner| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.time(|inner| inner.flush())
+    }
+}
+
+impl<T: Seek> Seek for Timed<'_, T> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.time(|inner| inner.seek(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_timings_account_for_total() {
+        let master_key = MasterKey::default_key();
+        let mut furry_output = Cursor::new(Vec::new());
+        let pack = pack_to_furry_timed(
+            &mut Cursor::new(vec![7u8; 64 * 1024]),
+            &mut furry_output,
+            None,
+            OriginalFormat::Mp3,
+            &master_key,
+            &PackOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(pack.read + pack.write + pack.compute, pack.total);
+
+        let (format, unpack) = unpack_from_furry_timed(
+            &mut Cursor::new(furry_output.into_inner()),
+            &mut Vec::new(),
+            &master_key,
+        )
+        .unwrap();
+        assert_eq!(format, OriginalFormat::Mp3);
+        assert!(unpack.compute <= unpack.total);
+    }
+}
@@ -0,0 +1,217 @@
+//! 从解码后的 PCM 直接封装（合成器/TTS 等生成的音频）
+//!
+//! 采样先编码为真实的音频格式再封装，生成的 `.furry` 可直接由播放器解码；
+//! 调用方无需先写临时 WAV 文件。
+
+use std::io::{Cursor, Seek, Write};
+
+use furry_crypto::MasterKey;
+use furry_format::OriginalFormat;
+
+use crate::{pack_to_furry, ConverterError, PackOptions, TagsJsonV1, TAGS_SCHEMA_V1};
+
+const WAV_HEADER_LEN: usize = 44;
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+
+/// [`pack_pcm`] 的目标编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TargetCodec {
+    /// 16-bit PCM WAV，兼容性最好
+    #[default]
+    WavPcm16,
+    /// 32-bit float WAV，原样保留 f32 采样
+    WavFloat32,
+}
+
+impl TargetCodec {
+    fn original_format(self) -> OriginalFormat {
+        match self {
+            Self::WavPcm16 | Self::WavFloat32 => OriginalFormat::Wav,
+        }
+    }
+
+    fn codec_name(self) -> &'static str {
+        match self {
+            Self::WavPcm16 => "pcm_s16le",
+            Self::WavFloat32 => "pcm_f32le",
+        }
+    }
+}
+
+/// 将交错的 f32 采样编码为 `target` 后封装为 .furry
+///
+/// 采样按 `[-1.0, 1.0]` 解释，整型编码时钳制；末尾不足一帧的采样被丢弃。
+/// tags META 写入采样率、声道数、时长与编码，`options.tags_override` 中已设置的字段优先。
+pub fn pack_pcm<I, W>(
+    samples: I,
+    sample_rate: u32,
+    channels: u16,
+    output: &mut W,
+    master_key: &MasterKey,
+    target: TargetCodec,
+    options: &PackOptions,
+) -> Result<(), ConverterError>
+where
+    I: IntoIterator<Item = f32>,
+    W: Write + Seek,
+{
+    if sample_rate == 0 || channels == 0 {
+        return Err(ConverterError::UnsupportedFormat(format!(
+            "PCM {} Hz / {} channels",
+            sample_rate, channels
+        )));
+    }
+
+    let wav = encode_wav(samples, sample_rate, channels, target)?;
+    let frames = (wav.len() - WAV_HEADER_LEN) as u64 / block_align(channels, target) as u64;
+
+    let generated = TagsJsonV1 {
+        schema: TAGS_SCHEMA_V1.to_string(),
+        duration_ms: Some(frames * 1000 / sample_rate as u64),
+        sample_rate: Some(sample_rate),
+        channels: Some(channels),
+        codec: Some(target.codec_name().to_string()),
+        ..Default::default()
+    };
+    let tags = match &options.tags_override {
+        Some(tags_override) => {
+            tags_override.validate()?;
+            tags_override.merge_over(Some(generated))
+        }
+        None => generated,
+    };
+    let options = PackOptions {
+        tags_override: Some(tags),
+        ..options.clone()
+    };
+
+    pack_to_furry(
+        &mut Cursor::new(wav),
+        output,
+        None,
+        target.original_format(),
+        master_key,
+        &options,
+    )
+}
+
+fn block_align(channels: u16, target: TargetCodec) -> u16 {
+    let bytes = match target {
+        TargetCodec::WavPcm16 => 2,
+        TargetCodec::WavFloat32 => 4,
+    };
+    channels * bytes
+}
+
+/// 编码为完整的 WAV 文件（44 字节头 + data）
+fn encode_wav<I>(
+    samples: I,
+    sample_rate: u32,
+    channels: u16,
+    target: TargetCodec,
+) -> Result<Vec<u8>, ConverterError>
+where
+    I: IntoIterator<Item = f32>,
+{
+    let mut wav = vec![0u8; WAV_HEADER_LEN];
+    for sample in samples {
+        match target {
+            TargetCodec::WavPcm16 => {
+                let value = (sample.clamp(-1.0, 1.0) * 32767.0).round() as i16;
+                wav.extend_from_slice(&value.to_le_bytes());
+            }
+            TargetCodec::WavFloat32 => wav.extend_from_slice(&sample.to_le_bytes()),
+        }
+    }
+    let align = block_align(channels, target) as usize;
+    let data_len = (wav.len() - WAV_HEADER_LEN) / align * align;
+    wav.truncate(WAV_HEADER_LEN + data_len);
+    let data_len = u32::try_from(data_len)
+        .ok()
+        .filter(|len| *len <= u32::MAX - WAV_HEADER_LEN as u32)
+        .ok_or_else(|| ConverterError::UnsupportedFormat("PCM exceeds 4 GiB WAV limit".into()))?;
+
+    let (format_tag, bits) = match target {
+        TargetCodec::WavPcm16 => (WAVE_FORMAT_PCM, 16u16),
+        TargetCodec::WavFloat32 => (WAVE_FORMAT_IEEE_FLOAT, 32u16),
+    };
+    let mut header = Vec::with_capacity(WAV_HEADER_LEN);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(WAV_HEADER_LEN as u32 - 8 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&format_tag.to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * align as u32).to_le_bytes());
+    header.extend_from_slice(&(align as u16).to_le_bytes());
+    header.extend_from_slice(&bits.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    wav[..WAV_HEADER_LEN].copy_from_slice(&header);
+    Ok(wav)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_tags, unpack_from_furry};
+
+    #[test]
+    fn test_pack_pcm_produces_playable_wav() {
+        let master_key = MasterKey::default_key();
+        // 1 秒 48 kHz 立体声，外加半帧多余采样
+        let samples: Vec<f32> = (0..96_001)
+            .map(|i| ((i / 2) as f32 * 0.001).sin())
+            .collect();
+        let path = std::env::temp_dir().join(format!("furry_pcm_{}.furry", std::process::id()));
+
+        let mut output = std::fs::File::create(&path).unwrap();
+        pack_pcm(
+            samples.iter().copied(),
+            48_000,
+            2,
+            &mut output,
+            &master_key,
+            TargetCodec::WavFloat32,
+            &PackOptions::default(),
+        )
+        .unwrap();
+        drop(output);
+
+        let tags = read_tags(&path, &master_key).unwrap().unwrap();
+        let mut wav = Vec::new();
+        let format = unpack_from_furry(
+            &mut std::fs::File::open(&path).unwrap(),
+            &mut wav,
+            &master_key,
+        )
+        .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(format, OriginalFormat::Wav);
+        assert_eq!(tags.sample_rate, Some(48_000));
+        assert_eq!(tags.channels, Some(2));
+        assert_eq!(tags.duration_ms, Some(1000));
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(wav.len(), WAV_HEADER_LEN + 96_000 * 4);
+        let decoded: Vec<f32> = wav[WAV_HEADER_LEN..]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(decoded, samples[..96_000]);
+
+        assert!(pack_pcm(
+            [0.0f32],
+            0,
+            2,
+            &mut Cursor::new(Vec::new()),
+            &master_key,
+            TargetCodec::WavPcm16,
+            &PackOptions::default(),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! 生成 furry_crypto 固定测试向量
+//!
+//! 输出可直接粘贴到 `src/lib.rs` 测试中的常量；仅在有意修改密码学行为时重新生成。
+//!
+//! ```text
+//! cargo run -p furry_crypto --example gen_test_vectors
+//! ```
+
+use furry_crypto::*;
+
+/// 固定输入（须与 `src/lib.rs` 中 `test_vectors` 测试保持一致）
+const MASTER: [u8; AEAD_KEY_LEN] = [0x11; AEAD_KEY_LEN];
+const SALT: [u8; SALT_LEN] = [0x22; SALT_LEN];
+const FILE_ID: [u8; FILE_ID_LEN] = [0x33; FILE_ID_LEN];
+const CHUNK_HEADER: [u8; CHUNK_HEADER_LEN] = [0x44; CHUNK_HEADER_LEN];
+const CHUNK_SEQ: u64 = 7;
+const PLAINTEXT: &[u8] = b"furry test vector plaintext";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn main() {
+    let keys = derive_file_keys(&MasterKey::new(MASTER), &SALT).unwrap();
+    let nonce = nonce_for_chunk(&keys.nonce_prefix, CHUNK_SEQ);
+    let aad = build_aad_v1(&FILE_ID, 1, 0, &CHUNK_HEADER);
+    let mut ciphertext = PLAINTEXT.to_vec();
+    let tag = encrypt_in_place_detached(&keys.aead_key, &nonce, &aad, &mut ciphertext).unwrap();
+
+    println!("const AEAD_KEY: &str = \"{}\";", hex(&keys.aead_key));
+    println!(
+        "const NONCE_PREFIX: &str = \"{}\";",
+        hex(&keys.nonce_prefix)
+    );
+    println!(
+        "const META_XOR_KEY: &str = \"{}\";",
+        hex(&keys.meta_xor_key)
+    );
+    println!("const NONCE: &str = \"{}\";", hex(&nonce));
+    println!("const AAD: &str = \"{}\";", hex(&aad));
+    println!("const CIPHERTEXT: &str = \"{}\";", hex(&ciphertext));
+    println!("const TAG: &str = \"{}\";", hex(&tag));
+}
@@ -9,7 +9,9 @@ use aes_gcm::aead::generic_array::GenericArray;
 use aes_gcm::aead::{AeadInPlace, KeyInit};
 use aes_gcm::{Aes256Gcm, Nonce};
 use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
 use sha2::Sha256;
+use subtle::ConstantTimeEq;
 use zeroize::Zeroize;
 
 // ============================================================================
@@ -18,10 +20,14 @@ use zeroize::Zeroize;
 
 pub const FILE_ID_LEN: usize = 16;
 pub const SALT_LEN: usize = 16;
+/// 长 salt（≥256 bit 合规要求）
+pub const LONG_SALT_LEN: usize = 32;
 pub const AEAD_KEY_LEN: usize = 32;
 pub const NONCE_PREFIX_LEN: usize = 4;
 pub const NONCE_LEN: usize = 12;
 pub const TAG_LEN: usize = 16;
+/// HMAC-SHA256 输出长度
+pub const MAC_LEN: usize = 32;
 pub const CHUNK_HEADER_LEN: usize = 40;
 
 pub const AAD_PREFIX: [u8; 8] = *b"FURRYAAD";
@@ -47,6 +53,10 @@ pub enum CryptoError {
     Aead,
     #[error("Random generation failed")]
     Random,
+    #[error("Invalid salt length: {0} (minimum 16 bytes)")]
+    InvalidSaltLength(usize),
+    #[error("Master key provider did not expose a key")]
+    KeyUnavailable,
 }
 
 // ============================================================================
@@ -74,6 +84,43 @@ impl MasterKey {
     }
 }
 
+/// 主密钥来源
+///
+/// 密钥可以保存在 SecretBox/HSM/系统密钥库等受保护内存中，只在 HKDF 期间
+/// 通过回调短暂暴露，不必物化为 [`MasterKey`]。回调返回后实现方应清除临时副本。
+/// 方法是对象安全的，可以用 `&dyn MasterKeyProvider` 传递。
+pub trait MasterKeyProvider {
+    /// 以主密钥字节调用 `f` 一次；无法取得密钥时不调用
+    fn with_key(&self, f: &mut dyn FnMut(&[u8; AEAD_KEY_LEN]));
+}
+
+impl MasterKeyProvider for MasterKey {
+    fn with_key(&self, f: &mut dyn FnMut(&[u8; AEAD_KEY_LEN])) {
+        f(&self.0)
+    }
+}
+
+impl<T: MasterKeyProvider + ?Sized> MasterKeyProvider for &T {
+    fn with_key(&self, f: &mut dyn FnMut(&[u8; AEAD_KEY_LEN])) {
+        (**self).with_key(f)
+    }
+}
+
+impl<T: MasterKeyProvider + ?Sized> MasterKeyProvider for Box<T> {
+    fn with_key(&self, f: &mut dyn FnMut(&[u8; AEAD_KEY_LEN])) {
+        (**self).with_key(f)
+    }
+}
+
+/// 常量时间比较，避免通过时序泄露密钥内容
+impl PartialEq for MasterKey {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MasterKey {}
+
 impl Drop for MasterKey {
     fn drop(&mut self) {
         self.0.zeroize();
@@ -93,6 +140,12 @@ pub struct FileKeys {
     pub nonce_prefix: [u8; NONCE_PREFIX_LEN],
     /// META 混淆密钥
     pub meta_xor_key: [u8; AEAD_KEY_LEN],
+    /// 头部 + 索引完整性 MAC 密钥
+    pub integrity_key: [u8; AEAD_KEY_LEN],
+    /// META 独立加密密钥（头部 `FLAG_META_KEY` 置位时使用）
+    pub meta_aead_key: [u8; AEAD_KEY_LEN],
+    /// META 独立 nonce 前缀
+    pub meta_nonce_prefix: [u8; NONCE_PREFIX_LEN],
 }
 
 impl Drop for FileKeys {
@@ -100,6 +153,9 @@ impl Drop for FileKeys {
         self.aead_key.zeroize();
         self.nonce_prefix.zeroize();
         self.meta_xor_key.zeroize();
+        self.integrity_key.zeroize();
+        self.meta_aead_key.zeroize();
+        self.meta_nonce_prefix.zeroize();
     }
 }
 
@@ -108,11 +164,19 @@ impl Drop for FileKeys {
 // ============================================================================
 
 /// 从主密钥和 salt 派生文件密钥组
+///
+/// salt 长度可变，但不得短于 [`SALT_LEN`]。
 pub fn derive_file_keys(
-    master_key: &MasterKey,
-    salt: &[u8; SALT_LEN],
+    master_key: &dyn MasterKeyProvider,
+    salt: &[u8],
 ) -> Result<FileKeys, CryptoError> {
-    let hk = Hkdf::<Sha256>::new(Some(salt), master_key.bytes());
+    if salt.len() < SALT_LEN {
+        return Err(CryptoError::InvalidSaltLength(salt.len()));
+    }
+    // 主密钥只在 extract 期间可见，之后只保留 PRK
+    let mut hk = None;
+    master_key.with_key(&mut |bytes| hk = Some(Hkdf::<Sha256>::new(Some(salt), bytes)));
+    let hk = hk.ok_or(CryptoError::KeyUnavailable)?;
 
     let mut aead_key = [0u8; AEAD_KEY_LEN];
     hk.expand(b"furry/v1/aead_key", &mut aead_key)
@@ -126,13 +190,65 @@ pub fn derive_file_keys(
     hk.expand(b"furry/v1/meta_xor_key", &mut meta_xor_key)
         .map_err(|_| CryptoError::HkdfExpand)?;
 
+    let mut integrity_key = [0u8; AEAD_KEY_LEN];
+    hk.expand(b"furry/v1/integrity_key", &mut integrity_key)
+        .map_err(|_| CryptoError::HkdfExpand)?;
+
+    let mut meta_aead_key = [0u8; AEAD_KEY_LEN];
+    hk.expand(b"furry/v1/meta_aead_key", &mut meta_aead_key)
+        .map_err(|_| CryptoError::HkdfExpand)?;
+
+    let mut meta_nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+    hk.expand(b"furry/v1/meta_nonce_prefix", &mut meta_nonce_prefix)
+        .map_err(|_| CryptoError::HkdfExpand)?;
+
     Ok(FileKeys {
         aead_key,
         nonce_prefix,
         meta_xor_key,
+        integrity_key,
+        meta_aead_key,
+        meta_nonce_prefix,
     })
 }
 
+// ============================================================================
+// 完整性 MAC
+// ============================================================================
+
+/// 对依次拼接的 `parts` 计算 HMAC-SHA256
+pub fn hmac_sha256_parts(key: &[u8; AEAD_KEY_LEN], parts: &[&[u8]]) -> [u8; MAC_LEN] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// 常量时间校验 MAC；`expected` 可为截断后的前缀（至少 16 字节）
+pub fn verify_hmac_sha256_parts(
+    key: &[u8; AEAD_KEY_LEN],
+    parts: &[&[u8]],
+    expected: &[u8],
+) -> bool {
+    if expected.len() < 16 || expected.len() > MAC_LEN {
+        return false;
+    }
+    let mac = hmac_sha256_parts(key, parts);
+    ct_eq(&mac[..expected.len()], expected)
+}
+
+// ============================================================================
+// 常量时间比较
+// ============================================================================
+
+/// 常量时间比较两段字节（长度不同直接返回 `false`，长度本身不保密）
+///
+/// 比较密钥、file_id、MAC 等由秘密派生的数据时应使用它而不是 `==`。
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
 // ============================================================================
 // Nonce 生成
 // ============================================================================
@@ -238,6 +354,57 @@ pub fn xor_meta_in_place(meta_xor_key: &[u8; AEAD_KEY_LEN], chunk_seq: u64, data
 // 随机数生成
 // ============================================================================
 
+/// 随机数来源
+///
+/// 写入器用它生成 file_id/salt 与 padding 内容；生产环境使用 [`OsRng`]。
+pub trait Rng: Send {
+    /// 用随机字节填满 `buf`
+    fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), CryptoError>;
+}
+
+/// 操作系统 CSPRNG（`getrandom`）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRng;
+
+impl Rng for OsRng {
+    fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), CryptoError> {
+        generate_random_bytes(buf)
+    }
+}
+
+/// 由种子决定的伪随机数（SplitMix64），用于生成可复现的测试文件
+///
+/// **切勿用于生产**：相同种子得到相同 salt，同一主密钥下会派生出相同的文件密钥，
+/// 导致跨文件的 nonce 复用，AES-GCM 的机密性与完整性随之失效。
+#[derive(Debug, Clone)]
+pub struct TestRng {
+    state: u64,
+}
+
+impl TestRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl Rng for TestRng {
+    fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), CryptoError> {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
 /// 生成随机 salt
 pub fn generate_salt() -> Result<[u8; SALT_LEN], CryptoError> {
     let mut salt = [0u8; SALT_LEN];
@@ -245,6 +412,13 @@ pub fn generate_salt() -> Result<[u8; SALT_LEN], CryptoError> {
     Ok(salt)
 }
 
+/// 生成随机长 salt（32 字节）
+pub fn generate_long_salt() -> Result<[u8; LONG_SALT_LEN], CryptoError> {
+    let mut salt = [0u8; LONG_SALT_LEN];
+    getrandom::getrandom(&mut salt).map_err(|_| CryptoError::Random)?;
+    Ok(salt)
+}
+
 /// 生成随机 file_id
 pub fn generate_file_id() -> Result<[u8; FILE_ID_LEN], CryptoError> {
     let mut file_id = [0u8; FILE_ID_LEN];
@@ -344,4 +518,129 @@ mod tests {
         xor_meta_in_place(&keys.meta_xor_key, 42, &mut buffer);
         assert_eq!(&buffer[..], &original[..]);
     }
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(b"abc", b"abc"));
+        assert!(!ct_eq(b"abc", b"abd"));
+        assert!(!ct_eq(b"abc", b"abcd"));
+        assert!(MasterKey::default_key() == MasterKey::new(MASTER_KEY_BYTES));
+        assert!(MasterKey::default_key() != MasterKey::new([0u8; AEAD_KEY_LEN]));
+    }
+
+    // 固定测试向量：由 `examples/gen_test_vectors.rs` 生成，输入须与其保持一致。
+    // aes-gcm/hkdf 升级后若这些断言失败，说明已有文件将无法解密。
+    const TV_MASTER: [u8; AEAD_KEY_LEN] = [0x11; AEAD_KEY_LEN];
+    const TV_SALT: [u8; SALT_LEN] = [0x22; SALT_LEN];
+    const TV_FILE_ID: [u8; FILE_ID_LEN] = [0x33; FILE_ID_LEN];
+    const TV_CHUNK_HEADER: [u8; CHUNK_HEADER_LEN] = [0x44; CHUNK_HEADER_LEN];
+    const TV_CHUNK_SEQ: u64 = 7;
+    const TV_PLAINTEXT: &[u8] = b"furry test vector plaintext";
+
+    const AEAD_KEY: &str = "efccce78f7d6fa357f87e694d506450769f0a7567d10586723ba5745f89bd0d9";
+    const NONCE_PREFIX: &str = "ad7b1bcd";
+    const META_XOR_KEY: &str = "92dc2c9bddddf06ce55f00dcf2fdaf762110a414bfa0c25f7101bc789775be1c";
+    const NONCE: &str = "ad7b1bcd0700000000000000";
+    const AAD: &str = "46555252594141440100000000003333333333333333333333333333333344444444444444444444444444444444444444444444444444444444444444444444444444444444";
+    const CIPHERTEXT: &str = "8557ac47b1c1414909827b41291557ec4ba0a5bfccde769ea89415";
+    const TAG: &str = "79194d3a5351282e0129801ea2ea9c52";
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// 仅用 SHA-256 手写的 HMAC，不经过 hkdf/hmac crate
+    fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+        use sha2::Digest;
+        let mut block = [0u8; 64];
+        block[..key.len()].copy_from_slice(key);
+        let pad = |byte: u8| block.map(|b| b ^ byte);
+        let inner = Sha256::new()
+            .chain_update(pad(0x36))
+            .chain_update(msg)
+            .finalize();
+        Sha256::new()
+            .chain_update(pad(0x5c))
+            .chain_update(inner)
+            .finalize()
+            .into()
+    }
+
+    #[test]
+    fn test_vectors() {
+        let keys = derive_file_keys(&MasterKey::new(TV_MASTER), &TV_SALT).unwrap();
+        assert_eq!(hex(&keys.aead_key), AEAD_KEY);
+        assert_eq!(hex(&keys.nonce_prefix), NONCE_PREFIX);
+        assert_eq!(hex(&keys.meta_xor_key), META_XOR_KEY);
+
+        let nonce = nonce_for_chunk(&keys.nonce_prefix, TV_CHUNK_SEQ);
+        assert_eq!(hex(&nonce), NONCE);
+        let aad = build_aad_v1(&TV_FILE_ID, 1, 0, &TV_CHUNK_HEADER);
+        assert_eq!(hex(&aad), AAD);
+
+        let mut buffer = TV_PLAINTEXT.to_vec();
+        let tag = encrypt_in_place_detached(&keys.aead_key, &nonce, &aad, &mut buffer).unwrap();
+        assert_eq!(hex(&buffer), CIPHERTEXT);
+        assert_eq!(hex(&tag), TAG);
+
+        decrypt_in_place_detached(&keys.aead_key, &nonce, &aad, &mut buffer, &tag).unwrap();
+        assert_eq!(buffer, TV_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_master_key_provider() {
+        struct Borrowed<'a>(&'a [u8; AEAD_KEY_LEN]);
+        impl MasterKeyProvider for Borrowed<'_> {
+            fn with_key(&self, f: &mut dyn FnMut(&[u8; AEAD_KEY_LEN])) {
+                f(self.0)
+            }
+        }
+        struct Locked;
+        impl MasterKeyProvider for Locked {
+            fn with_key(&self, _f: &mut dyn FnMut(&[u8; AEAD_KEY_LEN])) {}
+        }
+
+        let expected = derive_file_keys(&MasterKey::new(TV_MASTER), &TV_SALT).unwrap();
+        let keys = derive_file_keys(&Borrowed(&TV_MASTER), &TV_SALT).unwrap();
+        assert_eq!(keys.aead_key, expected.aead_key);
+        assert_eq!(keys.integrity_key, expected.integrity_key);
+        assert!(matches!(
+            derive_file_keys(&Locked, &TV_SALT),
+            Err(CryptoError::KeyUnavailable)
+        ));
+    }
+
+    #[test]
+    fn test_derive_file_keys_matches_manual_hkdf() {
+        // 输出均不超过 32 字节，HKDF-Expand 只需第一块：T(1) = HMAC(PRK, info || 0x01)
+        let prk = hmac_sha256(&TV_SALT, &TV_MASTER);
+        let expand = |info: &[u8]| hmac_sha256(&prk, &[info, &[1u8]].concat());
+
+        let keys = derive_file_keys(&MasterKey::new(TV_MASTER), &TV_SALT).unwrap();
+        assert_eq!(keys.aead_key, expand(b"furry/v1/aead_key"));
+        assert_eq!(
+            keys.nonce_prefix,
+            expand(b"furry/v1/nonce_prefix")[..NONCE_PREFIX_LEN]
+        );
+        assert_eq!(keys.meta_xor_key, expand(b"furry/v1/meta_xor_key"));
+        assert_eq!(keys.integrity_key, expand(b"furry/v1/integrity_key"));
+        assert_eq!(keys.meta_aead_key, expand(b"furry/v1/meta_aead_key"));
+        assert_eq!(
+            keys.meta_nonce_prefix,
+            expand(b"furry/v1/meta_nonce_prefix")[..NONCE_PREFIX_LEN]
+        );
+
+        let mac = hmac_sha256_parts(&keys.integrity_key, &[b"head", b"er"]);
+        assert_eq!(mac, hmac_sha256(&keys.integrity_key, b"header"));
+        assert!(verify_hmac_sha256_parts(
+            &keys.integrity_key,
+            &[b"header"],
+            &mac[..16]
+        ));
+        assert!(!verify_hmac_sha256_parts(
+            &keys.integrity_key,
+            &[b"header!"],
+            &mac[..16]
+        ));
+    }
 }
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = furry_format::ChunkRecordHeaderV1::read_from(&mut &data[..]) {
+        let _ = header.record_len();
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(index) = furry_format::FurryIndexV1::parse(data) {
+        let _ = index.audio_entries();
+        let _ = index.to_bytes();
+    }
+});
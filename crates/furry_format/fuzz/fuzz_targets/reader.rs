@@ -0,0 +1,17 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use furry_crypto::MasterKey;
+use libfuzzer_sys::fuzz_target;
+
+// 整个文件作为输入：头部/索引记录范围、长度字段都不应导致 panic 或超大分配
+fuzz_target!(|data: &[u8]| {
+    let master_key = MasterKey::default_key();
+    if let Ok(mut reader) = furry_format::FurryReader::open(Cursor::new(data), &master_key) {
+        let entries = reader.index.entries.clone();
+        for entry in &entries {
+            let _ = reader.read_chunk(entry);
+        }
+    }
+});
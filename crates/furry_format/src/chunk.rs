@@ -11,7 +11,7 @@ pub const CHUNK_HEADER_VERSION: u16 = 1;
 
 /// Chunk 类型
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ChunkType {
     Audio = 0x01,
     Index = 0x02,
@@ -35,6 +35,11 @@ impl ChunkType {
 pub mod chunk_flags {
     /// META chunk 使用 XOR 混淆
     pub const FLAG_META_XOR: u8 = 0x01;
+    /// META 载荷在加密前经 gzip 压缩（见 [`crate::compress_meta_payload`]）
+    pub const FLAG_META_GZIP: u8 = 0x02;
+
+    /// 本版本能正确还原的 META 标志位；其余标志（含尚未实现的 XOR 混淆）读取时报错
+    pub const SUPPORTED_META_FLAGS: u8 = FLAG_META_GZIP;
 }
 
 /// Chunk 记录头 (v1, 40 bytes)
@@ -143,6 +148,7 @@ impl ChunkRecordHeaderV1 {
 
     /// 计算整个 chunk record 的总长度（header + ciphertext + tag）
     pub fn record_len(&self) -> u32 {
-        CHUNK_HEADER_LEN as u32 + self.plain_len + furry_crypto::TAG_LEN as u32
+        // plain_len 来自文件，饱和加法避免溢出 panic（超长记录会在读取时被拒绝）
+        (CHUNK_HEADER_LEN as u32 + furry_crypto::TAG_LEN as u32).saturating_add(self.plain_len)
     }
 }
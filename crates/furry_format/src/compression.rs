@@ -0,0 +1,29 @@
+//! META 载荷压缩（gzip，`chunk_flags::FLAG_META_GZIP`）
+//!
+//! 文本类 META（tags JSON、歌词）可在加密前压缩。解压时输出按该 kind 的大小上限截断，
+//! 压缩炸弹无法绕过 OOM 保护。
+
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::FormatError;
+
+/// gzip 压缩 META 载荷
+pub fn compress_meta_payload(data: &[u8]) -> Result<Vec<u8>, FormatError> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(data.len() / 2), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// 解压 META 载荷；解压后超过 `limit` 字节时返回 `None`
+pub fn decompress_meta_payload(data: &[u8], limit: u32) -> Result<Option<Vec<u8>>, FormatError> {
+    let mut out = Vec::new();
+    GzDecoder::new(data)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|_| FormatError::CorruptMeta("invalid gzip payload"))?;
+    Ok((out.len() <= limit as usize).then_some(out))
+}
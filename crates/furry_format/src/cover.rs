@@ -0,0 +1,59 @@
+//! 封面 META 载荷
+//!
+//! 标准载荷为 `mime\0<image-bytes>`；旧文件/外部工具可能直接写入裸图片字节。
+
+/// mime 前缀最大长度
+const MAX_MIME_LEN: usize = 64;
+
+/// 根据文件头魔数识别图片 MIME 类型
+pub fn sniff_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if bytes.starts_with(b"BM") {
+        Some("image/bmp")
+    } else {
+        None
+    }
+}
+
+/// 宽松解析封面载荷，返回 `(mime, 图片字节)`
+///
+/// 仅当第一个 NUL 之前是形如 `image/...` 的 mime 时才视为前缀；
+/// 否则把整个载荷当作图片字节，并按魔数猜测 mime。
+pub fn parse_cover_payload(payload: &[u8]) -> (Option<String>, &[u8]) {
+    if let Some(nul) = payload.iter().take(MAX_MIME_LEN + 1).position(|b| *b == 0) {
+        let (prefix, rest) = (&payload[..nul], &payload[nul + 1..]);
+        if is_image_mime(prefix) {
+            let mime = String::from_utf8_lossy(prefix).into_owned();
+            return (Some(mime), rest);
+        }
+    }
+    (sniff_image_mime(payload).map(str::to_string), payload)
+}
+
+/// 重新编码为标准 `mime\0<bytes>` 载荷（未知 mime 时前缀为空）
+pub fn normalize_cover_payload(payload: &[u8]) -> Vec<u8> {
+    let (mime, image) = parse_cover_payload(payload);
+    let mime = mime.unwrap_or_default();
+    let mut out = Vec::with_capacity(mime.len() + 1 + image.len());
+    out.extend_from_slice(mime.as_bytes());
+    out.push(0);
+    out.extend_from_slice(image);
+    out
+}
+
+fn is_image_mime(prefix: &[u8]) -> bool {
+    let Some(subtype) = prefix.strip_prefix(b"image/") else {
+        return false;
+    };
+    !subtype.is_empty()
+        && subtype
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+}
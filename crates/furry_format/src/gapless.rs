@@ -0,0 +1,73 @@
+//! 无缝播放信息（`MetaKind::Gapless`）
+//!
+//! MP3/AAC 编码器会在开头插入 priming 采样（delay）、在结尾补齐 padding，
+//! 不裁掉就会在连续曲目之间产生短暂静音。打包时从 LAME 头或 iTunSMPB 读取，
+//! 载荷与 SourceInfo 相同：UTF-8 文本，每行一个 `key=value`，未知 key 忽略。
+
+/// 无缝播放裁剪信息（单位：每声道采样帧）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GaplessInfo {
+    /// 开头需跳过的 priming 帧数（含解码器延迟）
+    pub delay: u32,
+    /// 结尾需裁掉的 padding 帧数
+    pub padding: u32,
+    /// 有效帧数（不含 delay/padding）；未知时由总帧数推算
+    pub valid_frames: Option<u64>,
+}
+
+impl GaplessInfo {
+    /// 是否需要裁剪
+    pub fn is_empty(&self) -> bool {
+        self.delay == 0 && self.padding == 0
+    }
+
+    /// 序列化为 META 载荷
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!("delay={}\npadding={}\n", self.delay, self.padding);
+        if let Some(frames) = self.valid_frames {
+            out.push_str(&format!("valid_frames={}\n", frames));
+        }
+        out.into_bytes()
+    }
+
+    /// 从 META 载荷解析；缺少 delay/padding 时返回 `None`
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let text = String::from_utf8_lossy(data);
+        let (mut delay, mut padding, mut valid_frames) = (None, None, None);
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "delay" => delay = value.parse().ok(),
+                "padding" => padding = value.parse().ok(),
+                "valid_frames" => valid_frames = value.parse().ok(),
+                _ => {}
+            }
+        }
+        Some(Self {
+            delay: delay?,
+            padding: padding?,
+            valid_frames,
+        })
+    }
+
+    /// 解析 iTunes 的 `iTunSMPB` 注释
+    ///
+    /// 形如 ` 00000000 00000840 000001CA 00000000003F31F6 ...`，
+    /// 第 2/3/4 个十六进制字段依次为 delay、padding 与有效帧数。
+    pub fn from_itunsmpb(value: &str) -> Option<Self> {
+        let mut fields = value.split_whitespace().skip(1);
+        let delay = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let padding = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let valid_frames = fields
+            .next()
+            .and_then(|f| u64::from_str_radix(f, 16).ok())
+            .filter(|&n| n > 0);
+        Some(Self {
+            delay,
+            padding,
+            valid_frames,
+        })
+    }
+}
@@ -1,14 +1,139 @@
 //! 文件头定义
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 
-use crate::FormatError;
+use crate::{FormatError, OriginalFormat};
 
 pub const FURRY_MAGIC: [u8; 8] = *b"FURRYFMT";
 pub const FURRY_VERSION: u16 = 1;
 pub const FURRY_HEADER_LEN: u16 = 96;
 
+/// HKDF-SHA256，16 字节 salt（`salt` 字段）
+pub const KDF_HKDF_SHA256: u16 = 1;
+/// HKDF-SHA256，32 字节 salt（`salt` 字段 + `reserved2`）
+pub const KDF_HKDF_SHA256_SALT32: u16 = 2;
+
+/// AES-256-GCM
+pub const AEAD_AES_256_GCM: u16 = 1;
+/// 当前支持的 chunk 头版本
+pub const CHUNK_HEADER_VERSION_V1: u16 = 1;
+
+/// 密钥派生算法（`kdf_id`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfAlgorithm {
+    /// HKDF-SHA256，16 字节 salt
+    HkdfSha256,
+    /// HKDF-SHA256，32 字节 salt
+    HkdfSha256Salt32,
+}
+
+impl KdfAlgorithm {
+    pub fn from_id(id: u16) -> Option<Self> {
+        match id {
+            KDF_HKDF_SHA256 => Some(Self::HkdfSha256),
+            KDF_HKDF_SHA256_SALT32 => Some(Self::HkdfSha256Salt32),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u16 {
+        match self {
+            Self::HkdfSha256 => KDF_HKDF_SHA256,
+            Self::HkdfSha256Salt32 => KDF_HKDF_SHA256_SALT32,
+        }
+    }
+}
+
+/// AEAD 算法（`aead_id`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AeadAlgorithm {
+    Aes256Gcm,
+}
+
+impl AeadAlgorithm {
+    pub fn from_id(id: u16) -> Option<Self> {
+        match id {
+            AEAD_AES_256_GCM => Some(Self::Aes256Gcm),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u16 {
+        match self {
+            Self::Aes256Gcm => AEAD_AES_256_GCM,
+        }
+    }
+}
+
+/// 头部标志位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HeaderFlags(u32);
+
+impl HeaderFlags {
+    /// 已定义的全部标志位
+    pub const KNOWN: u32 = FLAG_PLAINTEXT | FLAG_INDEX_MAC | FLAG_FORMAT_HINT | FLAG_META_KEY;
+
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// 是否为未加密调试文件（`FLAG_PLAINTEXT`）
+    pub fn is_plaintext(self) -> bool {
+        self.contains(FLAG_PLAINTEXT)
+    }
+
+    /// 是否带有头部 + 索引 MAC（`FLAG_INDEX_MAC`）
+    pub fn has_index_mac(self) -> bool {
+        self.contains(FLAG_INDEX_MAC)
+    }
+
+    /// 明文头部是否带有原始格式提示（`FLAG_FORMAT_HINT`）
+    pub fn has_format_hint(self) -> bool {
+        self.contains(FLAG_FORMAT_HINT)
+    }
+
+    /// META chunk 是否使用独立密钥加密（`FLAG_META_KEY`）
+    pub fn has_meta_key(self) -> bool {
+        self.contains(FLAG_META_KEY)
+    }
+
+    /// 本版本不认识的标志位
+    pub fn unknown_bits(self) -> u32 {
+        self.0 & !Self::KNOWN
+    }
+}
+
+/// 头部标志：chunk 未加密（tag 全零），仅供调试容器布局
+///
+/// **不安全**：音频与 META 以明文存储。只有启用 `insecure-plaintext` feature
+/// 时才能写入/读取此类文件，默认构建遇到该标志会拒绝打开。
+pub const FLAG_PLAINTEXT: u32 = 1 << 0;
+
+/// 头部标志：`reserved2` 存放覆盖头部与 INDEX 记录的 HMAC-SHA256（截断为 16 字节）
+///
+/// 防止把同一密钥下其他文件的索引拼接进来。与 32 字节 salt（同样占用
+/// `reserved2`）互斥。
+pub const FLAG_INDEX_MAC: u32 = 1 << 1;
+/// 头部标志：`format_hint` 存放原始格式（`OriginalFormat` 的取值）
+///
+/// 无需密钥即可判断文件类型。该字段未经认证，权威值仍是加密索引中的 `original_format`。
+pub const FLAG_FORMAT_HINT: u32 = 1 << 2;
+/// 头部标志：META chunk 使用独立派生的 `meta_aead_key` 与 nonce 前缀加密
+///
+/// 音频密钥泄露时 tags/封面/歌词仍受保护。AUDIO/PADDING/INDEX 不受影响。
+pub const FLAG_META_KEY: u32 = 1 << 3;
+/// 头部中索引 MAC 的长度
+pub const INDEX_MAC_LEN: usize = 16;
+
 /// .furry 文件主头部 (v1, 96 bytes)
 #[derive(Debug, Clone)]
 pub struct FurryHeaderV1 {
@@ -16,11 +141,15 @@ pub struct FurryHeaderV1 {
     pub header_size: u16,
     pub flags: u32,
     pub fake_header_len: u32,
+    /// INDEX 之后追加的无意义字节数（原 reserved0），读取与扫描在此之前结束
+    pub fake_footer_len: u32,
     pub file_id: [u8; 16],
     pub salt: [u8; 16],
     pub kdf_id: u16,
     pub aead_id: u16,
     pub chunk_header_version: u16,
+    /// 原始格式提示（原 reserved1），仅在 `FLAG_FORMAT_HINT` 置位时有意义
+    pub format_hint: u16,
     pub index_offset: u64,
     pub index_total_len: u32,
     pub header_crc32: u32,
@@ -34,11 +163,13 @@ impl FurryHeaderV1 {
             header_size: FURRY_HEADER_LEN,
             flags: 0,
             fake_header_len: 0,
+            fake_footer_len: 0,
             file_id,
             salt,
-            kdf_id: 1,  // HKDF-SHA256
-            aead_id: 1, // AES-256-GCM
-            chunk_header_version: 1,
+            kdf_id: KDF_HKDF_SHA256,
+            aead_id: AEAD_AES_256_GCM,
+            chunk_header_version: CHUNK_HEADER_VERSION_V1,
+            format_hint: 0,
             index_offset: 0,
             index_total_len: 0,
             header_crc32: 0,
@@ -46,6 +177,155 @@ impl FurryHeaderV1 {
         }
     }
 
+    /// 使用 32 字节 salt 创建头部（`kdf_id = 2`，后半段存放在 `reserved2`）
+    pub fn new_with_long_salt(file_id: [u8; 16], salt: [u8; furry_crypto::LONG_SALT_LEN]) -> Self {
+        let (lo, hi) = salt.split_at(16);
+        let mut header = Self::new(file_id, lo.try_into().expect("16-byte half"));
+        header.kdf_id = KDF_HKDF_SHA256_SALT32;
+        header.reserved2.copy_from_slice(hi);
+        header
+    }
+
+    /// 按 `kdf_id` 取出 HKDF salt
+    pub fn kdf_salt(&self) -> Result<Vec<u8>, FormatError> {
+        match self.kdf_id {
+            KDF_HKDF_SHA256 => Ok(self.salt.to_vec()),
+            KDF_HKDF_SHA256_SALT32 => Ok([self.salt, self.reserved2].concat()),
+            other => Err(FormatError::UnsupportedKdf(other)),
+        }
+    }
+
+    /// 明文头部中的原始格式提示，无需派生密钥
+    ///
+    /// **未经认证**：任何人都能改写该字段，只用于库扫描、未解锁时的预览等
+    /// "这是什么文件"的快速判断；权威值以加密索引中的 `original_format` 为准。
+    /// 未写入提示或提示为 `Unknown` 时返回 `None`。
+    pub fn original_format_hint(&self) -> Option<OriginalFormat> {
+        if !HeaderFlags::from_bits(self.flags).has_format_hint() {
+            return None;
+        }
+        Some(OriginalFormat::from_u8(self.format_hint as u8))
+            .filter(|format| *format != OriginalFormat::Unknown)
+    }
+
+    /// 写入原始格式提示并设置 `FLAG_FORMAT_HINT`；`None` 清除
+    pub fn set_original_format_hint(&mut self, format: Option<OriginalFormat>) {
+        match format {
+            Some(format) => {
+                self.flags |= FLAG_FORMAT_HINT;
+                self.format_hint = format as u16;
+            }
+            None => {
+                self.flags &= !FLAG_FORMAT_HINT;
+                self.format_hint = 0;
+            }
+        }
+    }
+
+    /// 检查算法 id、chunk 头版本与标志位是否受支持
+    ///
+    /// 在派生密钥前调用，未知算法给出明确错误，而不是在解密时才以认证失败告终。
+    pub fn check_supported(&self) -> Result<(), FormatError> {
+        if KdfAlgorithm::from_id(self.kdf_id).is_none() {
+            return Err(FormatError::UnsupportedKdf(self.kdf_id));
+        }
+        if AeadAlgorithm::from_id(self.aead_id).is_none() {
+            return Err(FormatError::UnsupportedAead(self.aead_id));
+        }
+        if self.chunk_header_version != CHUNK_HEADER_VERSION_V1 {
+            return Err(FormatError::UnsupportedChunkHeaderVersion(
+                self.chunk_header_version,
+            ));
+        }
+        let unknown = HeaderFlags::from_bits(self.flags).unknown_bits();
+        if unknown != 0 {
+            return Err(FormatError::UnsupportedFlags(unknown));
+        }
+        if self.flags & FLAG_INDEX_MAC != 0 && self.kdf_id == KDF_HKDF_SHA256_SALT32 {
+            return Err(FormatError::IndexMacUnavailable(
+                "reserved2 is occupied by the 32-byte salt",
+            ));
+        }
+        Ok(())
+    }
+
+    /// 按 chunk 类型选择 AEAD 密钥与 nonce 前缀（见 [`FLAG_META_KEY`]）
+    pub fn chunk_aead_keys<'k>(
+        &self,
+        keys: &'k furry_crypto::FileKeys,
+        chunk_type: crate::ChunkType,
+    ) -> (
+        &'k [u8; furry_crypto::AEAD_KEY_LEN],
+        &'k [u8; furry_crypto::NONCE_PREFIX_LEN],
+    ) {
+        if chunk_type == crate::ChunkType::Meta && self.flags & FLAG_META_KEY != 0 {
+            (&keys.meta_aead_key, &keys.meta_nonce_prefix)
+        } else {
+            (&keys.aead_key, &keys.nonce_prefix)
+        }
+    }
+
+    /// 计算索引 MAC：HMAC(integrity_key, header(reserved2 置零) || INDEX 记录)
+    pub fn index_mac(
+        &self,
+        keys: &furry_crypto::FileKeys,
+        index_record: &[u8],
+    ) -> Result<[u8; INDEX_MAC_LEN], FormatError> {
+        let mut unmacced = self.clone();
+        unmacced.reserved2 = [0u8; 16];
+        // CRC 在 MAC 之后计算，不纳入 MAC
+        unmacced.header_crc32 = 0;
+        let mut header_bytes = Vec::with_capacity(FURRY_HEADER_LEN as usize);
+        unmacced.write_to(&mut header_bytes)?;
+        let mac =
+            furry_crypto::hmac_sha256_parts(&keys.integrity_key, &[&header_bytes, index_record]);
+        let mut out = [0u8; INDEX_MAC_LEN];
+        out.copy_from_slice(&mac[..INDEX_MAC_LEN]);
+        Ok(out)
+    }
+
+    /// 头部 CRC32（`header_crc32` 字段按 0 计算）
+    pub fn compute_crc32(&self) -> u32 {
+        let mut unchecked = self.clone();
+        unchecked.header_crc32 = 0;
+        let mut bytes = Vec::with_capacity(FURRY_HEADER_LEN as usize);
+        unchecked
+            .write_to(&mut bytes)
+            .expect("writing to Vec cannot fail");
+        crc32fast::hash(&bytes)
+    }
+
+    /// 校验头部 CRC32；为 0 表示旧版写入器或未完成的文件，不校验
+    pub fn check_crc32(&self) -> Result<(), FormatError> {
+        if self.header_crc32 != 0 && self.header_crc32 != self.compute_crc32() {
+            return Err(FormatError::HeaderCrcMismatch);
+        }
+        Ok(())
+    }
+
+    /// 不派生密钥的快速校验：读取完整头部并检查版本、大小、CRC 与索引位置
+    ///
+    /// 只读 96 字节并取一次流长度，远快于 `FurryReader::open`；通过并不保证
+    /// 密钥正确或 chunk 未损坏，只用于"是否为完整 .furry 文件"的判断。
+    pub fn validate_quick<R: Read + Seek>(r: &mut R) -> Result<Self, FormatError> {
+        let stream_len = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(0))?;
+        let header = Self::read_from(r)?;
+        header.check_supported()?;
+        header.check_crc32()?;
+        let stream_len = header.logical_end(stream_len);
+        if header.index_offset < header.data_start_offset() {
+            return Err(FormatError::NotFinalized);
+        }
+        let index_end = header
+            .index_offset
+            .checked_add(header.index_total_len as u64);
+        if index_end.is_none_or(|end| end > stream_len) {
+            return Err(FormatError::CorruptIndex("index extends past end of file"));
+        }
+        Ok(header)
+    }
+
     pub fn read_from<R: Read>(r: &mut R) -> Result<Self, FormatError> {
         let mut magic = [0u8; 8];
         r.read_exact(&mut magic)?;
@@ -65,7 +345,7 @@ impl FurryHeaderV1 {
 
         let flags = r.read_u32::<LittleEndian>()?;
         let fake_header_len = r.read_u32::<LittleEndian>()?;
-        let _reserved0 = r.read_u32::<LittleEndian>()?;
+        let fake_footer_len = r.read_u32::<LittleEndian>()?;
 
         let mut file_id = [0u8; 16];
         r.read_exact(&mut file_id)?;
@@ -76,7 +356,7 @@ impl FurryHeaderV1 {
         let kdf_id = r.read_u16::<LittleEndian>()?;
         let aead_id = r.read_u16::<LittleEndian>()?;
         let chunk_header_version = r.read_u16::<LittleEndian>()?;
-        let _reserved1 = r.read_u16::<LittleEndian>()?;
+        let format_hint = r.read_u16::<LittleEndian>()?;
 
         let index_offset = r.read_u64::<LittleEndian>()?;
         let index_total_len = r.read_u32::<LittleEndian>()?;
@@ -90,11 +370,13 @@ impl FurryHeaderV1 {
             header_size,
             flags,
             fake_header_len,
+            fake_footer_len,
             file_id,
             salt,
             kdf_id,
             aead_id,
             chunk_header_version,
+            format_hint,
             index_offset,
             index_total_len,
             header_crc32,
@@ -108,13 +390,13 @@ impl FurryHeaderV1 {
         w.write_u16::<LittleEndian>(self.header_size)?;
         w.write_u32::<LittleEndian>(self.flags)?;
         w.write_u32::<LittleEndian>(self.fake_header_len)?;
-        w.write_u32::<LittleEndian>(0)?; // reserved0
+        w.write_u32::<LittleEndian>(self.fake_footer_len)?;
         w.write_all(&self.file_id)?;
         w.write_all(&self.salt)?;
         w.write_u16::<LittleEndian>(self.kdf_id)?;
         w.write_u16::<LittleEndian>(self.aead_id)?;
         w.write_u16::<LittleEndian>(self.chunk_header_version)?;
-        w.write_u16::<LittleEndian>(0)?; // reserved1
+        w.write_u16::<LittleEndian>(self.format_hint)?;
         w.write_u64::<LittleEndian>(self.index_offset)?;
         w.write_u32::<LittleEndian>(self.index_total_len)?;
         w.write_u32::<LittleEndian>(self.header_crc32)?;
@@ -122,6 +404,11 @@ impl FurryHeaderV1 {
         Ok(())
     }
 
+    /// 逻辑结尾：去掉 fake footer 后的长度（`stream_len` 为物理长度）
+    pub fn logical_end(&self, stream_len: u64) -> u64 {
+        stream_len.saturating_sub(self.fake_footer_len as u64)
+    }
+
     /// 计算数据起始偏移（跳过 fake header）
     pub fn data_start_offset(&self) -> u64 {
         FURRY_HEADER_LEN as u64 + self.fake_header_len as u64
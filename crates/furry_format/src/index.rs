@@ -1,6 +1,7 @@
 //! 索引定义
 
 use byteorder::{LittleEndian, ReadBytesExt};
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 
 use crate::{ChunkType, FormatError};
@@ -9,6 +10,20 @@ pub const INDEX_MAGIC: [u8; 8] = *b"FURRYIDX";
 pub const INDEX_VERSION: u16 = 1;
 pub const INDEX_HEADER_LEN: usize = 32;
 pub const INDEX_ENTRY_LEN: usize = 48;
+/// 紧凑条目长度：chunk_seq + file_offset + record_len + plain_len
+pub const INDEX_COMPACT_ENTRY_LEN: usize = 24;
+
+/// 索引标志：紧凑纯音频布局
+///
+/// 所有条目均为 AUDIO、无 chunk_flags 且 virtual_offset 按条目顺序连续时可用；
+/// 条目只存 [`INDEX_COMPACT_ENTRY_LEN`] 字节，virtual_offset 由 plain_len 累加还原。
+pub const INDEX_FLAG_COMPACT_AUDIO: u16 = 1 << 0;
+
+/// 索引标志：条目的 `reserved2` 存放该 chunk 磁盘上密文 + tag 的 CRC32
+///
+/// 仅用于诊断：AEAD 校验失败时据此区分"密文字节被改动"（CRC 不符）与
+/// "chunk 头/AAD 不一致"（CRC 相符）。紧凑布局放不下 CRC，设置后不再使用紧凑索引。
+pub const INDEX_FLAG_CHUNK_CRC: u16 = 1 << 1;
 
 /// 原始音频格式
 #[repr(u8)]
@@ -41,6 +56,24 @@ impl OriginalFormat {
             _ => Self::Unknown,
         }
     }
+
+    /// 规范的小写扩展名（不带点），未知格式为空字符串
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Mp3 => "mp3",
+            Self::Ogg => "ogg",
+            Self::Flac => "flac",
+            Self::Unknown => "",
+        }
+    }
+}
+
+/// 序列化（tags JSON、FFI/JNI 等）统一使用小写扩展名；`Debug` 仅用于日志
+impl std::fmt::Display for OriginalFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.extension())
+    }
 }
 
 /// 索引头 (v1, 32 bytes)
@@ -125,7 +158,7 @@ impl IndexEntryV1 {
             chunk_type: ChunkType::Meta,
             chunk_flags,
             reserved0: 0,
-            meta_kind: meta_kind as u16,
+            meta_kind: meta_kind.id(),
             reserved1: 0,
             reserved2: 0,
             reserved3: 0,
@@ -151,22 +184,100 @@ impl IndexEntryV1 {
 }
 
 /// META 类型
-#[repr(u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// 磁盘上以 `u16` 存储。本库不认识的取值解析为 [`MetaKind::Custom`] 并保留原始 id，
+/// 应用可借此存放自定义元数据而无需修改格式；读取上限按保守的 Tags 上限处理。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MetaKind {
-    Unknown = 0,
-    CoverArt = 1,
-    Lyrics = 2,
-    Tags = 3,
+    /// id 0：恢复扫描中无法确定类型的 META
+    Unknown,
+    CoverArt,
+    Lyrics,
+    Tags,
+    SourceInfo,
+    /// 源文件原始标签块，见 [`crate::RawTagFormat`]
+    RawTagBlock,
+    /// 无缝播放裁剪信息，见 [`crate::GaplessInfo`]
+    Gapless,
+    /// 多曲目专辑的曲目表，见 [`crate::TrackTable`]
+    TrackTable,
+    /// 应用自定义类型（建议使用 `0x1000` 以上的 id，避免与将来的内置类型冲突）
+    ///
+    /// id 不得与内置类型相同，否则写入时返回 `ReservedMetaKind`；可用 [`MetaKind::custom`] 预先检查。
+    Custom(u16),
 }
 
 impl MetaKind {
     pub fn from_u16(v: u16) -> Self {
         match v {
+            0 => Self::Unknown,
             1 => Self::CoverArt,
             2 => Self::Lyrics,
             3 => Self::Tags,
-            _ => Self::Unknown,
+            4 => Self::SourceInfo,
+            10 => Self::RawTagBlock,
+            11 => Self::Gapless,
+            12 => Self::TrackTable,
+            other => Self::Custom(other),
+        }
+    }
+
+    /// 磁盘上的 `meta_kind` 取值
+    pub fn id(self) -> u16 {
+        match self {
+            Self::Unknown => 0,
+            Self::CoverArt => 1,
+            Self::Lyrics => 2,
+            Self::Tags => 3,
+            Self::SourceInfo => 4,
+            Self::RawTagBlock => 10,
+            Self::Gapless => 11,
+            Self::TrackTable => 12,
+            Self::Custom(id) => id,
+        }
+    }
+
+    /// 构造自定义类型；`id` 已被内置类型（含 `Unknown`）占用时返回 `None`
+    pub fn custom(id: u16) -> Option<Self> {
+        match Self::from_u16(id) {
+            custom @ Self::Custom(_) => Some(custom),
+            _ => None,
+        }
+    }
+
+    /// 是否为本库定义的类型（`Unknown` 与 `Custom` 以外）
+    pub fn is_builtin(self) -> bool {
+        !matches!(self, Self::Unknown | Self::Custom(_))
+    }
+
+    /// 稳定的 snake_case 名称（用于 JSON/诊断输出）；自定义类型统一为 `custom`，见 `Display`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::CoverArt => "cover_art",
+            Self::Lyrics => "lyrics",
+            Self::Tags => "tags",
+            Self::SourceInfo => "source_info",
+            Self::RawTagBlock => "raw_tag_block",
+            Self::Gapless => "gapless",
+            Self::TrackTable => "track_table",
+            Self::Custom(_) => "custom",
+        }
+    }
+}
+
+impl From<MetaKind> for u16 {
+    fn from(kind: MetaKind) -> Self {
+        kind.id()
+    }
+}
+
+/// 与 `as_str` 相同，自定义类型带上 id（如 `custom_4096`）
+impl std::fmt::Display for MetaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Custom(id) => write!(f, "custom_{}", id),
+            other => f.write_str(other.as_str()),
         }
     }
 }
@@ -191,99 +302,82 @@ impl FurryIndexV1 {
         self.header.entry_count = self.entries.len() as u32;
     }
 
-    /// 从解密后的明文解析索引
-    pub fn parse(plain: &[u8]) -> Result<Self, FormatError> {
-        if plain.len() < INDEX_HEADER_LEN {
-            return Err(FormatError::CorruptIndex("index header too short"));
-        }
-
-        let mut cur = Cursor::new(plain);
-
-        // 读取魔数
-        let mut magic = [0u8; 8];
-        cur.read_exact(&mut magic)?;
-        if magic != INDEX_MAGIC {
-            return Err(FormatError::InvalidIndexMagic);
-        }
-
-        let version = cur.read_u16::<LittleEndian>()?;
-        if version != INDEX_VERSION {
-            return Err(FormatError::UnsupportedIndexVersion(version));
-        }
+    /// 条目能否用紧凑布局无损表示（见 [`INDEX_FLAG_COMPACT_AUDIO`]）
+    pub fn is_compact_eligible(&self) -> bool {
+        let mut next_offset = 0u64;
+        self.entries.iter().all(|e| {
+            let contiguous = e.virtual_offset == next_offset;
+            next_offset += e.plain_len as u64;
+            e.chunk_type == ChunkType::Audio && e.chunk_flags == 0 && e.meta_kind == 0 && contiguous
+        })
+    }
 
-        let flags = cur.read_u16::<LittleEndian>()?;
-        let entry_count = cur.read_u32::<LittleEndian>()?;
-        let audio_stream_len = cur.read_u64::<LittleEndian>()?;
-        let original_format = OriginalFormat::from_u8(cur.read_u8()?);
+    /// 是否以紧凑布局序列化：设置了标志、未记录 chunk CRC 且条目满足条件
+    fn writes_compact(&self) -> bool {
+        self.header.flags & INDEX_FLAG_COMPACT_AUDIO != 0
+            && !self.has_chunk_crc()
+            && self.is_compact_eligible()
+    }
 
-        let mut reserved = [0u8; 7];
-        cur.read_exact(&mut reserved)?;
+    /// 条目是否记录了 chunk CRC（见 [`INDEX_FLAG_CHUNK_CRC`]）
+    pub fn has_chunk_crc(&self) -> bool {
+        self.header.flags & INDEX_FLAG_CHUNK_CRC != 0
+    }
 
-        let header = IndexHeaderV1 {
-            version,
-            flags,
-            entry_count,
-            audio_stream_len,
-            original_format,
-            reserved,
+    /// `to_bytes` 输出的长度
+    pub fn serialized_len(&self) -> usize {
+        let entry_len = if self.writes_compact() {
+            INDEX_COMPACT_ENTRY_LEN
+        } else {
+            INDEX_ENTRY_LEN
         };
+        INDEX_HEADER_LEN + self.entries.len() * entry_len
+    }
 
-        // 验证长度
-        let expected_len = INDEX_HEADER_LEN + (entry_count as usize) * INDEX_ENTRY_LEN;
-        if plain.len() != expected_len {
-            return Err(FormatError::CorruptIndex("index length mismatch"));
-        }
-
-        // 读取条目
-        let mut entries = Vec::with_capacity(entry_count as usize);
-        for _ in 0..entry_count {
-            let chunk_seq = cur.read_u64::<LittleEndian>()?;
-            let file_offset = cur.read_u64::<LittleEndian>()?;
-            let record_len = cur.read_u32::<LittleEndian>()?;
-            let plain_len = cur.read_u32::<LittleEndian>()?;
-            let virtual_offset = cur.read_u64::<LittleEndian>()?;
-            let chunk_type = ChunkType::from_u8(cur.read_u8()?)
-                .ok_or(FormatError::CorruptIndex("unknown chunk_type in index"))?;
-            let chunk_flags = cur.read_u8()?;
-            let reserved0 = cur.read_u16::<LittleEndian>()?;
-            let meta_kind = cur.read_u16::<LittleEndian>()?;
-            let reserved1 = cur.read_u16::<LittleEndian>()?;
-            let reserved2 = cur.read_u32::<LittleEndian>()?;
-            let reserved3 = cur.read_u32::<LittleEndian>()?;
-
-            entries.push(IndexEntryV1 {
-                chunk_seq,
-                file_offset,
-                record_len,
-                plain_len,
-                virtual_offset,
-                chunk_type,
-                chunk_flags,
-                reserved0,
-                meta_kind,
-                reserved1,
-                reserved2,
-                reserved3,
-            });
+    /// 从解密后的明文解析索引
+    ///
+    /// 一次性解析全部条目；只需顺序遍历（如百万级小 chunk 的扫描）时用
+    /// [`IndexEntryIter`]，不必先物化整个 `Vec`。
+    pub fn parse(plain: &[u8]) -> Result<Self, FormatError> {
+        let (header, iter) = IndexEntryIter::new(plain)?;
+        let mut entries = Vec::with_capacity(header.entry_count as usize);
+        for entry in iter {
+            entries.push(entry?);
         }
-
         Ok(Self { header, entries })
     }
 
     /// 序列化为字节（加密前）
+    ///
+    /// 设置了 [`INDEX_FLAG_COMPACT_AUDIO`] 但条目不满足条件时退回完整布局并清除该标志。
     pub fn to_bytes(&self) -> Vec<u8> {
-        let len = INDEX_HEADER_LEN + self.entries.len() * INDEX_ENTRY_LEN;
-        let mut buf = Vec::with_capacity(len);
+        let compact = self.writes_compact();
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        let flags = if compact {
+            self.header.flags
+        } else {
+            self.header.flags & !INDEX_FLAG_COMPACT_AUDIO
+        };
 
         // 写入头部
         buf.extend_from_slice(&INDEX_MAGIC);
         buf.extend_from_slice(&self.header.version.to_le_bytes());
-        buf.extend_from_slice(&self.header.flags.to_le_bytes());
+        buf.extend_from_slice(&flags.to_le_bytes());
         buf.extend_from_slice(&self.header.entry_count.to_le_bytes());
         buf.extend_from_slice(&self.header.audio_stream_len.to_le_bytes());
         buf.push(self.header.original_format as u8);
         buf.extend_from_slice(&self.header.reserved);
 
+        if compact {
+            for entry in &self.entries {
+                buf.extend_from_slice(&entry.chunk_seq.to_le_bytes());
+                buf.extend_from_slice(&entry.file_offset.to_le_bytes());
+                buf.extend_from_slice(&entry.record_len.to_le_bytes());
+                buf.extend_from_slice(&entry.plain_len.to_le_bytes());
+            }
+            return buf;
+        }
+
         // 写入条目
         for entry in &self.entries {
             buf.extend_from_slice(&entry.chunk_seq.to_le_bytes());
@@ -303,6 +397,11 @@ impl FurryIndexV1 {
         buf
     }
 
+    /// 按 chunk_seq 查找条目
+    pub fn entry_by_seq(&self, seq: u64) -> Option<&IndexEntryV1> {
+        self.entries.iter().find(|e| e.chunk_seq == seq)
+    }
+
     /// 获取所有 AUDIO 条目（按 virtual_offset 排序）
     pub fn audio_entries(&self) -> Vec<&IndexEntryV1> {
         let mut entries: Vec<_> = self
@@ -327,9 +426,179 @@ impl FurryIndexV1 {
         let mut entries: Vec<_> = self
             .entries
             .iter()
-            .filter(|e| e.chunk_type == ChunkType::Meta && MetaKind::from_u16(e.meta_kind) == kind)
+            .filter(|e| e.chunk_type == ChunkType::Meta && e.meta_kind == kind.id())
             .collect();
         entries.sort_by_key(|e| e.chunk_seq);
         entries
     }
+
+    /// 按 META kind 汇总磁盘占用（record_len 之和，含 chunk 头与 tag）
+    pub fn meta_size_by_kind(&self) -> HashMap<MetaKind, u64> {
+        let mut sizes = HashMap::new();
+        for e in self
+            .entries
+            .iter()
+            .filter(|e| e.chunk_type == ChunkType::Meta)
+        {
+            *sizes.entry(MetaKind::from_u16(e.meta_kind)).or_insert(0) += e.record_len as u64;
+        }
+        sizes
+    }
+
+    /// 按 chunk 类型汇总磁盘占用（不含文件头与 INDEX 记录本身）
+    pub fn size_by_chunk_type(&self) -> HashMap<ChunkType, u64> {
+        let mut sizes = HashMap::new();
+        for e in &self.entries {
+            *sizes.entry(e.chunk_type).or_insert(0) += e.record_len as u64;
+        }
+        sizes
+    }
+}
+
+/// 索引条目的惰性解析器
+///
+/// 直接在解密后的索引明文上逐条解析，内存占用与条目数无关。
+/// 头部与总长度在构造时校验，单个条目的错误（未知 chunk_type）在迭代时返回，之后迭代结束。
+#[derive(Debug, Clone)]
+pub struct IndexEntryIter<'a> {
+    cur: &'a [u8],
+    remaining: u32,
+    compact: bool,
+    /// 紧凑布局下累加的 virtual_offset
+    virtual_offset: u64,
+}
+
+impl<'a> IndexEntryIter<'a> {
+    /// 解析并校验索引头，返回头部与条目迭代器
+    pub fn new(plain: &'a [u8]) -> Result<(IndexHeaderV1, Self), FormatError> {
+        if plain.len() < INDEX_HEADER_LEN {
+            return Err(FormatError::CorruptIndex("index header too short"));
+        }
+
+        let mut cur = Cursor::new(plain);
+
+        // 读取魔数
+        let mut magic = [0u8; 8];
+        cur.read_exact(&mut magic)?;
+        if magic != INDEX_MAGIC {
+            return Err(FormatError::InvalidIndexMagic);
+        }
+
+        let version = cur.read_u16::<LittleEndian>()?;
+        if version != INDEX_VERSION {
+            return Err(FormatError::UnsupportedIndexVersion(version));
+        }
+
+        let flags = cur.read_u16::<LittleEndian>()?;
+        let entry_count = cur.read_u32::<LittleEndian>()?;
+        let audio_stream_len = cur.read_u64::<LittleEndian>()?;
+        let original_format = OriginalFormat::from_u8(cur.read_u8()?);
+
+        let mut reserved = [0u8; 7];
+        cur.read_exact(&mut reserved)?;
+
+        let header = IndexHeaderV1 {
+            version,
+            flags,
+            entry_count,
+            audio_stream_len,
+            original_format,
+            reserved,
+        };
+
+        let compact = flags & INDEX_FLAG_COMPACT_AUDIO != 0;
+        let entry_len = if compact {
+            INDEX_COMPACT_ENTRY_LEN
+        } else {
+            INDEX_ENTRY_LEN
+        };
+
+        // 验证长度
+        // 32 位平台上 entry_count * 48 可能溢出，用 checked 运算
+        let expected_len = (entry_count as usize)
+            .checked_mul(entry_len)
+            .and_then(|n| n.checked_add(INDEX_HEADER_LEN));
+        if expected_len != Some(plain.len()) {
+            return Err(FormatError::CorruptIndex("index length mismatch"));
+        }
+
+        let iter = Self {
+            cur: &plain[INDEX_HEADER_LEN..],
+            remaining: entry_count,
+            compact,
+            virtual_offset: 0,
+        };
+        Ok((header, iter))
+    }
+
+    /// 只产出指定类型的条目，不收集中间结果
+    pub fn of_type(
+        self,
+        chunk_type: ChunkType,
+    ) -> impl Iterator<Item = Result<IndexEntryV1, FormatError>> + 'a {
+        self.filter(move |e| e.as_ref().map_or(true, |e| e.chunk_type == chunk_type))
+    }
+
+    fn read_entry(&mut self) -> Result<IndexEntryV1, FormatError> {
+        let cur = &mut self.cur;
+        let chunk_seq = cur.read_u64::<LittleEndian>()?;
+        let file_offset = cur.read_u64::<LittleEndian>()?;
+        let record_len = cur.read_u32::<LittleEndian>()?;
+        let plain_len = cur.read_u32::<LittleEndian>()?;
+
+        if self.compact {
+            let entry = IndexEntryV1::new_audio(
+                chunk_seq,
+                file_offset,
+                record_len,
+                plain_len,
+                self.virtual_offset,
+            );
+            self.virtual_offset += plain_len as u64;
+            return Ok(entry);
+        }
+
+        let virtual_offset = cur.read_u64::<LittleEndian>()?;
+        let chunk_type = ChunkType::from_u8(cur.read_u8()?)
+            .ok_or(FormatError::CorruptIndex("unknown chunk_type in index"))?;
+        let chunk_flags = cur.read_u8()?;
+        let reserved0 = cur.read_u16::<LittleEndian>()?;
+        let meta_kind = cur.read_u16::<LittleEndian>()?;
+        let reserved1 = cur.read_u16::<LittleEndian>()?;
+        let reserved2 = cur.read_u32::<LittleEndian>()?;
+        let reserved3 = cur.read_u32::<LittleEndian>()?;
+
+        Ok(IndexEntryV1 {
+            chunk_seq,
+            file_offset,
+            record_len,
+            plain_len,
+            virtual_offset,
+            chunk_type,
+            chunk_flags,
+            reserved0,
+            meta_kind,
+            reserved1,
+            reserved2,
+            reserved3,
+        })
+    }
+}
+
+impl Iterator for IndexEntryIter<'_> {
+    type Item = Result<IndexEntryV1, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let entry = self.read_entry();
+        // 出错后不再继续解析
+        self.remaining = if entry.is_ok() { self.remaining - 1 } else { 0 };
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
 }
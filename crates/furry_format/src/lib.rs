@@ -1,15 +1,31 @@
 //! furry_format - .furry 文件格式读写库
 
 mod chunk;
+mod compression;
+mod cover;
+mod gapless;
 mod header;
 mod index;
+mod lyrics;
+mod raw_tags;
 mod reader;
+mod source_info;
+mod track_table;
+mod verify;
 mod writer;
 
 pub use chunk::*;
+pub use compression::*;
+pub use cover::*;
+pub use gapless::*;
 pub use header::*;
 pub use index::*;
+pub use lyrics::*;
+pub use raw_tags::*;
 pub use reader::*;
+pub use source_info::*;
+pub use track_table::*;
+pub use verify::*;
 pub use writer::*;
 
 /// 格式错误
@@ -39,9 +55,63 @@ pub enum FormatError {
     #[error("Unsupported index version: {0}")]
     UnsupportedIndexVersion(u16),
 
+    #[error("Unsupported KDF id: {0}")]
+    UnsupportedKdf(u16),
+
+    #[error("Chunk too large: {0} bytes (max {max})", max = crate::MAX_CHUNK_PLAIN_LEN)]
+    ChunkTooLarge(usize),
+
+    #[error("Too many chunks (max {0})")]
+    TooManyChunks(usize),
+
+    #[error("Unsupported AEAD id: {0}")]
+    UnsupportedAead(u16),
+
+    #[error("Unsupported header flags: {0:#x}")]
+    UnsupportedFlags(u32),
+
+    #[error("File was never finalized (no index); use open_with_recovery")]
+    NotFinalized,
+
+    #[error("Index MAC unavailable: {0}")]
+    IndexMacUnavailable(&'static str),
+
+    #[error("Header flags cannot change after chunks have been written")]
+    HeaderFlagsLocked,
+
+    #[error("Header CRC32 mismatch")]
+    HeaderCrcMismatch,
+
+    #[error("Header/index MAC mismatch (index may have been substituted)")]
+    IndexMacMismatch,
+
+    #[error("Unencrypted debug file (FLAG_PLAINTEXT); enable the `insecure-plaintext` feature to read it")]
+    PlaintextNotEnabled,
+
     #[error("Crypto error: {0}")]
     Crypto(#[from] furry_crypto::CryptoError),
 
     #[error("Corrupt index: {0}")]
     CorruptIndex(&'static str),
+
+    #[error("Corrupt META: {0}")]
+    CorruptMeta(&'static str),
+
+    #[error("Unsupported chunk flag: {0:#04x}")]
+    UnsupportedChunkFlag(u8),
+
+    #[error("Custom META kind {0} collides with a built-in kind")]
+    ReservedMetaKind(u16),
+
+    #[error("No candidate master key matches this file")]
+    NoMatchingKey,
+
+    #[error("Too many candidate master keys: {0} (max {max})", max = crate::MAX_CANDIDATE_KEYS)]
+    TooManyCandidateKeys(usize),
+
+    #[error("Content needs {needed} bytes, exceeds padding target {target}")]
+    PaddingTargetTooSmall { needed: u64, target: u64 },
+
+    #[error("Cannot pad exactly {0} bytes (a padding record costs at least {PADDING_RECORD_OVERHEAD} bytes)")]
+    PaddingTargetUnreachable(u64),
 }
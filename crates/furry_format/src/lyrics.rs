@@ -0,0 +1,44 @@
+//! 歌词 META 载荷
+//!
+//! 标准载荷为 `lang\0<text>`，lang 为 ISO-639 语言代码（2~3 个 ASCII 字母，小写）；
+//! 旧文件直接写入歌词文本，视为 `und`（未指定语言）。
+
+/// 未指定语言
+pub const LYRICS_LANG_UNDETERMINED: &str = "und";
+
+/// 编码为标准 `lang\0<text>` 载荷；语言代码不合法时使用 `und`
+pub fn encode_lyrics_payload(lang: &str, text: &str) -> Vec<u8> {
+    let lang = sanitize_lang(lang).unwrap_or_else(|| LYRICS_LANG_UNDETERMINED.to_string());
+    let mut out = Vec::with_capacity(lang.len() + 1 + text.len());
+    out.extend_from_slice(lang.as_bytes());
+    out.push(0);
+    out.extend_from_slice(text.as_bytes());
+    out
+}
+
+/// 宽松解析歌词载荷，返回 `(lang, text)`
+///
+/// 仅当第一个 NUL 之前是合法语言代码时才视为前缀；否则整个载荷即歌词文本。
+pub fn parse_lyrics_payload(payload: &[u8]) -> (String, String) {
+    if let Some(nul) = payload.iter().take(4).position(|b| *b == 0) {
+        if let Some(lang) = std::str::from_utf8(&payload[..nul])
+            .ok()
+            .and_then(sanitize_lang)
+        {
+            let text = String::from_utf8_lossy(&payload[nul + 1..]).into_owned();
+            return (lang, text);
+        }
+    }
+    (
+        LYRICS_LANG_UNDETERMINED.to_string(),
+        String::from_utf8_lossy(payload).into_owned(),
+    )
+}
+
+/// 语言代码仅允许 2~3 个 ASCII 字母，统一小写；不合法时丢弃
+pub fn sanitize_lang(lang: &str) -> Option<String> {
+    if !(2..=3).contains(&lang.len()) || !lang.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(lang.to_ascii_lowercase())
+}
@@ -0,0 +1,88 @@
+//! 原始标签块 META 载荷（`MetaKind::RawTagBlock`）
+//!
+//! 载荷为 `kind\0<block-bytes>`，保存源文件中未经解析的标签块（ID3v2 整体、
+//! FLAC VORBIS_COMMENT 元数据块），保留 tags JSON 无法表达的帧（replaygain、
+//! MusicBrainz id、自定义 TXXX 等）。
+
+/// 原始标签块类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawTagFormat {
+    /// ID3v2 标签（含 10 字节头与可选 footer）
+    Id3v2,
+    /// FLAC VORBIS_COMMENT 元数据块（含 4 字节块头）
+    FlacVorbisComment,
+}
+
+impl RawTagFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Id3v2 => "id3v2",
+            Self::FlacVorbisComment => "flac_vorbis_comment",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "id3v2" => Some(Self::Id3v2),
+            "flac_vorbis_comment" => Some(Self::FlacVorbisComment),
+            _ => None,
+        }
+    }
+}
+
+/// 编码为 `kind\0<block>` 载荷
+pub fn encode_raw_tag_payload(format: RawTagFormat, block: &[u8]) -> Vec<u8> {
+    let kind = format.as_str();
+    let mut out = Vec::with_capacity(kind.len() + 1 + block.len());
+    out.extend_from_slice(kind.as_bytes());
+    out.push(0);
+    out.extend_from_slice(block);
+    out
+}
+
+/// 解析 `kind\0<block>` 载荷；kind 未知时返回 `None`
+pub fn parse_raw_tag_payload(payload: &[u8]) -> Option<(RawTagFormat, &[u8])> {
+    let nul = payload.iter().take(32).position(|b| *b == 0)?;
+    let format = RawTagFormat::parse(std::str::from_utf8(&payload[..nul]).ok()?)?;
+    Some((format, &payload[nul + 1..]))
+}
+
+/// 在文件开头的字节中定位原始标签块；`head` 不足以容纳整个块时返回 `None`
+pub fn find_raw_tag_block(head: &[u8]) -> Option<(RawTagFormat, &[u8])> {
+    if head.len() >= 10 && head.starts_with(b"ID3") {
+        // 标签大小为 4 字节 synchsafe 整数，不含 10 字节头；flags bit4 表示有 footer
+        let size_bytes = &head[6..10];
+        if size_bytes.iter().any(|b| b & 0x80 != 0) {
+            return None;
+        }
+        let size = size_bytes
+            .iter()
+            .fold(0usize, |acc, b| (acc << 7) | *b as usize);
+        let footer = if head[5] & 0x10 != 0 { 10 } else { 0 };
+        let total = 10 + size + footer;
+        return head.get(..total).map(|block| (RawTagFormat::Id3v2, block));
+    }
+
+    if head.starts_with(b"fLaC") {
+        let mut pos = 4;
+        loop {
+            let block_header = head.get(pos..pos + 4)?;
+            let is_last = block_header[0] & 0x80 != 0;
+            let block_type = block_header[0] & 0x7F;
+            let len =
+                u32::from_be_bytes([0, block_header[1], block_header[2], block_header[3]]) as usize;
+            let end = pos + 4 + len;
+            if block_type == 4 {
+                return head
+                    .get(pos..end)
+                    .map(|block| (RawTagFormat::FlacVorbisComment, block));
+            }
+            if is_last {
+                return None;
+            }
+            pos = end;
+        }
+    }
+
+    None
+}
@@ -1,10 +1,34 @@
 //! .furry 文件读取器
+//!
+//! 头部为明文，不需要密钥即可用 [`FurryHeaderV1::read_from`] 读取；索引是加密的，
+//! 因此定位任何 chunk 都需要主密钥。持有密钥后，[`FurryReader::read_raw_chunk`]
+//! 可原样取出记录（仍为密文），供备份/同步工具按 chunk 增量上传。
 
 use std::io::{Read, Seek, SeekFrom};
 
 use furry_crypto::{FileKeys, MasterKey};
 
-use crate::{ChunkRecordHeaderV1, ChunkType, FormatError, FurryHeaderV1, FurryIndexV1};
+use crate::{
+    ChunkRecordHeaderV1, ChunkType, FormatError, FurryHeaderV1, FurryIndexV1, FURRY_HEADER_LEN,
+};
+
+/// tags META 大小上限
+pub const MAX_TAGS_BYTES: u32 = 256 * 1024; // 256 KiB
+/// lyrics META 大小上限
+pub const MAX_LYRICS_BYTES: u32 = 2 * 1024 * 1024; // 2 MiB
+/// 原始标签块 META 大小上限（ID3v2 可能内嵌图片）
+pub const MAX_RAW_TAG_BLOCK_BYTES: u32 = 16 * 1024 * 1024; // 16 MiB
+/// SourceInfo META 大小上限
+pub const MAX_SOURCE_INFO_BYTES: u32 = 4 * 1024; // 4 KiB
+
+/// 封面 META 大小上限（包含 `mime\0` 前缀）
+///
+/// Cover art can be large; keep this high to avoid unexpectedly dropping art.
+/// NOTE: Very large covers may increase memory usage on mobile.
+pub const MAX_COVER_BYTES: u32 = 64 * 1024 * 1024; // 64 MiB
+
+/// [`FurryReader::open_with_keys_try`] 最多尝试的候选主密钥数
+pub const MAX_CANDIDATE_KEYS: usize = 8;
 
 /// .furry 文件读取器
 pub struct FurryReader<R: Read + Seek> {
@@ -12,46 +36,257 @@ pub struct FurryReader<R: Read + Seek> {
     pub header: FurryHeaderV1,
     pub keys: FileKeys,
     pub index: FurryIndexV1,
+    /// 底层流逻辑长度（不含 fake footer），用于在分配缓冲区前校验记录范围
+    stream_len: u64,
+    /// 索引由扫描 chunk 重建（见 [`Self::open_with_recovery`]）
+    recovered: bool,
 }
 
 impl<R: Read + Seek> FurryReader<R> {
     /// 打开 .furry 文件
-    pub fn open(mut inner: R, master_key: &MasterKey) -> Result<Self, FormatError> {
-        inner.seek(SeekFrom::Start(0))?;
-        let header = FurryHeaderV1::read_from(&mut inner)?;
+    ///
+    /// 只读取头部与索引，不会读取任何 AUDIO/META chunk，见 [`Self::open_index_only`]。
+    pub fn open(inner: R, master_key: &MasterKey) -> Result<Self, FormatError> {
+        Self::open_index_only(inner, master_key)
+    }
 
-        let keys = furry_crypto::derive_file_keys(master_key, &header.salt)?;
-        let index = Self::read_and_decrypt_index(&mut inner, &header, &keys)?;
+    /// 仅读取头部并解密索引（适合大批量扫描曲库）
+    ///
+    /// 开销为一次 HKDF 派生 + 一次索引 AEAD 解密；头部和索引记录各用一次
+    /// `read_exact` 读入，避免在无缓冲的 `File` 上产生大量小读取。
+    /// 之后可按需调用 [`Self::read_latest_meta`] 读取单个 META。
+    pub fn open_index_only(mut inner: R, master_key: &MasterKey) -> Result<Self, FormatError> {
+        let (header, keys, stream_len) = Self::read_header(&mut inner, master_key)?;
+        // 写入器在 finish 时才回填 index_offset，为 0 说明写入中断
+        if header.index_offset < header.data_start_offset() {
+            return Err(FormatError::NotFinalized);
+        }
+        let index = Self::read_and_decrypt_index(&mut inner, &header, &keys, stream_len)?;
 
         Ok(Self {
             inner,
             header,
             keys,
             index,
+            stream_len,
+            recovered: false,
         })
     }
 
+    /// 依次用候选主密钥打开（密钥轮换期间新旧密钥并存，不知道文件用的是哪一个）
+    ///
+    /// 头部只读取一次，以索引能否通过认证区分密钥；未匹配的派生密钥随即丢弃（drop 时清零）。
+    /// 都不匹配时返回 `NoMatchingKey`，候选超过 [`MAX_CANDIDATE_KEYS`] 个时直接拒绝。
+    pub fn open_with_keys_try(
+        mut inner: R,
+        master_keys: &[&MasterKey],
+    ) -> Result<Self, FormatError> {
+        if master_keys.len() > MAX_CANDIDATE_KEYS {
+            return Err(FormatError::TooManyCandidateKeys(master_keys.len()));
+        }
+        let (header, stream_len) = Self::read_header_unkeyed(&mut inner)?;
+        if header.index_offset < header.data_start_offset() {
+            return Err(FormatError::NotFinalized);
+        }
+        let salt = header.kdf_salt()?;
+        for master_key in master_keys {
+            let keys = furry_crypto::derive_file_keys(*master_key, &salt)?;
+            match Self::read_and_decrypt_index(&mut inner, &header, &keys, stream_len) {
+                Ok(index) => {
+                    return Ok(Self {
+                        inner,
+                        header,
+                        keys,
+                        index,
+                        stream_len,
+                        recovered: false,
+                    })
+                }
+                Err(FormatError::Crypto(_) | FormatError::IndexMacMismatch) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(FormatError::NoMatchingKey)
+    }
+
+    /// 打开文件；未完成写入（[`FormatError::NotFinalized`]）时扫描 chunk 重建索引
+    ///
+    /// 扫描从数据区开始逐条校验 chunk（含 AEAD 认证），遇到截断或损坏的记录即停止。
+    /// 重建的索引中 META 类型未知（kind 只记录在索引里），原始格式为 `Unknown`。
+    pub fn open_with_recovery(mut inner: R, master_key: &MasterKey) -> Result<Self, FormatError> {
+        let (header, keys, stream_len) = Self::read_header(&mut inner, master_key)?;
+        if header.index_offset >= header.data_start_offset() {
+            inner.seek(SeekFrom::Start(0))?;
+            return Self::open_index_only(inner, master_key);
+        }
+        let index = Self::scan_chunks(&mut inner, &header, &keys, stream_len)?;
+
+        Ok(Self {
+            inner,
+            header,
+            keys,
+            index,
+            stream_len,
+            recovered: true,
+        })
+    }
+
+    /// 索引是否由扫描重建
+    pub fn is_recovered(&self) -> bool {
+        self.recovered
+    }
+
+    fn read_header(
+        inner: &mut R,
+        master_key: &MasterKey,
+    ) -> Result<(FurryHeaderV1, FileKeys, u64), FormatError> {
+        let (header, stream_len) = Self::read_header_unkeyed(inner)?;
+        let keys = furry_crypto::derive_file_keys(master_key, &header.kdf_salt()?)?;
+        Ok((header, keys, stream_len))
+    }
+
+    /// 读取并校验头部，返回头部与逻辑流长度（不派生密钥）
+    fn read_header_unkeyed(inner: &mut R) -> Result<(FurryHeaderV1, u64), FormatError> {
+        let stream_len = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(0))?;
+        let mut header_buf = [0u8; FURRY_HEADER_LEN as usize];
+        inner
+            .read_exact(&mut header_buf)
+            .map_err(|e| eof_as_corrupt(e.into(), "header truncated"))?;
+        let header = FurryHeaderV1::read_from(&mut &header_buf[..])?;
+        header.check_supported()?;
+        header.check_crc32()?;
+        // 之后的范围检查与扫描都以逻辑结尾为界，忽略追加的 fake footer
+        let stream_len = header.logical_end(stream_len);
+        if header.flags & crate::FLAG_PLAINTEXT != 0 && !cfg!(feature = "insecure-plaintext") {
+            return Err(FormatError::PlaintextNotEnabled);
+        }
+        Ok((header, stream_len))
+    }
+
+    fn scan_chunks(
+        inner: &mut R,
+        header: &FurryHeaderV1,
+        keys: &FileKeys,
+        stream_len: u64,
+    ) -> Result<FurryIndexV1, FormatError> {
+        let mut index = FurryIndexV1::new(0, crate::OriginalFormat::Unknown);
+        let mut offset = header.data_start_offset();
+        let mut buf = Vec::new();
+        loop {
+            inner.seek(SeekFrom::Start(offset))?;
+            let Ok(chunk_header) = ChunkRecordHeaderV1::read_from(inner) else {
+                break;
+            };
+            let record_len = chunk_header.record_len();
+            if !range_in_stream(offset, record_len, stream_len) {
+                break;
+            }
+            buf.resize(chunk_header.plain_len as usize, 0);
+            inner.read_exact(&mut buf)?;
+            let mut tag = [0u8; furry_crypto::TAG_LEN];
+            inner.read_exact(&mut tag)?;
+            if Self::open_in_place(header, keys, &chunk_header, &mut buf, &tag).is_err() {
+                break;
+            }
+
+            let seq = chunk_header.chunk_seq;
+            let plain_len = chunk_header.plain_len;
+            let entry = match chunk_header.chunk_type {
+                ChunkType::Audio => {
+                    index.header.audio_stream_len += plain_len as u64;
+                    crate::IndexEntryV1::new_audio(
+                        seq,
+                        offset,
+                        record_len,
+                        plain_len,
+                        chunk_header.virtual_offset,
+                    )
+                }
+                ChunkType::Meta => crate::IndexEntryV1::new_meta(
+                    seq,
+                    offset,
+                    record_len,
+                    plain_len,
+                    crate::MetaKind::Unknown,
+                    chunk_header.chunk_flags,
+                ),
+                ChunkType::Padding => {
+                    crate::IndexEntryV1::new_padding(seq, offset, record_len, plain_len)
+                }
+                ChunkType::Index => break,
+            };
+            index.add_entry(entry);
+            offset += record_len as u64;
+        }
+        Ok(index)
+    }
+
     fn read_and_decrypt_index(
         inner: &mut R,
         header: &FurryHeaderV1,
         keys: &FileKeys,
+        stream_len: u64,
     ) -> Result<FurryIndexV1, FormatError> {
+        // 一次读入整个 INDEX 记录（header + ciphertext + tag）
+        let min_len = crate::CHUNK_HEADER_LEN as u32 + furry_crypto::TAG_LEN as u32;
+        if header.index_total_len < min_len {
+            return Err(FormatError::CorruptIndex("index_total_len too small"));
+        }
+        if !range_in_stream(header.index_offset, header.index_total_len, stream_len) {
+            return Err(FormatError::CorruptIndex("index record beyond end of file"));
+        }
+
         inner.seek(SeekFrom::Start(header.index_offset))?;
+        let mut record = vec![0u8; header.index_total_len as usize];
+        inner
+            .read_exact(&mut record)
+            .map_err(|e| eof_as_corrupt(e.into(), "index chunk truncated"))?;
 
-        let chunk_header = ChunkRecordHeaderV1::read_from(inner)?;
+        let mut cur = &record[..];
+        let chunk_header = ChunkRecordHeaderV1::read_from(&mut cur)?;
         if chunk_header.chunk_type != ChunkType::Index {
             return Err(FormatError::CorruptIndex(
                 "index_offset not pointing to INDEX chunk",
             ));
         }
+        if chunk_header.record_len() != header.index_total_len
+            || cur.len() != chunk_header.plain_len as usize + furry_crypto::TAG_LEN
+        {
+            return Err(FormatError::CorruptIndex("index record length mismatch"));
+        }
 
-        let mut ciphertext = vec![0u8; chunk_header.plain_len as usize];
-        inner.read_exact(&mut ciphertext)?;
+        if header.flags & crate::FLAG_INDEX_MAC != 0 {
+            let expected = header.index_mac(keys, &record)?;
+            if !furry_crypto::ct_eq(&expected, &header.reserved2) {
+                return Err(FormatError::IndexMacMismatch);
+            }
+        }
 
-        let mut tag = [0u8; furry_crypto::TAG_LEN];
-        inner.read_exact(&mut tag)?;
+        let (ciphertext, tag) = cur.split_at(chunk_header.plain_len as usize);
+        let mut ciphertext = ciphertext.to_vec();
+        let mut tag_bytes = [0u8; furry_crypto::TAG_LEN];
+        tag_bytes.copy_from_slice(tag);
+
+        Self::open_in_place(header, keys, &chunk_header, &mut ciphertext, &tag_bytes)?;
+
+        FurryIndexV1::parse(&ciphertext)
+    }
 
-        let nonce = furry_crypto::nonce_for_chunk(&keys.nonce_prefix, chunk_header.chunk_seq);
+    /// 解密并校验 chunk 负载；`FLAG_PLAINTEXT` 文件直接返回明文
+    fn open_in_place(
+        header: &FurryHeaderV1,
+        keys: &FileKeys,
+        chunk_header: &ChunkRecordHeaderV1,
+        buffer: &mut [u8],
+        tag: &[u8; furry_crypto::TAG_LEN],
+    ) -> Result<(), FormatError> {
+        if header.flags & crate::FLAG_PLAINTEXT != 0 {
+            return Ok(());
+        }
+
+        let (aead_key, nonce_prefix) = header.chunk_aead_keys(keys, chunk_header.chunk_type);
+        let nonce = furry_crypto::nonce_for_chunk(nonce_prefix, chunk_header.chunk_seq);
         let aad = furry_crypto::build_aad_v1(
             &header.file_id,
             header.version,
@@ -59,41 +294,64 @@ impl<R: Read + Seek> FurryReader<R> {
             &chunk_header.to_bytes(),
         );
 
-        furry_crypto::decrypt_in_place_detached(
-            &keys.aead_key,
-            &nonce,
-            &aad,
-            &mut ciphertext,
-            &tag,
-        )?;
+        furry_crypto::decrypt_in_place_detached(aead_key, &nonce, &aad, buffer, tag)?;
+        Ok(())
+    }
 
-        FurryIndexV1::parse(&ciphertext)
+    /// 密钥派生算法（`open` 已拒绝未知 id）
+    pub fn kdf(&self) -> crate::KdfAlgorithm {
+        crate::KdfAlgorithm::from_id(self.header.kdf_id).unwrap_or(crate::KdfAlgorithm::HkdfSha256)
+    }
+
+    /// AEAD 算法（`open` 已拒绝未知 id）
+    pub fn aead(&self) -> crate::AeadAlgorithm {
+        crate::AeadAlgorithm::from_id(self.header.aead_id)
+            .unwrap_or(crate::AeadAlgorithm::Aes256Gcm)
+    }
+
+    /// 头部标志位
+    pub fn flags(&self) -> crate::HeaderFlags {
+        crate::HeaderFlags::from_bits(self.header.flags)
     }
 
     /// 读取并解密指定 chunk
+    ///
+    /// META chunk 带有本版本无法还原的标志位（见 `chunk_flags::SUPPORTED_META_FLAGS`）时
+    /// 返回 `UnsupportedChunkFlag`，不把混淆后的字节当作载荷返回。
     pub fn read_chunk(&mut self, entry: &crate::IndexEntryV1) -> Result<Vec<u8>, FormatError> {
         self.inner.seek(SeekFrom::Start(entry.file_offset))?;
 
-        let chunk_header = ChunkRecordHeaderV1::read_from(&mut self.inner)?;
+        let chunk_header = ChunkRecordHeaderV1::read_from(&mut self.inner)
+            .map_err(|e| eof_as_corrupt(e, "chunk record truncated"))?;
 
-        let mut ciphertext = vec![0u8; chunk_header.plain_len as usize];
-        self.inner.read_exact(&mut ciphertext)?;
+        // chunk 头与索引条目必须一致，否则后续 tag 读取会错位
+        if chunk_header.plain_len != entry.plain_len || chunk_header.chunk_seq != entry.chunk_seq {
+            return Err(FormatError::CorruptIndex("chunk/index length mismatch"));
+        }
+        if !range_in_stream(
+            entry.file_offset,
+            chunk_header.record_len(),
+            self.stream_len,
+        ) {
+            return Err(FormatError::CorruptIndex("chunk record beyond end of file"));
+        }
+        let unsupported = chunk_header.chunk_flags & !crate::chunk_flags::SUPPORTED_META_FLAGS;
+        if chunk_header.chunk_type == ChunkType::Meta && unsupported != 0 {
+            return Err(FormatError::UnsupportedChunkFlag(unsupported));
+        }
 
+        let mut ciphertext = vec![0u8; chunk_header.plain_len as usize];
         let mut tag = [0u8; furry_crypto::TAG_LEN];
-        self.inner.read_exact(&mut tag)?;
-
-        let nonce = furry_crypto::nonce_for_chunk(&self.keys.nonce_prefix, chunk_header.chunk_seq);
-        let aad = furry_crypto::build_aad_v1(
-            &self.header.file_id,
-            self.header.version,
-            self.header.flags,
-            &chunk_header.to_bytes(),
-        );
+        // 范围已按打开时的长度检查，文件仍可能在读取期间被截断
+        self.inner
+            .read_exact(&mut ciphertext)
+            .and_then(|()| self.inner.read_exact(&mut tag))
+            .map_err(|e| eof_as_corrupt(e.into(), "chunk record truncated"))?;
 
-        furry_crypto::decrypt_in_place_detached(
-            &self.keys.aead_key,
-            &nonce,
-            &aad,
+        Self::open_in_place(
+            &self.header,
+            &self.keys,
+            &chunk_header,
             &mut ciphertext,
             &tag,
         )?;
@@ -101,7 +359,162 @@ impl<R: Read + Seek> FurryReader<R> {
         Ok(ciphertext)
     }
 
+    /// 按 chunk_seq 读取并解密 chunk（调试"解密第 42 个 chunk"等场景）
+    ///
+    /// 索引中没有该 seq 时返回 `None`；返回的是 chunk 明文本身，压缩的 META 不解压。
+    pub fn read_chunk_by_seq(&mut self, seq: u64) -> Result<Option<Vec<u8>>, FormatError> {
+        let Some(entry) = self.index.entry_by_seq(seq).cloned() else {
+            return Ok(None);
+        };
+        self.read_chunk(&entry).map(Some)
+    }
+
+    /// AUDIO chunk 按 virtual_offset 排序后在文件中是否首尾相接（中间没有 PADDING/META）
+    pub fn audio_is_contiguous(&self) -> bool {
+        let entries = self.index.audio_entries();
+        entries
+            .windows(2)
+            .all(|w| w[0].file_offset.checked_add(w[0].record_len as u64) == Some(w[1].file_offset))
+    }
+
+    /// 按 virtual_offset 顺序解密全部 AUDIO chunk，依次交给 `f`
+    ///
+    /// AUDIO chunk 物理连续时（常见的无穿插布局）只 seek 一次，之后每条记录用一次
+    /// `read_exact` 整段读入；否则退回逐 chunk seek 的 [`Self::read_chunk`]。
+    /// `f` 返回错误时立即中止。
+    pub fn for_each_audio_chunk<E, F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        E: From<FormatError>,
+        F: FnMut(&[u8]) -> Result<(), E>,
+    {
+        let entries: Vec<_> = self.index.audio_entries().into_iter().cloned().collect();
+        if !self.audio_is_contiguous() {
+            for entry in &entries {
+                f(&self.read_chunk(entry)?)?;
+            }
+            return Ok(());
+        }
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return Ok(());
+        };
+        if last
+            .file_offset
+            .checked_add(last.record_len as u64)
+            .is_none_or(|end| end > self.stream_len)
+        {
+            return Err(FormatError::CorruptIndex("chunk record beyond end of file").into());
+        }
+
+        self.inner
+            .seek(SeekFrom::Start(first.file_offset))
+            .map_err(FormatError::from)?;
+        let header_len = crate::CHUNK_HEADER_LEN as usize;
+        let mut record = Vec::new();
+        for entry in &entries {
+            record.resize(entry.record_len as usize, 0);
+            self.inner
+                .read_exact(&mut record)
+                .map_err(|e| eof_as_corrupt(e.into(), "chunk record truncated"))?;
+            let chunk_header = ChunkRecordHeaderV1::read_from(&mut &record[..])?;
+            if chunk_header.plain_len != entry.plain_len
+                || chunk_header.chunk_seq != entry.chunk_seq
+                || chunk_header.record_len() != entry.record_len
+            {
+                return Err(FormatError::CorruptIndex("chunk/index length mismatch").into());
+            }
+            let (payload, tag) = record[header_len..].split_at_mut(entry.plain_len as usize);
+            let tag: &[u8; furry_crypto::TAG_LEN] = (&*tag)
+                .try_into()
+                .map_err(|_| FormatError::CorruptIndex("chunk/index length mismatch"))?;
+            Self::open_in_place(&self.header, &self.keys, &chunk_header, payload, tag)?;
+            f(payload)?;
+        }
+        Ok(())
+    }
+
+    /// 原样读取整条 chunk 记录（chunk 头 + 密文 + tag），不解密
+    ///
+    /// 只校验记录范围及 chunk 头与索引条目一致，不做 AEAD 认证。
+    pub fn read_raw_chunk(&mut self, entry: &crate::IndexEntryV1) -> Result<Vec<u8>, FormatError> {
+        if !range_in_stream(entry.file_offset, entry.record_len, self.stream_len) {
+            return Err(FormatError::CorruptIndex("chunk record beyond end of file"));
+        }
+        self.inner.seek(SeekFrom::Start(entry.file_offset))?;
+        let mut record = vec![0u8; entry.record_len as usize];
+        self.inner
+            .read_exact(&mut record)
+            .map_err(|e| eof_as_corrupt(e.into(), "chunk record truncated"))?;
+
+        let chunk_header = ChunkRecordHeaderV1::read_from(&mut &record[..])?;
+        if chunk_header.plain_len != entry.plain_len
+            || chunk_header.chunk_seq != entry.chunk_seq
+            || chunk_header.record_len() != entry.record_len
+        {
+            return Err(FormatError::CorruptIndex("chunk/index length mismatch"));
+        }
+        Ok(record)
+    }
+
+    /// 按索引条目读取 chunk 头之后的密文 + tag，不解析 chunk 头
+    pub(crate) fn read_chunk_body(
+        &mut self,
+        entry: &crate::IndexEntryV1,
+    ) -> Result<Vec<u8>, FormatError> {
+        let header_len = crate::CHUNK_HEADER_LEN as u32;
+        if entry.record_len < header_len
+            || !range_in_stream(entry.file_offset, entry.record_len, self.stream_len)
+        {
+            return Err(FormatError::CorruptIndex("chunk record beyond end of file"));
+        }
+        self.inner
+            .seek(SeekFrom::Start(entry.file_offset + header_len as u64))?;
+        let mut body = vec![0u8; (entry.record_len - header_len) as usize];
+        self.inner
+            .read_exact(&mut body)
+            .map_err(|e| eof_as_corrupt(e.into(), "chunk record truncated"))?;
+        Ok(body)
+    }
+
+    /// PADDING chunk 的磁盘占用（record_len 之和，含 chunk 头与 tag），只读索引
+    pub fn padding_bytes(&self) -> u64 {
+        self.index
+            .entries
+            .iter()
+            .filter(|e| e.chunk_type == crate::ChunkType::Padding)
+            .map(|e| e.record_len as u64)
+            .sum()
+    }
+
+    /// AUDIO chunk 映射：`(virtual_start, plain_len, file_offset)`，按 virtual_start 升序
+    ///
+    /// 便于按字节范围定位需要的最少 chunk（如 HTTP Range 服务），
+    /// 可配合 `partition_point` 做二分查找。
+    pub fn audio_chunk_map(&self) -> Vec<(u64, u32, u64)> {
+        self.index
+            .audio_entries()
+            .into_iter()
+            .map(|e| (e.virtual_offset, e.plain_len, e.file_offset))
+            .collect()
+    }
+
+    /// 解密后音频流的总字节数（不含 padding/META）
+    ///
+    /// 优先取索引头的 `audio_stream_len`；为 0（旧版/流式写入未回填）时按 AUDIO 条目求和。
+    pub fn total_audio_bytes(&self) -> u64 {
+        match self.index.header.audio_stream_len {
+            0 => self
+                .index
+                .audio_entries()
+                .iter()
+                .map(|e| e.plain_len as u64)
+                .sum(),
+            len => len,
+        }
+    }
+
     /// 读取指定 kind 的最新 META chunk（按 chunk_seq 最大）
+    ///
+    /// 压缩的 META 返回解压后的载荷；超过该 kind 大小上限时返回 `None`。
     pub fn read_latest_meta(
         &mut self,
         kind: crate::MetaKind,
@@ -114,28 +527,186 @@ impl<R: Read + Seek> FurryReader<R> {
         let Some(entry) = entry else {
             return Ok(None);
         };
+        if entry.plain_len > Self::meta_size_limit(kind) {
+            return Ok(None);
+        }
+        self.read_meta_entry(&entry, Self::meta_size_limit(kind))
+    }
+
+    /// 解密 META chunk，设置了 `FLAG_META_GZIP` 时解压；解压后超过 `limit` 返回 `None`
+    ///
+    /// [`Self::read_chunk`] 始终返回 chunk 明文本身（可能是压缩数据），供原样复制使用。
+    fn read_meta_entry(
+        &mut self,
+        entry: &crate::IndexEntryV1,
+        limit: u32,
+    ) -> Result<Option<Vec<u8>>, FormatError> {
+        let data = self.read_chunk(entry)?;
+        if entry.chunk_flags & crate::chunk_flags::FLAG_META_GZIP == 0 {
+            return Ok(Some(data));
+        }
+        crate::decompress_meta_payload(&data, limit)
+    }
+
+    /// 各 kind 的 META 明文大小上限
+    fn meta_size_limit(kind: crate::MetaKind) -> u32 {
         // Guard against pathological META payload sizes (can OOM on mobile).
-        // Cover art can be large, but should still be bounded.
-        const MAX_TAGS_BYTES: u32 = 256 * 1024; // 256 KiB
-        const MAX_LYRICS_BYTES: u32 = 2 * 1024 * 1024; // 2 MiB
-
-        // Cover art can be large; keep this high to avoid unexpectedly dropping art.
-        // NOTE: Very large covers may increase memory usage on mobile.
-        const MAX_COVER_BYTES: u32 = 64 * 1024 * 1024; // 64 MiB (includes mime\0 prefix)
-        let max_plain_len = match kind {
-            crate::MetaKind::Tags => MAX_TAGS_BYTES,
+        match kind {
+            crate::MetaKind::Tags | crate::MetaKind::TrackTable => MAX_TAGS_BYTES,
             crate::MetaKind::Lyrics => MAX_LYRICS_BYTES,
             crate::MetaKind::CoverArt => MAX_COVER_BYTES,
-            crate::MetaKind::Unknown => MAX_TAGS_BYTES,
-        };
-        if entry.plain_len > max_plain_len {
-            return Ok(None);
+            crate::MetaKind::SourceInfo | crate::MetaKind::Gapless => MAX_SOURCE_INFO_BYTES,
+            crate::MetaKind::RawTagBlock => MAX_RAW_TAG_BLOCK_BYTES,
+            crate::MetaKind::Unknown | crate::MetaKind::Custom(_) => MAX_TAGS_BYTES,
         }
-        Ok(Some(self.read_chunk(&entry)?))
+    }
+
+    /// 读取指定 kind 的全部 META chunk，按 chunk_seq 升序
+    ///
+    /// 用于可多次写入的类型（如应用自定义的 [`crate::MetaKind::Custom`]）；
+    /// 超过该 kind 大小上限（压缩条目按解压后大小）的条目跳过。
+    pub fn read_all_meta(&mut self, kind: crate::MetaKind) -> Result<Vec<Vec<u8>>, FormatError> {
+        let max_plain_len = Self::meta_size_limit(kind);
+        let entries: Vec<_> = self
+            .index
+            .meta_entries_by_kind(kind)
+            .into_iter()
+            .filter(|e| e.plain_len <= max_plain_len)
+            .cloned()
+            .collect();
+        let mut payloads = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            payloads.extend(self.read_meta_entry(entry, max_plain_len)?);
+        }
+        Ok(payloads)
+    }
+
+    /// 读取全部语言的歌词，返回 `(lang, text)`，按 chunk_seq 排序
+    ///
+    /// 同一语言出现多次时以 chunk_seq 最大者为准；超过大小上限的条目跳过。
+    pub fn read_lyrics(&mut self) -> Result<Vec<(String, String)>, FormatError> {
+        let entries: Vec<_> = self
+            .index
+            .meta_entries_by_kind(crate::MetaKind::Lyrics)
+            .into_iter()
+            .filter(|e| e.plain_len <= MAX_LYRICS_BYTES)
+            .cloned()
+            .collect();
+        let mut lyrics: Vec<(String, String)> = Vec::new();
+        for entry in &entries {
+            let Some(payload) = self.read_meta_entry(entry, MAX_LYRICS_BYTES)? else {
+                continue;
+            };
+            let (lang, text) = crate::parse_lyrics_payload(&payload);
+            lyrics.retain(|(l, _)| *l != lang);
+            lyrics.push((lang, text));
+        }
+        Ok(lyrics)
+    }
+
+    /// 读取来源信息（SourceInfo META）
+    pub fn read_source_info(&mut self) -> Result<Option<crate::SourceInfo>, FormatError> {
+        Ok(self
+            .read_latest_meta(crate::MetaKind::SourceInfo)?
+            .map(|data| crate::SourceInfo::parse(&data)))
+    }
+
+    /// 读取无缝播放裁剪信息（无或无法解析时为 `None`）
+    pub fn read_gapless(&mut self) -> Result<Option<crate::GaplessInfo>, FormatError> {
+        Ok(self
+            .read_latest_meta(crate::MetaKind::Gapless)?
+            .and_then(|data| crate::GaplessInfo::parse(&data)))
+    }
+
+    /// 读取多曲目专辑的曲目表（无或没有有效曲目时为 `None`）
+    pub fn read_track_table(&mut self) -> Result<Option<crate::TrackTable>, FormatError> {
+        Ok(self
+            .read_latest_meta(crate::MetaKind::TrackTable)?
+            .and_then(|data| crate::TrackTable::parse(&data)))
+    }
+
+    /// 原始扩展名（不带点）
+    ///
+    /// 已知格式取自索引；`Unknown` 时回退到 SourceInfo 中记录的源文件扩展名，
+    /// 都没有则为空字符串。
+    pub fn original_extension(&mut self) -> String {
+        let format = self.index.header.original_format;
+        if format != crate::OriginalFormat::Unknown {
+            return format.extension().to_string();
+        }
+        self.read_source_info()
+            .ok()
+            .flatten()
+            .and_then(|info| info.extension)
+            .unwrap_or_default()
+    }
+
+    /// 生成该文件的工具及版本（未记录或读取失败时为 `None`）
+    pub fn producer(&mut self) -> Option<String> {
+        self.read_source_info().ok().flatten()?.producer
+    }
+
+    /// 按虚拟顺序计算解密后音频流的 BLAKE3 摘要
+    ///
+    /// 这是明文摘要：同一首歌多次打包（salt/密文不同）结果一致，可用于曲库去重；
+    /// 与对 .furry 文件本身（密文）计算的摘要不同。需要解密全部 AUDIO，明文不落盘。
+    pub fn audio_plaintext_digest(&mut self) -> Result<[u8; 32], FormatError> {
+        let entries: Vec<_> = self.index.audio_entries().into_iter().cloned().collect();
+        let mut hasher = blake3::Hasher::new();
+        for entry in &entries {
+            hasher.update(&self.read_chunk(entry)?);
+        }
+        Ok(*hasher.finalize().as_bytes())
     }
 
     /// 获取内部 reader
     pub fn into_inner(self) -> R {
         self.inner
     }
+
+    /// 拆分为内部 reader 与已解析的头部、文件密钥、索引
+    ///
+    /// 配合 [`Self::from_parts`] 在交出底层流（如给解码器）后重新组装，
+    /// 免去再次 HKDF 派生与索引解密。
+    pub fn into_parts(self) -> (R, FurryHeaderV1, FileKeys, FurryIndexV1) {
+        (self.inner, self.header, self.keys, self.index)
+    }
+
+    /// 由 [`Self::into_parts`] 的结果重新组装，只重新测量流长度
+    ///
+    /// 各部分必须来自同一文件，否则之后的读取会认证失败。
+    /// 不保留 [`Self::is_recovered`] 状态。
+    pub fn from_parts(
+        mut inner: R,
+        header: FurryHeaderV1,
+        keys: FileKeys,
+        index: FurryIndexV1,
+    ) -> Result<Self, FormatError> {
+        let stream_len = header.logical_end(inner.seek(SeekFrom::End(0))?);
+        Ok(Self {
+            inner,
+            header,
+            keys,
+            index,
+            stream_len,
+            recovered: false,
+        })
+    }
+}
+
+/// 把读到流末尾的 `UnexpectedEof` 换成格式错误，便于调用方区分截断文件与真正的 IO 故障
+fn eof_as_corrupt(err: FormatError, what: &'static str) -> FormatError {
+    match err {
+        FormatError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            FormatError::CorruptIndex(what)
+        }
+        other => other,
+    }
+}
+
+/// `[offset, offset + len)` 是否完整落在流内
+fn range_in_stream(offset: u64, len: u32, stream_len: u64) -> bool {
+    offset
+        .checked_add(len as u64)
+        .is_some_and(|end| end <= stream_len)
 }
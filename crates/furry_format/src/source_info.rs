@@ -0,0 +1,97 @@
+//! 来源信息（SourceInfo META）
+//!
+//! 载荷为 UTF-8 文本，每行一个 `key=value`，未知 key 忽略。
+
+/// producer 字符串最大长度（字节）
+pub const MAX_PRODUCER_LEN: usize = 64;
+/// 原始扩展名最大长度（字节）
+pub const MAX_EXTENSION_LEN: usize = 16;
+
+/// 来源信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceInfo {
+    /// 生成该文件的工具及版本，如 `furry_converter 0.1.0`
+    pub producer: Option<String>,
+    /// 音频明文的 BLAKE3 摘要（打包时流式计算，见 `FurryReader::audio_plaintext_digest`）
+    pub audio_digest: Option<[u8; 32]>,
+    /// 源文件扩展名（小写、不带点），仅在 `OriginalFormat::Unknown` 时记录
+    pub extension: Option<String>,
+}
+
+impl SourceInfo {
+    /// 序列化为 META 载荷
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = String::new();
+        if let Some(producer) = &self.producer {
+            push_line(&mut out, "producer", &truncate(producer, MAX_PRODUCER_LEN));
+        }
+        if let Some(digest) = &self.audio_digest {
+            let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            push_line(&mut out, "audio_blake3", &hex);
+        }
+        if let Some(ext) = self.extension.as_deref().and_then(sanitize_extension) {
+            push_line(&mut out, "extension", &ext);
+        }
+        out.into_bytes()
+    }
+
+    /// 从 META 载荷解析（宽松：无效 UTF-8 与未知 key 均忽略）
+    pub fn parse(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data);
+        let mut info = Self::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "producer" => info.producer = Some(truncate(value, MAX_PRODUCER_LEN)),
+                "audio_blake3" => info.audio_digest = parse_digest(value),
+                "extension" => info.extension = sanitize_extension(value),
+                _ => {}
+            }
+        }
+        info
+    }
+}
+
+fn push_line(out: &mut String, key: &str, value: &str) {
+    out.push_str(key);
+    out.push('=');
+    out.push_str(&value.replace(['\r', '\n'], " "));
+    out.push('\n');
+}
+
+/// 扩展名仅允许 ASCII 字母数字，统一小写；不合法时丢弃
+pub fn sanitize_extension(ext: &str) -> Option<String> {
+    let ext = ext.trim_start_matches('.');
+    if ext.is_empty()
+        || ext.len() > MAX_EXTENSION_LEN
+        || !ext.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+    Some(ext.to_ascii_lowercase())
+}
+
+fn parse_digest(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+/// 按字符边界截断到最多 `max` 字节
+fn truncate(s: &str, max: usize) -> String {
+    if s.len() <= max {
+        return s.to_string();
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_string()
+}
@@ -0,0 +1,87 @@
+//! 多曲目专辑的曲目表（`MetaKind::TrackTable`）
+//!
+//! 一个 .furry 可以首尾相接地容纳多个完整的音频文件（各自带文件头），
+//! 曲目表记录每首在虚拟音频流中的范围，播放器据此把它们当作独立的条目。
+//! 载荷为 UTF-8 文本，每行一首：`index\tvirtual_start\tvirtual_len\ttitle\ttags`，
+//! `tags` 为该曲目的 tags JSON（可为空，本库原样保存不解析）；无法解析的行忽略。
+
+/// 曲目表中的一首
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackTableEntry {
+    /// 曲目序号（从 0 开始）
+    pub index: u32,
+    /// 在虚拟音频流中的起始偏移
+    pub virtual_start: u64,
+    /// 字节长度
+    pub virtual_len: u64,
+    pub title: String,
+    /// 该曲目的完整 tags JSON
+    pub tags_json: Option<String>,
+}
+
+impl TrackTableEntry {
+    /// 虚拟音频流中的结束偏移（不含）
+    pub fn virtual_end(&self) -> u64 {
+        self.virtual_start.saturating_add(self.virtual_len)
+    }
+}
+
+/// 曲目表
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackTable {
+    pub tracks: Vec<TrackTableEntry>,
+}
+
+impl TrackTable {
+    /// 按序号查找曲目
+    pub fn get(&self, index: u32) -> Option<&TrackTableEntry> {
+        self.tracks.iter().find(|t| t.index == index)
+    }
+
+    /// 序列化为 META 载荷；标题中的制表符与换行替换为空格
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = String::new();
+        for track in &self.tracks {
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                track.index,
+                track.virtual_start,
+                track.virtual_len,
+                track.title.replace(['\t', '\r', '\n'], " "),
+                track
+                    .tags_json
+                    .as_deref()
+                    .unwrap_or("")
+                    .replace(['\t', '\r', '\n'], " "),
+            ));
+        }
+        out.into_bytes()
+    }
+
+    /// 从 META 载荷解析；没有有效曲目时返回 `None`
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let text = String::from_utf8_lossy(data);
+        let tracks: Vec<_> = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(5, '\t');
+                let index = fields.next()?.parse().ok()?;
+                let virtual_start = fields.next()?.parse().ok()?;
+                let virtual_len = fields.next()?.parse().ok()?;
+                let title = fields.next().unwrap_or("").to_string();
+                let tags_json = fields
+                    .next()
+                    .filter(|tags| !tags.is_empty())
+                    .map(str::to_string);
+                Some(TrackTableEntry {
+                    index,
+                    virtual_start,
+                    virtual_len,
+                    title,
+                    tags_json,
+                })
+            })
+            .collect();
+        (!tracks.is_empty()).then_some(Self { tracks })
+    }
+}
@@ -0,0 +1,149 @@
+//! 完整性校验
+
+use std::io::{Read, Seek};
+
+use crate::{ChunkType, FormatError, FurryIndexV1, FurryReader, MetaKind};
+
+/// 校验选项
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// 只校验 AUDIO chunk（跳过体积较大的 META/PADDING）
+    pub audio_only: bool,
+    /// 从第几个 chunk 开始（用于恢复被取消的校验，取上次报告的 `checked_chunks`）
+    pub resume_from: usize,
+}
+
+/// 校验报告
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// 需要校验的 chunk 总数
+    pub total_chunks: usize,
+    /// 已校验的 chunk 数（可作为下次的 `resume_from`）
+    pub checked_chunks: usize,
+    /// 校验失败的 chunk_seq
+    pub failed_chunks: Vec<u64>,
+    /// 与 `failed_chunks` 一一对应的失败原因
+    pub faults: Vec<ChunkFault>,
+    /// 是否被回调取消
+    pub cancelled: bool,
+    /// 填充与未知 chunk 统计
+    pub census: ChunkCensus,
+}
+
+/// chunk 校验失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkFault {
+    /// 磁盘上的密文/tag 与写入时的 CRC32 不符：位翻转或内容被改写
+    Ciphertext,
+    /// 密文 CRC 相符但 AEAD 认证失败：chunk 头（AAD）被改动或不一致
+    Header,
+    /// 文件未记录 chunk CRC（[`crate::INDEX_FLAG_CHUNK_CRC`]）或密文无法读取，无法区分
+    Unknown,
+}
+
+/// 索引中不承载音频/已知元数据的 chunk 统计
+///
+/// 填充本身是格式的混淆手段，但收到的文件里多出大量填充或未知类型的
+/// chunk 也可能意味着夹带数据，这里只如实列出，不判定好坏。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkCensus {
+    /// PADDING chunk 数
+    pub padding_chunks: usize,
+    /// PADDING chunk 的磁盘占用（record_len 之和）
+    pub padding_bytes: u64,
+    /// 类型不符合预期的 chunk_seq：索引内的 INDEX 条目、非内置 meta_kind（含应用自定义）的 META
+    pub unknown_chunks: Vec<u64>,
+}
+
+impl ChunkCensus {
+    /// 没有任何填充或未知 chunk
+    pub fn is_empty(&self) -> bool {
+        self.padding_chunks == 0 && self.unknown_chunks.is_empty()
+    }
+}
+
+impl FurryIndexV1 {
+    /// 统计填充与未知 chunk，只读索引
+    pub fn chunk_census(&self) -> ChunkCensus {
+        let mut census = ChunkCensus::default();
+        for entry in &self.entries {
+            match entry.chunk_type {
+                ChunkType::Padding => {
+                    census.padding_chunks += 1;
+                    census.padding_bytes += entry.record_len as u64;
+                }
+                ChunkType::Meta if !MetaKind::from_u16(entry.meta_kind).is_builtin() => {
+                    census.unknown_chunks.push(entry.chunk_seq);
+                }
+                ChunkType::Index => census.unknown_chunks.push(entry.chunk_seq),
+                _ => {}
+            }
+        }
+        census
+    }
+}
+
+impl VerifyReport {
+    /// 全部校验完成且没有失败
+    pub fn is_ok(&self) -> bool {
+        !self.cancelled && self.failed_chunks.is_empty()
+    }
+}
+
+impl<R: Read + Seek> FurryReader<R> {
+    /// 逐个解密 chunk 校验 AEAD tag
+    ///
+    /// `progress(done, total)` 在每个 chunk 后调用，返回 `false` 则中止校验。
+    /// 中止后 reader 仍可正常使用。chunk 按文件偏移顺序读取。
+    pub fn verify<F>(
+        &mut self,
+        options: &VerifyOptions,
+        mut progress: F,
+    ) -> Result<VerifyReport, FormatError>
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        let mut entries: Vec<_> = self
+            .index
+            .entries
+            .iter()
+            .filter(|e| !options.audio_only || e.chunk_type == ChunkType::Audio)
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.file_offset);
+
+        let mut report = VerifyReport {
+            total_chunks: entries.len(),
+            checked_chunks: options.resume_from.min(entries.len()),
+            census: self.index.chunk_census(),
+            ..Default::default()
+        };
+
+        for entry in &entries[report.checked_chunks..] {
+            if self.read_chunk(entry).is_err() {
+                report.failed_chunks.push(entry.chunk_seq);
+                report.faults.push(self.diagnose_chunk(entry));
+            }
+            report.checked_chunks += 1;
+
+            if !progress(report.checked_chunks, report.total_chunks) {
+                report.cancelled = report.checked_chunks < report.total_chunks;
+                break;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 按索引记录的 CRC 判断失败原因；只读 chunk 头之后的密文 + tag，不依赖 chunk 头内容
+    fn diagnose_chunk(&mut self, entry: &crate::IndexEntryV1) -> ChunkFault {
+        if !self.index.has_chunk_crc() {
+            return ChunkFault::Unknown;
+        }
+        match self.read_chunk_body(entry) {
+            Ok(body) if crc32fast::hash(&body) == entry.reserved2 => ChunkFault::Header,
+            Ok(_) => ChunkFault::Ciphertext,
+            Err(_) => ChunkFault::Unknown,
+        }
+    }
+}
@@ -2,35 +2,151 @@
 
 use std::io::{Seek, SeekFrom, Write};
 
-use furry_crypto::{FileKeys, MasterKey};
+use furry_crypto::{FileKeys, MasterKey, Rng};
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
     ChunkRecordHeaderV1, ChunkType, FormatError, FurryHeaderV1, FurryIndexV1, IndexEntryV1,
-    OriginalFormat, FURRY_HEADER_LEN,
+    OriginalFormat, CHUNK_HEADER_LEN, FURRY_HEADER_LEN, INDEX_ENTRY_LEN, INDEX_HEADER_LEN,
+    KDF_HKDF_SHA256, KDF_HKDF_SHA256_SALT32,
 };
 
+/// 单个 chunk 明文最大长度：记录长度（chunk 头 + 明文 + tag）必须能用 u32 表示
+pub const MAX_CHUNK_PLAIN_LEN: usize =
+    u32::MAX as usize - CHUNK_HEADER_LEN as usize - furry_crypto::TAG_LEN;
+/// 单个文件最多可写入的 chunk 数：INDEX 记录同样受 u32 长度限制（约 8900 万）
+pub const MAX_INDEX_ENTRIES: usize = (MAX_CHUNK_PLAIN_LEN - INDEX_HEADER_LEN) / INDEX_ENTRY_LEN;
+
+/// 每个 PADDING 记录的固定开销：chunk 头 + tag + 索引条目
+pub const PADDING_RECORD_OVERHEAD: u64 =
+    (CHUNK_HEADER_LEN as usize + furry_crypto::TAG_LEN + INDEX_ENTRY_LEN) as u64;
+
+/// 将 `record_bytes`（含每条记录的开销）拆分为若干 padding 负载，每个不超过 `max_chunk_size`
+///
+/// `record_bytes` 为 0 时返回空；无法恰好凑齐（小于单条记录开销）时返回 `None`。
+pub fn padding_chunk_sizes(record_bytes: u64, max_chunk_size: usize) -> Option<Vec<usize>> {
+    if record_bytes == 0 {
+        return Some(Vec::new());
+    }
+    if record_bytes < PADDING_RECORD_OVERHEAD {
+        return None;
+    }
+    let per_chunk = max_chunk_size.max(1) as u64 + PADDING_RECORD_OVERHEAD;
+    let count = record_bytes.div_ceil(per_chunk);
+    let payload = record_bytes - count * PADDING_RECORD_OVERHEAD;
+    Some(
+        (0..count)
+            .map(|i| (payload / count + u64::from(i < payload % count)) as usize)
+            .collect(),
+    )
+}
+
+/// 容器本身的开销字节数（不含音频、META、padding 负载）
+///
+/// 头部 + 每个 chunk 的记录头与 tag + INDEX 记录（完整布局，每个 chunk 一个条目）。
+/// 紧凑索引与 fake footer 不计入。
+pub fn format_overhead(chunk_count: u32, meta_count: u32, padding_count: u32) -> u64 {
+    let records = chunk_count as u64 + meta_count as u64 + padding_count as u64;
+    let per_record = (CHUNK_HEADER_LEN as usize + furry_crypto::TAG_LEN) as u64;
+    FURRY_HEADER_LEN as u64
+        + records * per_record
+        + index_record_len(INDEX_HEADER_LEN)
+        + records * INDEX_ENTRY_LEN as u64
+}
+
+/// INDEX 记录总长（chunk 头 + 明文 + tag）
+fn index_record_len(index_plain_len: usize) -> u64 {
+    (CHUNK_HEADER_LEN as usize + index_plain_len + furry_crypto::TAG_LEN) as u64
+}
+
 /// .furry 文件写入器
 pub struct FurryWriter<W: Write + Seek> {
-    inner: W,
+    /// 仅在 `finish` 中取出
+    inner: Option<W>,
     header: FurryHeaderV1,
     keys: FileKeys,
     index: FurryIndexV1,
     chunk_seq: u64,
     current_offset: u64,
+    /// 复用的加密缓冲区，避免每个 chunk 分配；drop 时清零
+    scratch: Zeroizing<Vec<u8>>,
+    /// file_id/salt/padding/fake footer 的随机来源
+    rng: Box<dyn Rng>,
+}
+
+/// 加密 chunk 负载并返回 tag；`FLAG_PLAINTEXT` 时保持明文、tag 全零
+fn seal_in_place(
+    header: &FurryHeaderV1,
+    keys: &FileKeys,
+    chunk_header: &ChunkRecordHeaderV1,
+    buffer: &mut [u8],
+) -> Result<[u8; furry_crypto::TAG_LEN], furry_crypto::CryptoError> {
+    if header.flags & crate::FLAG_PLAINTEXT != 0 {
+        return Ok([0u8; furry_crypto::TAG_LEN]);
+    }
+    let (aead_key, nonce_prefix) = header.chunk_aead_keys(keys, chunk_header.chunk_type);
+    let nonce = furry_crypto::nonce_for_chunk(nonce_prefix, chunk_header.chunk_seq);
+    let aad = furry_crypto::build_aad_v1(
+        &header.file_id,
+        header.version,
+        header.flags,
+        &chunk_header.to_bytes(),
+    );
+    furry_crypto::encrypt_in_place_detached(aead_key, &nonce, &aad, buffer)
 }
 
 impl<W: Write + Seek> FurryWriter<W> {
     /// 创建新的 .furry 文件
     pub fn create(
-        mut inner: W,
+        inner: W,
         master_key: &MasterKey,
         original_format: OriginalFormat,
     ) -> Result<Self, FormatError> {
-        let file_id = furry_crypto::generate_file_id()?;
-        let salt = furry_crypto::generate_salt()?;
-        let keys = furry_crypto::derive_file_keys(master_key, &salt)?;
+        Self::create_with_kdf(inner, master_key, original_format, KDF_HKDF_SHA256)
+    }
 
-        let header = FurryHeaderV1::new(file_id, salt);
+    /// 创建新的 .furry 文件并指定 KDF（见 `KDF_*` 常量）
+    pub fn create_with_kdf(
+        inner: W,
+        master_key: &MasterKey,
+        original_format: OriginalFormat,
+        kdf_id: u16,
+    ) -> Result<Self, FormatError> {
+        Self::create_with_rng(
+            inner,
+            master_key,
+            original_format,
+            kdf_id,
+            Box::new(furry_crypto::OsRng),
+        )
+    }
+
+    /// 同 [`Self::create_with_kdf`]，但指定随机来源
+    ///
+    /// 传入 [`furry_crypto::TestRng`] 可得到逐字节可复现的文件，仅限测试使用。
+    pub fn create_with_rng(
+        mut inner: W,
+        master_key: &MasterKey,
+        original_format: OriginalFormat,
+        kdf_id: u16,
+        mut rng: Box<dyn Rng>,
+    ) -> Result<Self, FormatError> {
+        let mut file_id = [0u8; furry_crypto::FILE_ID_LEN];
+        rng.fill_bytes(&mut file_id)?;
+        let header = match kdf_id {
+            KDF_HKDF_SHA256 => {
+                let mut salt = [0u8; furry_crypto::SALT_LEN];
+                rng.fill_bytes(&mut salt)?;
+                FurryHeaderV1::new(file_id, salt)
+            }
+            KDF_HKDF_SHA256_SALT32 => {
+                let mut salt = [0u8; furry_crypto::LONG_SALT_LEN];
+                rng.fill_bytes(&mut salt)?;
+                FurryHeaderV1::new_with_long_salt(file_id, salt)
+            }
+            other => return Err(FormatError::UnsupportedKdf(other)),
+        };
+        let keys = furry_crypto::derive_file_keys(master_key, &header.kdf_salt()?)?;
 
         // 写入占位头部（稍后更新）
         inner.seek(SeekFrom::Start(0))?;
@@ -39,15 +155,91 @@ impl<W: Write + Seek> FurryWriter<W> {
         let current_offset = FURRY_HEADER_LEN as u64;
 
         Ok(Self {
-            inner,
+            inner: Some(inner),
             header,
             keys,
             index: FurryIndexV1::new(0, original_format),
             chunk_seq: 0,
             current_offset,
+            scratch: Zeroizing::new(Vec::new()),
+            rng,
         })
     }
 
+    /// 创建**不加密**的 .furry 文件（`FLAG_PLAINTEXT`），仅用于调试容器布局
+    ///
+    /// 产物中音频与 META 均为明文，切勿用于正式发布的文件。
+    #[cfg(feature = "insecure-plaintext")]
+    pub fn create_plaintext(
+        inner: W,
+        master_key: &MasterKey,
+        original_format: OriginalFormat,
+    ) -> Result<Self, FormatError> {
+        let mut writer = Self::create(inner, master_key, original_format)?;
+        writer.header.flags |= crate::FLAG_PLAINTEXT;
+        Ok(writer)
+    }
+
+    /// 启用头部 + 索引 MAC（`FLAG_INDEX_MAC`），须在写入任何 chunk 之前调用
+    ///
+    /// 标志位参与每个 chunk 的 AAD，写入后再修改会使已写 chunk 无法解密。
+    pub fn with_index_mac(mut self) -> Result<Self, FormatError> {
+        if self.chunk_seq != 0 {
+            return Err(FormatError::IndexMacUnavailable(
+                "chunks have already been written",
+            ));
+        }
+        if self.header.kdf_id == KDF_HKDF_SHA256_SALT32 {
+            return Err(FormatError::IndexMacUnavailable(
+                "reserved2 is occupied by the 32-byte salt",
+            ));
+        }
+        self.header.flags |= crate::FLAG_INDEX_MAC;
+        Ok(self)
+    }
+
+    /// META chunk 改用独立派生的密钥加密（`FLAG_META_KEY`），须在写入任何 chunk 之前调用
+    ///
+    /// 旧版本读取器会因未知标志位拒绝打开。
+    pub fn with_meta_key(mut self) -> Result<Self, FormatError> {
+        if self.chunk_seq != 0 {
+            return Err(FormatError::HeaderFlagsLocked);
+        }
+        self.header.flags |= crate::FLAG_META_KEY;
+        Ok(self)
+    }
+
+    /// 纯音频文件使用紧凑索引布局（[`crate::INDEX_FLAG_COMPACT_AUDIO`]），索引约减半
+    ///
+    /// 写入了 META/PADDING 等条目时 `finish` 自动退回完整布局。旧版本读取器无法打开紧凑索引。
+    pub fn set_compact_index(&mut self, enabled: bool) {
+        if enabled {
+            self.index.header.flags |= crate::INDEX_FLAG_COMPACT_AUDIO;
+        } else {
+            self.index.header.flags &= !crate::INDEX_FLAG_COMPACT_AUDIO;
+        }
+    }
+
+    /// 在索引条目中记录每个 chunk 密文 + tag 的 CRC32（[`crate::INDEX_FLAG_CHUNK_CRC`]）
+    ///
+    /// 供 `verify` 区分位翻转与头部篡改；旧版本读取器忽略该标志。
+    pub fn set_chunk_crc(&mut self, enabled: bool) {
+        if enabled {
+            self.index.header.flags |= crate::INDEX_FLAG_CHUNK_CRC;
+        } else {
+            self.index.header.flags &= !crate::INDEX_FLAG_CHUNK_CRC;
+        }
+    }
+
+    /// 在明文头部镜像原始格式（`FLAG_FORMAT_HINT`），见 [`FurryHeaderV1::original_format_hint`]
+    ///
+    /// 原始格式未知时不写入。旧版本读取器会因未知标志位拒绝打开。
+    pub fn set_header_format_hint(&mut self, enabled: bool) {
+        let format = Some(self.index.header.original_format)
+            .filter(|format| enabled && *format != OriginalFormat::Unknown);
+        self.header.set_original_format_hint(format);
+    }
+
     /// 写入 AUDIO chunk
     pub fn write_audio_chunk(
         &mut self,
@@ -60,18 +252,39 @@ impl<W: Write + Seek> FurryWriter<W> {
     /// 写入 PADDING chunk
     pub fn write_padding_chunk(&mut self, size: usize) -> Result<(), FormatError> {
         let mut padding = vec![0u8; size];
-        furry_crypto::generate_random_bytes(&mut padding)?;
+        self.rng.fill_bytes(&mut padding)?;
         self.write_chunk_internal(ChunkType::Padding, &padding, 0, 0, 0)
     }
 
     /// 写入 META chunk
+    ///
+    /// 可在 `finish` 之前的任意时刻调用，包括 AUDIO 之后（如流式计算出的摘要/时长）。
+    /// 读取端通过索引定位 META，与其物理位置无关；同 kind 多次写入时以 chunk_seq 最大者为准。
     pub fn write_meta_chunk(
         &mut self,
         kind: crate::MetaKind,
         data: &[u8],
         chunk_flags: u8,
     ) -> Result<(), FormatError> {
-        self.write_chunk_internal(ChunkType::Meta, data, 0, kind as u16, chunk_flags)
+        // 与内置 id 冲突的 Custom 会被读回为内置类型，拒绝写入
+        if let crate::MetaKind::Custom(id) = kind {
+            if crate::MetaKind::custom(id).is_none() {
+                return Err(FormatError::ReservedMetaKind(id));
+            }
+        }
+        self.write_chunk_internal(ChunkType::Meta, data, 0, kind.id(), chunk_flags)
+    }
+
+    /// gzip 压缩后写入 META chunk（`FLAG_META_GZIP`），适合较大的 tags/歌词等文本
+    ///
+    /// 读取端的大小上限按解压后大小计算；旧版本读取器会把压缩数据当作明文载荷。
+    pub fn write_meta_chunk_compressed(
+        &mut self,
+        kind: crate::MetaKind,
+        data: &[u8],
+    ) -> Result<(), FormatError> {
+        let compressed = crate::compress_meta_payload(data)?;
+        self.write_meta_chunk(kind, &compressed, crate::chunk_flags::FLAG_META_GZIP)
     }
 
     fn write_chunk_internal(
@@ -82,50 +295,56 @@ impl<W: Write + Seek> FurryWriter<W> {
         meta_kind: u16,
         chunk_flags: u8,
     ) -> Result<(), FormatError> {
+        // 长度字段均为 u32，超限时报错而不是静默截断
+        if data.len() > MAX_CHUNK_PLAIN_LEN {
+            return Err(FormatError::ChunkTooLarge(data.len()));
+        }
+        if self.index.entries.len() >= MAX_INDEX_ENTRIES {
+            return Err(FormatError::TooManyChunks(MAX_INDEX_ENTRIES));
+        }
+        let plain_len = data.len() as u32;
+
         let chunk_seq = self.chunk_seq;
         self.chunk_seq += 1;
 
         let mut chunk_header =
-            ChunkRecordHeaderV1::new(chunk_type, chunk_seq, virtual_offset, data.len() as u32);
+            ChunkRecordHeaderV1::new(chunk_type, chunk_seq, virtual_offset, plain_len);
         chunk_header.chunk_flags = chunk_flags;
 
-        // 加密数据
-        let mut ciphertext = data.to_vec();
-        let nonce = furry_crypto::nonce_for_chunk(&self.keys.nonce_prefix, chunk_seq);
-        let aad = furry_crypto::build_aad_v1(
-            &self.header.file_id,
-            self.header.version,
-            self.header.flags,
-            &chunk_header.to_bytes(),
-        );
-
-        let tag = furry_crypto::encrypt_in_place_detached(
-            &self.keys.aead_key,
-            &nonce,
-            &aad,
-            &mut ciphertext,
-        )?;
+        // 加密数据（复用 scratch 缓冲区）
+        self.scratch.clear();
+        self.scratch.extend_from_slice(data);
+        let encrypted = seal_in_place(&self.header, &self.keys, &chunk_header, &mut self.scratch);
+        let tag = match encrypted {
+            Ok(tag) => tag,
+            Err(e) => {
+                // 加密失败时缓冲区仍是明文，立即清零
+                self.scratch.zeroize();
+                return Err(e.into());
+            }
+        };
 
         // 记录文件偏移
         let file_offset = self.current_offset;
 
         // 写入 chunk
-        chunk_header.write_to(&mut self.inner)?;
-        self.inner.write_all(&ciphertext)?;
-        self.inner.write_all(&tag)?;
+        let inner = self.inner.as_mut().expect("inner is only taken by finish");
+        chunk_header.write_to(inner)?;
+        inner.write_all(&self.scratch)?;
+        inner.write_all(&tag)?;
 
         let record_len = chunk_header.record_len();
         self.current_offset += record_len as u64;
 
         // 添加索引条目
-        let entry = match chunk_type {
+        let mut entry = match chunk_type {
             ChunkType::Audio => {
-                self.index.header.audio_stream_len += data.len() as u64;
+                self.index.header.audio_stream_len += plain_len as u64;
                 IndexEntryV1::new_audio(
                     chunk_seq,
                     file_offset,
                     record_len,
-                    data.len() as u32,
+                    plain_len,
                     virtual_offset,
                 )
             }
@@ -135,61 +354,122 @@ impl<W: Write + Seek> FurryWriter<W> {
                     chunk_seq,
                     file_offset,
                     record_len,
-                    data.len() as u32,
+                    plain_len,
                     kind,
                     chunk_flags,
                 )
             }
             ChunkType::Padding => {
-                IndexEntryV1::new_padding(chunk_seq, file_offset, record_len, data.len() as u32)
+                IndexEntryV1::new_padding(chunk_seq, file_offset, record_len, plain_len)
             }
             _ => return Ok(()),
         };
+        if self.index.has_chunk_crc() {
+            let mut hasher = crc32fast::Hasher::new();
+            hasher.update(&self.scratch);
+            hasher.update(&tag);
+            entry.reserved2 = hasher.finalize();
+        }
         self.index.add_entry(entry);
 
         Ok(())
     }
 
-    /// 完成写入（写入 INDEX 并更新头部）
+    /// 预估 `finish` 后的文件总大小（按当前条目数计算 INDEX 记录）
+    pub fn projected_total_size(&self) -> u64 {
+        self.current_offset
+            + index_record_len(self.index.serialized_len())
+            + self.header.fake_footer_len as u64
+    }
+
+    /// 在 INDEX 之后追加 `len` 个随机字节（fake footer），长度记录在头部
+    ///
+    /// 读取器按 `index_offset` 定位索引，并把文件结尾视为物理长度减去 `len`。
+    pub fn set_fake_footer_len(&mut self, len: u32) {
+        self.header.fake_footer_len = len;
+    }
+
+    /// 追加 PADDING，使 `finish` 后文件总大小恰为 `target`
+    ///
+    /// 已计入新增 padding 条目对 INDEX 大小的影响；应在最后一个 chunk 之后调用。
+    pub fn pad_to_total_size(
+        &mut self,
+        target: u64,
+        max_chunk_size: usize,
+    ) -> Result<(), FormatError> {
+        if target == self.projected_total_size() {
+            return Ok(());
+        }
+        // 追加 PADDING 后索引必为完整布局
+        let needed = self.current_offset
+            + index_record_len(INDEX_HEADER_LEN + self.index.entries.len() * INDEX_ENTRY_LEN)
+            + self.header.fake_footer_len as u64;
+        let remaining = target
+            .checked_sub(needed)
+            .ok_or(FormatError::PaddingTargetTooSmall { needed, target })?;
+        let sizes = padding_chunk_sizes(remaining, max_chunk_size)
+            .ok_or(FormatError::PaddingTargetUnreachable(remaining))?;
+        for size in sizes {
+            self.write_padding_chunk(size)?;
+        }
+        Ok(())
+    }
+
+    /// 完成写入（写入 INDEX、更新头部并 `flush`）
+    ///
+    /// 只 flush 到操作系统；需要落盘保证时由调用方对文件调用 `sync_all`。
     pub fn finish(mut self) -> Result<W, FormatError> {
+        let mut inner = self.inner.take().expect("inner is only taken by finish");
         // 写入 INDEX chunk
         let index_offset = self.current_offset;
         let index_data = self.index.to_bytes();
-        let index_plain_len = index_data.len() as u32;
+        let index_plain_len = u32::try_from(index_data.len())
+            .map_err(|_| FormatError::TooManyChunks(MAX_INDEX_ENTRIES))?;
 
         let chunk_seq = self.chunk_seq;
         let chunk_header =
             ChunkRecordHeaderV1::new(ChunkType::Index, chunk_seq, 0, index_plain_len);
 
         let mut ciphertext = index_data;
-        let nonce = furry_crypto::nonce_for_chunk(&self.keys.nonce_prefix, chunk_seq);
-        let aad = furry_crypto::build_aad_v1(
-            &self.header.file_id,
-            self.header.version,
-            self.header.flags,
-            &chunk_header.to_bytes(),
-        );
-
-        let tag = furry_crypto::encrypt_in_place_detached(
-            &self.keys.aead_key,
-            &nonce,
-            &aad,
-            &mut ciphertext,
-        )?;
-
-        chunk_header.write_to(&mut self.inner)?;
-        self.inner.write_all(&ciphertext)?;
-        self.inner.write_all(&tag)?;
+        let tag = seal_in_place(&self.header, &self.keys, &chunk_header, &mut ciphertext)?;
+
+        let mut record = chunk_header.to_bytes().to_vec();
+        record.extend_from_slice(&ciphertext);
+        record.extend_from_slice(&tag);
+        inner.write_all(&record)?;
 
         let index_total_len = chunk_header.record_len();
 
+        if self.header.fake_footer_len > 0 {
+            let mut footer = vec![0u8; self.header.fake_footer_len as usize];
+            self.rng.fill_bytes(&mut footer)?;
+            inner.write_all(&footer)?;
+        }
+
         // 更新头部
         self.header.index_offset = index_offset;
         self.header.index_total_len = index_total_len;
+        if self.header.flags & crate::FLAG_INDEX_MAC != 0 {
+            self.header.reserved2 = self.header.index_mac(&self.keys, &record)?;
+        }
+        self.header.header_crc32 = self.header.compute_crc32();
 
-        self.inner.seek(SeekFrom::Start(0))?;
-        self.header.write_to(&mut self.inner)?;
+        inner.seek(SeekFrom::Start(0))?;
+        self.header.write_to(&mut inner)?;
+        inner.flush()?;
+
+        Ok(inner)
+    }
+}
 
-        Ok(self.inner)
+impl<W: Write + Seek> Drop for FurryWriter<W> {
+    fn drop(&mut self) {
+        // 未调用 finish 的文件没有索引，只能用 open_with_recovery 打开
+        if cfg!(debug_assertions) && self.inner.is_some() && !std::thread::panicking() {
+            eprintln!(
+                "FurryWriter dropped without finish(); output is not finalized ({} chunks written)",
+                self.chunk_seq
+            );
+        }
     }
 }
@@ -0,0 +1,93 @@
+//! 异步事件流与命令发送端（`async` feature）
+//!
+//! 引擎内部仍使用 crossbeam 通道；这里各用一个转发线程桥接到 `async-channel`，
+//! 异步 UI 可以 `.await` 事件而无需轮询。
+
+use std::thread;
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use crate::{PlayerCommand, PlayerEvent, PlayerHandle};
+
+/// 事件转发线程检查流是否已被丢弃的间隔
+const CLOSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl PlayerHandle {
+    /// 将事件通道桥接为异步 `Stream`
+    ///
+    /// 与 `evt_rx` 共享同一队列：每个事件只会被其中一方取走，二者择一使用。
+    /// 引擎退出时转发线程立即结束；流被丢弃后，转发线程至多在
+    /// [`CLOSE_POLL_INTERVAL`] 内察觉并退出，不再从共享队列取走事件。
+    /// 流未实现 `Unpin`，需先 `pin!`/`Box::pin`。
+    pub fn event_stream(&self) -> impl Stream<Item = PlayerEvent> + Send + 'static {
+        let (tx, rx) = async_channel::bounded(64);
+        let evt_rx = self.evt_rx.clone();
+        thread::spawn(move || {
+            while !tx.is_closed() {
+                match evt_rx.recv_timeout(CLOSE_POLL_INTERVAL) {
+                    Ok(event) => {
+                        if tx.send_blocking(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => {}
+                    Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+        rx
+    }
+
+    /// 获取异步命令发送端
+    pub fn async_controller(&self) -> AsyncPlayerController {
+        let (tx, rx) = async_channel::bounded::<PlayerCommand>(32);
+        let cmd_tx = self.cmd_tx.clone();
+        thread::spawn(move || {
+            while let Ok(cmd) = rx.recv_blocking() {
+                if cmd_tx.send(cmd).is_err() {
+                    break;
+                }
+            }
+        });
+        AsyncPlayerController { tx }
+    }
+}
+
+/// 异步命令发送端（可克隆）
+///
+/// 命令队列满时 `send` 会让出执行权而不是阻塞线程；返回 `false` 表示引擎已退出。
+#[derive(Debug, Clone)]
+pub struct AsyncPlayerController {
+    tx: async_channel::Sender<PlayerCommand>,
+}
+
+impl AsyncPlayerController {
+    /// 发送任意命令
+    pub async fn send(&self, cmd: PlayerCommand) -> bool {
+        self.tx.send(cmd).await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_event_stream_leaves_events_to_evt_rx() {
+        let (cmd_tx, _cmd_rx) = crossbeam_channel::unbounded();
+        let (evt_tx, evt_rx) = crossbeam_channel::unbounded();
+        let handle = PlayerHandle { cmd_tx, evt_rx };
+
+        drop(handle.event_stream());
+        thread::sleep(CLOSE_POLL_INTERVAL * 3);
+        evt_tx.send(PlayerEvent::TrackEnded).unwrap();
+        // 给仍在运行的转发线程足够时间取走事件
+        thread::sleep(CLOSE_POLL_INTERVAL);
+
+        assert!(matches!(
+            handle.evt_rx.try_recv(),
+            Ok(PlayerEvent::TrackEnded)
+        ));
+    }
+}
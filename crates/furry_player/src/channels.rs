@@ -0,0 +1,195 @@
+//! 多声道处理
+//!
+//! 解码器输出 1–8 声道交错采样，声道顺序遵循 WAV/SMPTE 约定
+//! （FL FR FC LFE BL BR SL SR）。输出设备不支持源声道数时按本模块降混。
+
+/// 声道在声场中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Left,
+    Right,
+    Center,
+    Lfe,
+    SurroundLeft,
+    SurroundRight,
+    SurroundCenter,
+}
+
+/// 按声道数推断的标准布局
+fn layout(channels: usize) -> &'static [Position] {
+    use Position::*;
+    match channels {
+        1 => &[Center],
+        2 => &[Left, Right],
+        3 => &[Left, Right, Center],
+        4 => &[Left, Right, SurroundLeft, SurroundRight],
+        5 => &[Left, Right, Center, SurroundLeft, SurroundRight],
+        6 => &[Left, Right, Center, Lfe, SurroundLeft, SurroundRight],
+        7 => &[
+            Left,
+            Right,
+            Center,
+            Lfe,
+            SurroundCenter,
+            SurroundLeft,
+            SurroundRight,
+        ],
+        _ => &[
+            Left,
+            Right,
+            Center,
+            Lfe,
+            SurroundLeft,
+            SurroundRight,
+            SurroundLeft,
+            SurroundRight,
+        ],
+    }
+}
+
+/// 降混到立体声时各位置的 (左, 右) 系数（ITU-R BS.775，丢弃 LFE）
+fn stereo_gains(position: Position) -> (f32, f32) {
+    const MINUS_3DB: f32 = std::f32::consts::FRAC_1_SQRT_2;
+    match position {
+        Position::Left => (1.0, 0.0),
+        Position::Right => (0.0, 1.0),
+        Position::Center => (MINUS_3DB, MINUS_3DB),
+        Position::Lfe => (0.0, 0.0),
+        Position::SurroundLeft => (MINUS_3DB, 0.0),
+        Position::SurroundRight => (0.0, MINUS_3DB),
+        Position::SurroundCenter => (0.5, 0.5),
+    }
+}
+
+/// 把 `from` 声道的交错采样转换为 `to` 声道
+///
+/// - 多声道 → 立体声：按 ITU 系数降混并归一化，满幅输入不会削波
+/// - 任意 → 单声道：先降混为立体声再取平均
+/// - 单声道 → 多声道：复制到每个声道
+/// - 其余情况保留前 `min(from, to)` 个声道，多出的声道补静音
+pub fn remix(samples: &[f32], from: usize, to: usize) -> Vec<f32> {
+    let (from, to) = (from.max(1), to.max(1));
+    if from == to {
+        return samples.to_vec();
+    }
+    let frames = samples.chunks_exact(from);
+    let mut out = Vec::with_capacity(frames.len() * to);
+
+    if from == 1 {
+        for frame in frames {
+            out.extend(std::iter::repeat_n(frame[0], to));
+        }
+        return out;
+    }
+
+    if to <= 2 {
+        let gains: Vec<(f32, f32)> = layout(from)
+            .iter()
+            .take(from)
+            .map(|&p| stereo_gains(p))
+            .collect();
+        let norm_l: f32 = gains.iter().map(|g| g.0).sum();
+        let norm_r: f32 = gains.iter().map(|g| g.1).sum();
+        let norm = norm_l.max(norm_r).max(1.0);
+        for frame in frames {
+            let (l, r) = frame
+                .iter()
+                .zip(&gains)
+                .fold((0.0, 0.0), |(l, r), (s, g)| (l + s * g.0, r + s * g.1));
+            if to == 1 {
+                out.push((l + r) / (2.0 * norm));
+            } else {
+                out.push(l / norm);
+                out.push(r / norm);
+            }
+        }
+        return out;
+    }
+
+    let keep = from.min(to);
+    for frame in frames {
+        out.extend_from_slice(&frame[..keep]);
+        out.extend(std::iter::repeat_n(0.0, to - keep));
+    }
+    out
+}
+
+/// 各声道的峰值电平（绝对值），长度等于 `channels`
+pub fn channel_peaks(samples: &[f32], channels: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    let mut peaks = vec![0.0f32; channels];
+    for frame in samples.chunks_exact(channels) {
+        for (peak, s) in peaks.iter_mut().zip(frame) {
+            *peak = peak.max(s.abs());
+        }
+    }
+    peaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AudioDecoder, DecodeHint};
+
+    #[test]
+    fn test_surround_source_levels_and_downmix() {
+        // 5.1 WAV：每个声道一个固定电平
+        let channels = 6u16;
+        let frames = 4_800u32;
+        let levels = [1000i16, 2000, 3000, 4000, 5000, 6000];
+        let data_len = frames * channels as u32 * 2;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&48_000u32.to_le_bytes());
+        wav.extend_from_slice(&(48_000 * channels as u32 * 2).to_le_bytes());
+        wav.extend_from_slice(&(channels * 2).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for _ in 0..frames {
+            for level in levels {
+                wav.extend_from_slice(&level.to_le_bytes());
+            }
+        }
+
+        let mut decoder = AudioDecoder::new(
+            std::io::Cursor::new(wav),
+            &DecodeHint::from_extension("wav"),
+        )
+        .unwrap();
+        assert_eq!(decoder.info.channels, 6);
+        assert_eq!(decoder.current_timestamp(), None);
+
+        let mut decoded = 0;
+        while let Some(block) = decoder.decode_next().unwrap() {
+            assert_eq!(
+                decoder.current_timestamp(),
+                Some(std::time::Duration::from_secs_f64(
+                    decoded as f64 / 48_000.0
+                ))
+            );
+            let peaks = channel_peaks(&block, 6);
+            for (peak, level) in peaks.iter().zip(levels) {
+                assert_eq!(*peak, level as f32 / 32768.0);
+            }
+            let stereo = remix(&block, 6, 2);
+            assert_eq!(stereo.len(), block.len() / 3);
+            assert!(stereo.iter().all(|s| s.abs() <= 1.0));
+            assert_eq!(remix(&block, 6, 1).len(), block.len() / 6);
+            decoded += block.len() / 6;
+        }
+        assert_eq!(decoded, frames as usize);
+
+        // 满幅 5.1 降混后不削波，LFE 不进入立体声
+        let full = [1.0f32; 6];
+        let stereo = remix(&full, 6, 2);
+        assert!((stereo[0] - 1.0).abs() < 1e-6 && (stereo[1] - 1.0).abs() < 1e-6);
+        let lfe_only = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        assert_eq!(remix(&lfe_only, 6, 2), vec![0.0, 0.0]);
+    }
+}
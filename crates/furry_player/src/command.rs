@@ -8,7 +8,19 @@ use std::time::Duration;
 pub enum PlayerCommand {
     /// 加载 .furry 文件
     Load(PathBuf),
-    /// 播放
+    /// 直接加载普通音频文件（不经过 .furry 层，用于试听/对比）
+    LoadRaw(PathBuf),
+    /// 加载多曲目 .furry 专辑中的一首（序号见 `PlayerEvent::TrackTable`）
+    LoadTrack { path: PathBuf, track: u32 },
+    /// 替换播放队列（不加载）；当前序号仍在范围内时保留
+    SetQueue(Vec<PathBuf>),
+    /// 加载并播放队列中的第 `n` 首，发送 `TrackChanged`；曲目结束后自动播放下一首
+    PlayIndex(usize),
+    /// 播放队列中的下一首（末尾回到第一首）
+    Next,
+    /// 播放队列中的上一首（开头回到最后一首）
+    Previous,
+    /// 播放；加载中收到时会在加载完成后自动开始
     Play,
     /// 暂停
     Pause,
@@ -16,8 +28,29 @@ pub enum PlayerCommand {
     Stop,
     /// 跳转到指定位置
     Seek(Duration),
-    /// 设置音量 (0.0 - 1.0)
+    /// 按总时长的比例跳转（进度条 0.0 - 1.0，超出范围时钳制）
+    ///
+    /// 时长未知的 .furry 曲目按虚拟流的字节比例跳转（适用于 CBR），之后的进度为估算值；
+    /// 时长未知的普通文件无法换算，引擎回复 `Error`。
+    SeekFraction(f64),
+    /// 设置音量 (0.0 - 1.0)，超出范围时钳制；引擎以 `VolumeChanged` 回报实际值
     SetVolume(f32),
+    /// 查询当前音量，引擎回复 `VolumeChanged`
+    QueryVolume,
+    /// 查询引擎完整状态，引擎回复 `StateSnapshot`（新连接的 UI 用于初始化）
+    QueryState,
+    /// 设置进度事件的发送间隔（最小 16ms）
+    SetPositionUpdateInterval(Duration),
+    /// 设置输出缓冲区大小（帧，范围见 `BUFFER_SIZE_RANGE`），下次加载曲目时生效
+    ///
+    /// 调小降低延迟，调大减少系统繁忙时的断音。
+    SetBufferSize(usize),
+    /// 设置渲染目标（WAV 文件）；无输出设备时将解码结果写入该文件
+    SetRenderTarget(Option<PathBuf>),
+    /// 重置引擎：卸载当前曲目并恢复所有设置（音量、缓冲区、进度间隔、渲染目标）为默认值
+    ///
+    /// 完成后总是发送 `StateChanged(Idle)`，适合在切换用户/曲库或出错后复用同一引擎。
+    Reset,
     /// 关闭引擎
     Shutdown,
 }
@@ -29,10 +62,50 @@ pub enum PlayerEvent {
     StateChanged(PlaybackState),
     /// 播放进度更新
     Position(Duration),
+    /// 精确播放进度（与 `Position` 同时发送），用于歌词高亮/频谱等逐帧同步
+    PositionPrecise {
+        time: Duration,
+        /// 曲目起点起算的采样帧
+        frame: u64,
+        sample_rate: u32,
+    },
     /// 总时长更新
     Duration(Duration),
     /// 当前曲目信息
     TrackInfo(TrackInfo),
+    /// 曲目加载完成（解码器与输出已就绪），在 `TrackInfo`/`Duration` 之后发送
+    Loaded,
+    /// `Load` 的文件是多曲目专辑，在 `Loaded` 之前发送；UI 据此列出子播放列表，
+    /// 用 `LoadTrack` 播放其中一首
+    TrackTable {
+        path: PathBuf,
+        tracks: Vec<furry_format::TrackTableEntry>,
+    },
+    /// 引擎当前音量（钳制后的目标值）
+    ///
+    /// 在 `SetVolume`、`Reset` 之后以及响应 `QueryVolume` 时发送，多个控制端据此同步 UI。
+    VolumeChanged(f32),
+    /// 引擎状态快照，响应 `QueryState`
+    StateSnapshot {
+        state: PlaybackState,
+        /// 当前曲目（未加载时为 `None`）
+        track: Option<TrackInfo>,
+        position: Duration,
+        duration: Duration,
+        volume: f32,
+    },
+    /// 播放队列切换到新曲目（`PlayIndex`/`Next`/`Previous` 或自动播放下一首），在加载之前发送
+    TrackChanged {
+        /// 队列中的序号
+        index: usize,
+        path: PathBuf,
+        /// 队列长度，便于 UI 定位/滚动列表
+        queue_len: usize,
+    },
+    /// 输出缓冲区填充比例（0.0 - 1.0），供自适应下载/预读节流使用
+    ///
+    /// 随 `Position` 按进度间隔检查，变化超过 `BUFFER_LEVEL_STEP` 才发送；渲染到文件时恒为 1.0。
+    BufferLevel(f32),
     /// 曲目播放结束
     TrackEnded,
     /// 错误
@@ -58,4 +131,14 @@ pub struct TrackInfo {
     pub sample_rate: u32,
     pub channels: u16,
     pub duration: Duration,
+    /// 采样位深（未知为 `None`）
+    pub bits_per_sample: Option<u32>,
+    /// 平均码率（bit/s，未知为 `None`）
+    pub bit_rate: Option<u32>,
+    /// 能否准确 seek（时长未知或流不可 seek 时为 `false`，UI 应禁用进度条拖动）
+    pub seekable: bool,
+    /// 多曲目专辑中的曲目序号（整个文件加载时为 `None`）
+    pub album_track: Option<u32>,
+    /// 格式提示与实际 codec 不一致时的警告（仍可播放，UI 可提示文件可能被错标）
+    pub format_warning: Option<String>,
 }
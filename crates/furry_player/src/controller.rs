@@ -0,0 +1,133 @@
+//! 播放控制器
+//!
+//! 可克隆的命令发送端，供多个 UI 组件共享。
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+
+use crate::PlayerCommand;
+
+/// 播放控制器（可克隆，线程安全）
+///
+/// 所有方法返回 `false` 表示引擎已退出、命令未送达。
+#[derive(Debug, Clone)]
+pub struct PlayerController {
+    tx: Sender<PlayerCommand>,
+}
+
+impl PlayerController {
+    /// 从命令通道创建控制器
+    pub fn new(tx: Sender<PlayerCommand>) -> Self {
+        Self { tx }
+    }
+
+    /// 发送任意命令
+    pub fn send(&self, cmd: PlayerCommand) -> bool {
+        self.tx.send(cmd).is_ok()
+    }
+
+    /// 加载 .furry 文件
+    pub fn load(&self, path: impl Into<PathBuf>) -> bool {
+        self.send(PlayerCommand::Load(path.into()))
+    }
+
+    /// 直接加载普通音频文件（不经过 .furry 层）
+    pub fn load_raw(&self, path: impl Into<PathBuf>) -> bool {
+        self.send(PlayerCommand::LoadRaw(path.into()))
+    }
+
+    /// 加载多曲目专辑中的第 `track` 首
+    pub fn load_track(&self, path: impl Into<PathBuf>, track: u32) -> bool {
+        self.send(PlayerCommand::LoadTrack {
+            path: path.into(),
+            track,
+        })
+    }
+
+    /// 替换播放队列，见 [`PlayerCommand::SetQueue`]
+    pub fn set_queue(&self, paths: Vec<PathBuf>) -> bool {
+        self.send(PlayerCommand::SetQueue(paths))
+    }
+
+    /// 播放队列中的第 `index` 首
+    pub fn play_index(&self, index: usize) -> bool {
+        self.send(PlayerCommand::PlayIndex(index))
+    }
+
+    /// 下一首
+    pub fn next(&self) -> bool {
+        self.send(PlayerCommand::Next)
+    }
+
+    /// 上一首
+    pub fn previous(&self) -> bool {
+        self.send(PlayerCommand::Previous)
+    }
+
+    /// 播放
+    pub fn play(&self) -> bool {
+        self.send(PlayerCommand::Play)
+    }
+
+    /// 暂停
+    pub fn pause(&self) -> bool {
+        self.send(PlayerCommand::Pause)
+    }
+
+    /// 停止
+    pub fn stop(&self) -> bool {
+        self.send(PlayerCommand::Stop)
+    }
+
+    /// 跳转到指定位置
+    pub fn seek(&self, pos: Duration) -> bool {
+        self.send(PlayerCommand::Seek(pos))
+    }
+
+    /// 按总时长的比例跳转 (0.0 - 1.0)
+    pub fn seek_fraction(&self, fraction: f64) -> bool {
+        self.send(PlayerCommand::SeekFraction(fraction))
+    }
+
+    /// 设置音量 (0.0 - 1.0)
+    pub fn set_volume(&self, volume: f32) -> bool {
+        self.send(PlayerCommand::SetVolume(volume))
+    }
+
+    /// 查询当前音量，结果以 `PlayerEvent::VolumeChanged` 返回
+    pub fn query_volume(&self) -> bool {
+        self.send(PlayerCommand::QueryVolume)
+    }
+
+    /// 查询引擎完整状态，结果以 `PlayerEvent::StateSnapshot` 返回
+    pub fn query_state(&self) -> bool {
+        self.send(PlayerCommand::QueryState)
+    }
+
+    /// 设置进度事件间隔
+    pub fn set_position_update_interval(&self, interval: Duration) -> bool {
+        self.send(PlayerCommand::SetPositionUpdateInterval(interval))
+    }
+
+    /// 设置输出缓冲区大小（帧），下次加载曲目时生效
+    pub fn set_buffer_size(&self, frames: usize) -> bool {
+        self.send(PlayerCommand::SetBufferSize(frames))
+    }
+
+    /// 设置渲染目标（无输出设备时写入 WAV 文件）
+    pub fn set_render_target(&self, path: Option<PathBuf>) -> bool {
+        self.send(PlayerCommand::SetRenderTarget(path))
+    }
+
+    /// 重置引擎为初始状态，见 [`PlayerCommand::Reset`]
+    pub fn reset(&self) -> bool {
+        self.send(PlayerCommand::Reset)
+    }
+
+    /// 关闭引擎
+    pub fn shutdown(&self) -> bool {
+        self.send(PlayerCommand::Shutdown)
+    }
+}
@@ -2,16 +2,23 @@
 //!
 //! 使用 symphonia 解码音频流
 
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use symphonia::core::audio::{SampleBuffer, SignalSpec};
-use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::audio::{Channels, SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{
+    CodecType, Decoder, DecoderOptions, CODEC_TYPE_AAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP1,
+    CODEC_TYPE_MP2, CODEC_TYPE_MP3, CODEC_TYPE_NULL, CODEC_TYPE_OPUS, CODEC_TYPE_VORBIS,
+};
 use symphonia::core::errors::Error as SymphoniaError;
-use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo, Track};
 use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
+use symphonia::core::units::TimeBase;
+
+use crate::{StreamError, WavDecoder};
 
 /// 解码器错误
 #[derive(thiserror::Error, Debug)]
@@ -20,18 +27,42 @@ pub enum DecoderError {
     NoTrack,
     #[error("Unsupported codec")]
     UnsupportedCodec,
+    #[error("Invalid channel count: {0} (supported 1-8)")]
+    InvalidChannels(usize),
+    #[error("Invalid sample rate: {0} Hz (supported 8000-384000)")]
+    InvalidSampleRate(u32),
     #[error("Decode error: {0}")]
     Decode(String),
     #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
+    /// 音频 chunk 认证失败（文件损坏或被篡改），见 [`StreamError::ChunkAuthFailed`]
+    #[error("Audio chunk {0} failed authentication")]
+    ChunkAuthFailed(usize),
+}
+
+impl From<std::io::Error> for DecoderError {
+    fn from(e: std::io::Error) -> Self {
+        match StreamError::auth_failed_chunk(&e) {
+            Some(index) => DecoderError::ChunkAuthFailed(index),
+            None => DecoderError::Io(e),
+        }
+    }
 }
 
 impl From<SymphoniaError> for DecoderError {
     fn from(e: SymphoniaError) -> Self {
-        DecoderError::Decode(e.to_string())
+        match e {
+            SymphoniaError::IoError(e) => e.into(),
+            e => DecoderError::Decode(e.to_string()),
+        }
     }
 }
 
+/// 支持的声道数范围
+pub const CHANNELS_RANGE: std::ops::RangeInclusive<usize> = 1..=8;
+/// 支持的采样率范围（Hz）
+pub const SAMPLE_RATE_RANGE: std::ops::RangeInclusive<u32> = 8_000..=384_000;
+
 /// 音频信息
 #[derive(Debug, Clone)]
 pub struct AudioInfo {
@@ -39,48 +70,275 @@ pub struct AudioInfo {
     pub channels: usize,
     pub duration: Option<Duration>,
     pub codec: String,
+    /// 所选轨道 id（内置 WAV 解码器为 `None`）
+    pub track_id: Option<u32>,
+    /// 采样位深（无损/PCM 为真实位深，有损格式通常未知）
+    pub bits_per_sample: Option<u32>,
+    /// 平均码率（bit/s），由流长度与时长估算；任一未知时为 `None`
+    pub bit_rate: Option<u32>,
+    /// 能否准确 seek：底层流可 seek 且时长已知
+    pub seekable: bool,
+    /// 格式提示与实际 codec 不一致时的警告（如以 mp3 打包的 FLAC 数据）
+    pub format_mismatch: Option<String>,
+}
+
+/// 多轨容器的轨道选择
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrackSelection {
+    /// 可解码音频轨中声道数最多、采样率最高、时长已知者；并列时取靠前的轨道
+    #[default]
+    Best,
+    /// 指定轨道 id（必须是可解码的音频轨）
+    Id(u32),
+}
+
+/// 是否为可解码的音频轨：视频/字幕/数据轨及未注册的 codec 均跳过
+fn is_decodable_audio(track: &Track) -> bool {
+    let codec = track.codec_params.codec;
+    codec != CODEC_TYPE_NULL && symphonia::default::get_codecs().get_codec(codec).is_some()
+}
+
+/// 按 `selection` 选出要解码的音频轨
+pub fn select_audio_track(tracks: &[Track], selection: TrackSelection) -> Option<&Track> {
+    let mut audio = tracks.iter().filter(|t| is_decodable_audio(t));
+    match selection {
+        TrackSelection::Id(id) => audio.find(|t| t.id == id),
+        // max_by_key 并列时返回最后一个，反向迭代使靠前的轨道胜出
+        TrackSelection::Best => audio.rev().max_by_key(|t| {
+            let params = &t.codec_params;
+            (
+                params.channels.map(|c| c.count()).unwrap_or(0),
+                params.sample_rate.unwrap_or(0),
+                params.n_frames.is_some(),
+            )
+        }),
+    }
+}
+
+/// 解码器格式提示
+///
+/// 只是提示：symphonia 按内容探测，提示有误时仍可能解码成功；
+/// 带提示探测失败时会自动不带提示重试一次。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodeHint {
+    /// 扩展名（不带点），如 `mp3`
+    pub extension: Option<String>,
+    /// MIME 类型，如 `audio/mpeg`
+    pub mime: Option<String>,
+}
+
+impl DecodeHint {
+    /// 由扩展名构造，并为常见格式补上 MIME；空扩展名视为无提示
+    pub fn from_extension(ext: &str) -> Self {
+        if ext.is_empty() {
+            return Self::default();
+        }
+        let ext = ext.to_ascii_lowercase();
+        let mime = match ext.as_str() {
+            "mp3" => Some("audio/mpeg"),
+            "flac" => Some("audio/flac"),
+            "ogg" | "opus" => Some("audio/ogg"),
+            "wav" => Some("audio/wav"),
+            "aac" => Some("audio/aac"),
+            "m4a" | "mp4" => Some("audio/mp4"),
+            _ => None,
+        };
+        Self {
+            extension: Some(ext),
+            mime: mime.map(str::to_string),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extension.is_none() && self.mime.is_none()
+    }
+
+    fn to_probe_hint(&self) -> Hint {
+        let mut hint = Hint::new();
+        if let Some(ext) = &self.extension {
+            hint.with_extension(ext);
+        }
+        if let Some(mime) = &self.mime {
+            hint.mime_type(mime);
+        }
+        hint
+    }
+}
+
+/// 探测期间共享的源：探测失败时仍持有一份句柄，以便回绕后重试
+struct SharedSource(Arc<Mutex<Box<dyn MediaSource>>>);
+
+impl SharedSource {
+    fn lock(&self) -> std::io::Result<std::sync::MutexGuard<'_, Box<dyn MediaSource>>> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("decoder source lock poisoned"))
+    }
+}
+
+impl Read for SharedSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.lock()?.read(buf)
+    }
+}
+
+impl Seek for SharedSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.lock()?.seek(pos)
+    }
+}
+
+impl MediaSource for SharedSource {
+    fn is_seekable(&self) -> bool {
+        self.lock().is_ok_and(|s| s.is_seekable())
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.lock().ok()?.byte_len()
+    }
+}
+
+/// 扩展名提示与实际 codec 是否一致；未知扩展名返回 `None`
+pub fn codec_matches_hint(ext: &str, codec: CodecType) -> Option<bool> {
+    let lossy = [
+        CODEC_TYPE_MP1,
+        CODEC_TYPE_MP2,
+        CODEC_TYPE_MP3,
+        CODEC_TYPE_VORBIS,
+        CODEC_TYPE_OPUS,
+        CODEC_TYPE_AAC,
+    ];
+    let matches = match ext.to_ascii_lowercase().as_str() {
+        "mp3" => matches!(codec, CODEC_TYPE_MP1 | CODEC_TYPE_MP2 | CODEC_TYPE_MP3),
+        "flac" => codec == CODEC_TYPE_FLAC,
+        // Ogg 容器可承载 Vorbis/Opus/FLAC
+        "ogg" | "opus" => matches!(codec, CODEC_TYPE_VORBIS | CODEC_TYPE_OPUS | CODEC_TYPE_FLAC),
+        // WAV 为 PCM/ADPCM 等未压缩或简单编码
+        "wav" => codec != CODEC_TYPE_FLAC && !lossy.contains(&codec),
+        _ => return None,
+    };
+    Some(matches)
+}
+
+/// 由字节数与时长估算平均码率（bit/s），包含容器与标签开销
+fn estimate_bit_rate(byte_len: u64, duration: Duration) -> Option<u32> {
+    let secs = duration.as_secs_f64();
+    if byte_len == 0 || secs <= 0.0 {
+        return None;
+    }
+    let rate = byte_len as f64 * 8.0 / secs;
+    (rate <= u32::MAX as f64).then_some(rate.round() as u32)
 }
 
 /// 音频解码器
+///
+/// 播放引擎以推模式驱动解码器；自定义输出（如推流到网络）也可以直接拉取：
+/// 每次 [`Self::decode_next`] 返回一个包解码出的交错采样，[`Self::current_timestamp`]
+/// 给出这块采样起点的播放位置，不需要经过输出层。
+///
+/// ```no_run
+/// use furry_player::{AudioDecoder, DecodeHint};
+///
+/// let file = std::fs::File::open("song.flac")?;
+/// let mut decoder = AudioDecoder::new(file, &DecodeHint::from_extension("flac"))?;
+/// let channels = decoder.info.channels;
+/// while let Some(samples) = decoder.decode_next()? {
+///     let at = decoder.current_timestamp().unwrap_or_default();
+///     // 发送 `samples`（`samples.len() / channels` 帧），并以 `at` 作为时间戳
+///     println!("{:?}: {} frames", at, samples.len() / channels);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
 pub struct AudioDecoder {
-    format: Box<dyn FormatReader>,
-    decoder: Box<dyn Decoder>,
-    track_id: u32,
+    backend: Backend,
     spec: SignalSpec,
-    sample_buf: Option<SampleBuffer<f32>>,
     pub info: AudioInfo,
+    /// 无缝播放裁剪范围（未裁剪时间轴上的帧区间）
+    trim: Option<TrimRange>,
+    /// 最近一次 `decode_next` 输出的起点
+    last_timestamp: Option<Duration>,
+}
+
+/// 只输出 `[start, end)` 内的帧；`end` 未知时不裁结尾
+#[derive(Debug, Clone, Copy)]
+struct TrimRange {
+    start: u64,
+    end: Option<u64>,
+}
+
+enum Backend {
+    Symphonia {
+        format: Box<dyn FormatReader>,
+        decoder: Box<dyn Decoder>,
+        track_id: u32,
+        /// 包时间戳的时间基；缺失时按采样帧处理
+        time_base: Option<TimeBase>,
+        sample_buf: Option<SampleBuffer<f32>>,
+    },
+    /// 标准 PCM/float WAV 走内置解码器，不经过 symphonia
+    Wav(WavDecoder<Box<dyn MediaSource>>),
 }
 
 impl AudioDecoder {
     /// 从可读流创建解码器
+    ///
+    /// 提示为 `wav` 或内容为 RIFF/WAVE 且为标准 PCM/float 时使用内置 [`WavDecoder`]，
+    /// 否则使用 symphonia。WAV 头声明的采样率/声道数非法时直接报错。
+    /// 多轨容器按 [`TrackSelection::Best`] 选轨。
     pub fn new<R: Read + Seek + Send + Sync + MediaSource + 'static>(
         source: R,
-        hint: Option<&str>,
+        hint: &DecodeHint,
     ) -> Result<Self, DecoderError> {
-        let mss = MediaSourceStream::new(Box::new(source), Default::default());
+        Self::new_with_track(source, hint, TrackSelection::Best)
+    }
 
-        let mut probe_hint = Hint::new();
-        if let Some(ext) = hint {
-            probe_hint.with_extension(ext);
+    /// 同 [`Self::new`]，但指定多轨容器的选轨方式
+    pub fn new_with_track<R: Read + Seek + Send + Sync + MediaSource + 'static>(
+        source: R,
+        hint: &DecodeHint,
+        selection: TrackSelection,
+    ) -> Result<Self, DecoderError> {
+        let source_seekable = source.is_seekable();
+        let source_len = source.byte_len();
+        let mut source: Box<dyn MediaSource> = Box::new(source);
+        let ext_hint = hint.extension.as_deref();
+        let wav_hint = ext_hint.is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        if wav_hint || (source_seekable && WavDecoder::sniff(&mut source)) {
+            match WavDecoder::try_open(source) {
+                Ok(wav) => return Ok(Self::from_wav(wav)),
+                // 头部可解析但参数非法：symphonia 遇到 0 Hz 会直接 panic，不再回退
+                Err((
+                    _,
+                    e @ (DecoderError::InvalidSampleRate(_) | DecoderError::InvalidChannels(_)),
+                )) => return Err(e),
+                Err((rewound, _)) => source = rewound,
+            }
         }
 
-        let probed = symphonia::default::get_probe()
-            .format(
-                &probe_hint,
+        let shared = Arc::new(Mutex::new(source));
+        let probe = |probe_hint: &Hint| {
+            let mss =
+                MediaSourceStream::new(Box::new(SharedSource(shared.clone())), Default::default());
+            symphonia::default::get_probe().format(
+                probe_hint,
                 mss,
                 &FormatOptions::default(),
                 &MetadataOptions::default(),
             )
-            .map_err(|e| DecoderError::Decode(e.to_string()))?;
+        };
+        let probed = match probe(&hint.to_probe_hint()) {
+            Ok(probed) => probed,
+            // 提示可能有误（错标的文件），回绕后按纯内容探测重试
+            Err(_) if !hint.is_empty() && source_seekable => {
+                SharedSource(shared.clone()).seek(SeekFrom::Start(0))?;
+                probe(&Hint::new()).map_err(|e| DecoderError::Decode(e.to_string()))?
+            }
+            Err(e) => return Err(DecoderError::Decode(e.to_string())),
+        };
 
         let format = probed.format;
 
-        // 查找第一个音频轨道
-        let track = format
-            .tracks()
-            .iter()
-            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-            .ok_or(DecoderError::NoTrack)?;
+        let track = select_audio_track(format.tracks(), selection).ok_or(DecoderError::NoTrack)?;
 
         let track_id = track.id;
         let codec_params = &track.codec_params;
@@ -89,17 +347,43 @@ impl AudioDecoder {
         let sample_rate = codec_params.sample_rate.unwrap_or(44100);
         let channels = codec_params.channels.map(|c| c.count()).unwrap_or(2);
 
+        // 损坏/恶意文件可能报告 0 或离谱的参数，会导致输出配置与缓冲区计算出错
+        if !CHANNELS_RANGE.contains(&channels) {
+            return Err(DecoderError::InvalidChannels(channels));
+        }
+        if !SAMPLE_RATE_RANGE.contains(&sample_rate) {
+            return Err(DecoderError::InvalidSampleRate(sample_rate));
+        }
+
         let duration = codec_params
             .n_frames
             .map(|frames| Duration::from_secs_f64(frames as f64 / sample_rate as f64));
 
         let codec = format!("{:?}", codec_params.codec);
+        let bit_rate = source_len
+            .zip(duration)
+            .and_then(|(len, d)| estimate_bit_rate(len, d));
+
+        // symphonia 按内容探测格式（提示仅作参考），因此提示错误时仍能解码，这里只报告不一致
+        let format_mismatch = ext_hint
+            .filter(|ext| codec_matches_hint(ext, codec_params.codec) == Some(false))
+            .map(|ext| {
+                format!(
+                    "format hint '{}' does not match detected codec {}",
+                    ext, codec
+                )
+            });
 
         let info = AudioInfo {
             sample_rate,
             channels,
+            seekable: source_seekable && duration.is_some(),
             duration,
             codec,
+            track_id: Some(track_id),
+            bits_per_sample: codec_params.bits_per_sample,
+            bit_rate,
+            format_mismatch,
         };
 
         // 创建解码器
@@ -108,17 +392,86 @@ impl AudioDecoder {
             .map_err(|_| DecoderError::UnsupportedCodec)?;
 
         let spec = SignalSpec::new(sample_rate, codec_params.channels.unwrap_or_default());
+        let time_base = codec_params.time_base;
 
         Ok(Self {
-            format,
-            decoder,
-            track_id,
+            backend: Backend::Symphonia {
+                format,
+                decoder,
+                track_id,
+                time_base,
+                sample_buf: None,
+            },
             spec,
-            sample_buf: None,
             info,
+            trim: None,
+            last_timestamp: None,
         })
     }
 
+    fn from_wav(wav: WavDecoder<Box<dyn MediaSource>>) -> Self {
+        let channels = wav.channels();
+        let spec = SignalSpec::new(
+            wav.sample_rate(),
+            Channels::from_bits_truncate((1u32 << channels) - 1),
+        );
+        let info = AudioInfo {
+            sample_rate: wav.sample_rate(),
+            channels,
+            duration: Some(wav.duration()),
+            seekable: true,
+            codec: format!("{:?}", wav.codec()),
+            track_id: None,
+            bits_per_sample: Some(wav.bits_per_sample()),
+            bit_rate: u32::try_from(
+                wav.sample_rate() as u64 * channels as u64 * wav.bits_per_sample() as u64,
+            )
+            .ok(),
+            format_mismatch: None,
+        };
+        Self {
+            backend: Backend::Wav(wav),
+            spec,
+            info,
+            trim: None,
+            last_timestamp: None,
+        }
+    }
+
+    /// 按编码器 delay/padding 裁剪输出，并修正 `info.duration`
+    ///
+    /// 仅对 symphonia 后端生效（内置 WAV 解码器没有 priming 采样）。
+    /// 有效帧数未记录时由容器报告的总帧数推算，两者都未知则只跳过开头。
+    pub fn set_gapless(&mut self, gapless: &furry_format::GaplessInfo) {
+        let Backend::Symphonia {
+            format, track_id, ..
+        } = &self.backend
+        else {
+            return;
+        };
+        if gapless.is_empty() {
+            return;
+        }
+        let total_frames = format
+            .tracks()
+            .iter()
+            .find(|t| t.id == *track_id)
+            .and_then(|t| t.codec_params.n_frames);
+        let start = gapless.delay as u64;
+        let valid_frames = gapless
+            .valid_frames
+            .or_else(|| total_frames.and_then(|n| n.checked_sub(start + gapless.padding as u64)));
+        self.trim = Some(TrimRange {
+            start,
+            end: valid_frames.map(|n| start + n),
+        });
+        if let Some(frames) = valid_frames {
+            self.info.duration = Some(Duration::from_secs_f64(
+                frames as f64 / self.info.sample_rate as f64,
+            ));
+        }
+    }
+
     /// 获取信号规格
     pub fn spec(&self) -> SignalSpec {
         self.spec
@@ -126,8 +479,39 @@ impl AudioDecoder {
 
     /// 解码下一帧，返回 f32 采样数据
     pub fn decode_next(&mut self) -> Result<Option<Vec<f32>>, DecoderError> {
+        let trim = self.trim;
+        let sample_rate = self.info.sample_rate as f64;
+        let (format, decoder, track_id, time_base, sample_buf) = match &mut self.backend {
+            Backend::Wav(wav) => {
+                let frame = wav.position_frames();
+                let block = wav.decode_next()?;
+                if block.is_some() {
+                    self.last_timestamp = Some(Duration::from_secs_f64(frame as f64 / sample_rate));
+                }
+                return Ok(block);
+            }
+            Backend::Symphonia {
+                format,
+                decoder,
+                track_id,
+                time_base,
+                sample_buf,
+            } => (format, decoder, *track_id, *time_base, sample_buf),
+        };
+        // 包时间戳 → 裁剪后时间轴上的位置
+        let trim_offset = trim.map_or(0.0, |t| t.start as f64 / sample_rate);
+        let timestamp = |ts: u64| {
+            let secs = match time_base {
+                Some(tb) => {
+                    let time = tb.calc_time(ts);
+                    time.seconds as f64 + time.frac
+                }
+                None => ts as f64 / sample_rate,
+            };
+            Duration::from_secs_f64((secs - trim_offset).max(0.0))
+        };
         loop {
-            let packet = match self.format.next_packet() {
+            let packet = match format.next_packet() {
                 Ok(p) => p,
                 Err(SymphoniaError::IoError(e))
                     if e.kind() == std::io::ErrorKind::UnexpectedEof =>
@@ -138,11 +522,19 @@ impl AudioDecoder {
             };
 
             // 跳过非目标轨道
-            if packet.track_id() != self.track_id {
+            if packet.track_id() != track_id {
                 continue;
             }
 
-            let decoded = match self.decoder.decode(&packet) {
+            // 已越过有效结尾（padding）
+            if trim
+                .and_then(|t| t.end)
+                .is_some_and(|end| packet.ts() >= end)
+            {
+                return Ok(None);
+            }
+
+            let decoded = match decoder.decode(&packet) {
                 Ok(d) => d,
                 Err(SymphoniaError::DecodeError(_)) => {
                     // 解码错误，尝试下一个包
@@ -155,33 +547,150 @@ impl AudioDecoder {
             let spec = *decoded.spec();
             let duration = decoded.capacity() as u64;
 
-            if self.sample_buf.is_none()
-                || self.sample_buf.as_ref().unwrap().capacity() < duration as usize
-            {
-                self.sample_buf = Some(SampleBuffer::new(duration, spec));
+            if sample_buf.is_none() || sample_buf.as_ref().unwrap().capacity() < duration as usize {
+                *sample_buf = Some(SampleBuffer::new(duration, spec));
             }
 
-            let sample_buf = self.sample_buf.as_mut().unwrap();
+            let frames = decoded.frames() as u64;
+            let sample_buf = sample_buf.as_mut().unwrap();
             sample_buf.copy_interleaved_ref(decoded);
 
-            return Ok(Some(sample_buf.samples().to_vec()));
+            let Some(trim) = trim else {
+                self.last_timestamp = Some(timestamp(packet.ts()));
+                return Ok(Some(sample_buf.samples().to_vec()));
+            };
+            // 包内帧区间 [ts, ts + frames) 与 [start, end) 求交
+            let ts = packet.ts();
+            let lo = trim.start.clamp(ts, ts + frames);
+            let hi = trim
+                .end
+                .map_or(ts + frames, |end| end.clamp(lo, ts + frames));
+            if lo == hi {
+                continue;
+            }
+            let channels = spec.channels.count();
+            let range = (lo - ts) as usize * channels..(hi - ts) as usize * channels;
+            self.last_timestamp = Some(timestamp(lo));
+            return Ok(Some(sample_buf.samples()[range].to_vec()));
         }
     }
 
+    /// 最近一次 [`Self::decode_next`] 输出的第一帧的播放位置
+    ///
+    /// 由包时间戳与轨道时间基换算，已扣除无缝播放裁掉的 delay；
+    /// 尚未解码或刚 seek 过时为 `None`。
+    pub fn current_timestamp(&self) -> Option<Duration> {
+        self.last_timestamp
+    }
+
     /// 跳转到指定时间
     pub fn seek(&mut self, time: Duration) -> Result<(), DecoderError> {
+        self.seek_before(time).map(|_| ())
+    }
+
+    /// 跳转到 `time` 或其之前最近的可解码位置
+    ///
+    /// 有损格式只能落在包边界上；返回值为之后 [`Self::decode_next`] 输出中
+    /// 位于 `time` 之前、需要丢弃的帧数。需要采样级精度时使用。
+    pub fn seek_before(&mut self, time: Duration) -> Result<u64, DecoderError> {
+        self.last_timestamp = None;
+        let trim_start = self.trim.map_or(0, |t| t.start);
+        let offset = trim_start as f64 / self.info.sample_rate as f64;
+        let (format, decoder, track_id) = match &mut self.backend {
+            Backend::Wav(wav) => return wav.seek(time).map(|_| 0),
+            Backend::Symphonia {
+                format,
+                decoder,
+                track_id,
+                ..
+            } => (format, decoder, *track_id),
+        };
+
+        // 裁剪后的时间轴比容器时间轴晚 `delay` 帧
         let seek_to = SeekTo::Time {
-            time: symphonia::core::units::Time::from(time.as_secs_f64()),
-            track_id: Some(self.track_id),
+            time: symphonia::core::units::Time::from(time.as_secs_f64() + offset),
+            track_id: Some(track_id),
         };
 
-        self.format
+        let seeked = format
             .seek(SeekMode::Accurate, seek_to)
             .map_err(|e| DecoderError::Decode(e.to_string()))?;
 
         // 重置解码器状态
-        self.decoder.reset();
+        decoder.reset();
+
+        // decode_next 会丢掉 trim.start 之前的帧
+        let first_out = seeked.actual_ts.max(trim_start);
+        Ok(seeked.required_ts.saturating_sub(first_out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use symphonia::core::codecs::{CodecParameters, CODEC_TYPE_PCM_S16LE};
+
+    fn audio_track(id: u32, codec: CodecType, channels: Channels, sample_rate: u32) -> Track {
+        let mut params = CodecParameters::new();
+        params
+            .for_codec(codec)
+            .with_channels(channels)
+            .with_sample_rate(sample_rate);
+        Track::new(id, params)
+    }
+
+    #[test]
+    fn test_select_best_audio_track() {
+        let stereo = Channels::FRONT_LEFT | Channels::FRONT_RIGHT;
+        let tracks = vec![
+            // 视频轨（未注册的 codec）
+            audio_track(
+                1,
+                symphonia::core::codecs::decl_codec_type(b"h264"),
+                stereo,
+                48_000,
+            ),
+            audio_track(2, CODEC_TYPE_MP3, Channels::FRONT_CENTRE, 22_050),
+            audio_track(3, CODEC_TYPE_PCM_S16LE, stereo, 44_100),
+            audio_track(4, CODEC_TYPE_FLAC, stereo, 44_100),
+        ];
+
+        let best = select_audio_track(&tracks, TrackSelection::Best).unwrap();
+        assert_eq!(best.id, 3);
+        let chosen = select_audio_track(&tracks, TrackSelection::Id(2)).unwrap();
+        assert_eq!(chosen.id, 2);
+        assert!(select_audio_track(&tracks, TrackSelection::Id(1)).is_none());
+    }
+
+    #[test]
+    fn test_zero_sample_rate_rejected() {
+        // 头部声称 0 Hz 的 16-bit 立体声 WAV
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36u32 + 8).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&0u32.to_le_bytes());
+        wav.extend_from_slice(&0u32.to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&8u32.to_le_bytes());
+        wav.extend_from_slice(&[0u8; 8]);
+
+        for hint in [DecodeHint::from_extension("wav"), DecodeHint::default()] {
+            let result = AudioDecoder::new(std::io::Cursor::new(wav.clone()), &hint);
+            assert!(matches!(result, Err(DecoderError::InvalidSampleRate(0))));
+        }
 
-        Ok(())
+        // 输出层同样不因 0 Hz 除以零
+        let path = std::env::temp_dir().join(format!("furry_zero_rate_{}.wav", std::process::id()));
+        let mut sink = crate::FileSink::create(&path, 0, 2).unwrap();
+        sink.write(&[0.0; 4]).unwrap();
+        assert_eq!(sink.position(), 0.0);
+        drop(sink);
+        std::fs::remove_file(&path).ok();
     }
 }
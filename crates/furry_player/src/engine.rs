@@ -1,6 +1,7 @@
 //! 播放引擎
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
@@ -8,16 +9,56 @@ use crossbeam_channel::{bounded, Receiver, Sender};
 use furry_crypto::MasterKey;
 
 use crate::{
-    AudioDecoder, AudioOutput, OutputConfig, PlaybackState, PlayerCommand, PlayerEvent, TrackInfo,
-    VirtualAudioStream,
+    AudioDecoder, AudioInfo, AudioOutput, DecodeHint, DecoderError, FileSink, GainRamp,
+    OutputConfig, OutputError, PlaybackState, PlayerCommand, PlayerController, PlayerEvent,
+    StreamError, StreamOptions, TrackInfo, VirtualAudioStream, BUFFER_SIZE_RANGE,
+    DEFAULT_BUFFER_SIZE,
 };
 
+/// 曲目加载错误
+#[derive(thiserror::Error, Debug)]
+pub enum LoadError {
+    #[error("Failed to open file: {0}")]
+    Stream(#[from] StreamError),
+
+    #[error("Failed to open file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to decode: {0}")]
+    Decoder(#[from] DecoderError),
+
+    #[error("Audio output error: {0}")]
+    Output(#[from] OutputError),
+
+    #[error("no audio device; use render mode")]
+    NoDevice,
+
+    #[error("Player engine exited unexpectedly")]
+    EngineGone,
+}
+
+/// 默认进度事件间隔
+pub const DEFAULT_POSITION_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+/// 进度事件最小间隔（约 60fps），避免事件洪泛
+pub const MIN_POSITION_UPDATE_INTERVAL: Duration = Duration::from_millis(16);
+/// `BufferLevel` 事件的最小变化量
+pub const BUFFER_LEVEL_STEP: f32 = 0.05;
+/// 距结尾不足该值的 seek 视为跳到结尾
+const SEEK_END_TOLERANCE: Duration = Duration::from_millis(50);
+
 /// 播放引擎句柄
 pub struct PlayerHandle {
     pub cmd_tx: Sender<PlayerCommand>,
     pub evt_rx: Receiver<PlayerEvent>,
 }
 
+impl PlayerHandle {
+    /// 获取可共享的控制器
+    pub fn controller(&self) -> PlayerController {
+        PlayerController::new(self.cmd_tx.clone())
+    }
+}
+
 /// 启动播放引擎
 pub fn spawn_player(master_key: MasterKey) -> PlayerHandle {
     let (cmd_tx, cmd_rx) = bounded(32);
@@ -30,6 +71,38 @@ pub fn spawn_player(master_key: MasterKey) -> PlayerHandle {
     PlayerHandle { cmd_tx, evt_rx }
 }
 
+/// 打开文件并启动播放引擎（便捷接口）
+///
+/// 同步完成加载：文件无法打开或解码时直接返回错误。
+/// 成功时引擎处于暂停状态，`TrackInfo`/`Duration` 事件已发出，发送 `Play` 即可播放。
+pub fn play_file(path: &Path, master_key: MasterKey) -> Result<PlayerHandle, LoadError> {
+    let (cmd_tx, cmd_rx) = bounded(32);
+    let (evt_tx, evt_rx) = bounded(64);
+    let (ready_tx, ready_rx) = bounded(1);
+    let path = path.to_path_buf();
+
+    thread::spawn(move || {
+        let mut state = EngineState::new(master_key, evt_tx);
+        let _ = state
+            .evt_tx
+            .send(PlayerEvent::StateChanged(PlaybackState::Idle));
+
+        // 音频输出流无法跨线程移动，因此在引擎线程内加载并回报结果
+        match state.try_load_track(path, LoadKind::Furry) {
+            Ok(()) => {
+                let _ = ready_tx.send(Ok(()));
+                run_loop(&cmd_rx, &mut state);
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+            }
+        }
+    });
+
+    ready_rx.recv().map_err(|_| LoadError::EngineGone)??;
+    Ok(PlayerHandle { cmd_tx, evt_rx })
+}
+
 fn run_engine(cmd_rx: Receiver<PlayerCommand>, evt_tx: Sender<PlayerEvent>, master_key: MasterKey) {
     let mut state = EngineState::new(master_key, evt_tx);
 
@@ -37,6 +110,10 @@ fn run_engine(cmd_rx: Receiver<PlayerCommand>, evt_tx: Sender<PlayerEvent>, mast
         .evt_tx
         .send(PlayerEvent::StateChanged(PlaybackState::Idle));
 
+    run_loop(&cmd_rx, &mut state);
+}
+
+fn run_loop(cmd_rx: &Receiver<PlayerCommand>, state: &mut EngineState) {
     loop {
         // 非阻塞检查命令
         match cmd_rx.try_recv() {
@@ -65,14 +142,106 @@ struct EngineState {
     evt_tx: Sender<PlayerEvent>,
     playback_state: PlaybackState,
     current_track: Option<LoadedTrack>,
-    volume: f32,
+    /// 与输出共享的音量增益，由声卡回调/渲染文件在消费采样时作用
+    gain: Arc<GainRamp>,
     position_base: Duration,
     last_position_update: std::time::Instant,
+    position_update_interval: Duration,
+    render_target: Option<PathBuf>,
+    buffer_size: usize,
+    /// 加载期间收到 `Play`，加载完成后自动播放
+    pending_play: bool,
+    /// 播放队列
+    queue: Vec<PathBuf>,
+    /// 当前曲目在队列中的序号；直接 `Load` 的曲目不在队列中，为 `None`
+    queue_index: Option<usize>,
+    /// 上次发送的缓冲区填充比例，用于节流 `BufferLevel`
+    last_buffer_level: Option<f32>,
+}
+
+/// 加载方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoadKind {
+    /// 整个 .furry 文件
+    Furry,
+    /// 普通音频文件
+    Raw,
+    /// 多曲目专辑中的一首
+    AlbumTrack(u32),
 }
 
 struct LoadedTrack {
     decoder: AudioDecoder,
-    output: AudioOutput,
+    output: TrackOutput,
+    info: TrackInfo,
+    /// 重新打开曲目的方式；内存中装入的曲目为 `None`
+    source: Option<(PathBuf, LoadKind)>,
+    /// 解码器时间 0 对应的曲目位置；按字节比例 seek 后为估算值
+    origin: Duration,
+}
+
+/// 曲目输出：声卡或渲染文件
+enum TrackOutput {
+    Device(AudioOutput),
+    File(FileSink),
+}
+
+impl TrackOutput {
+    fn set_playing(&self, playing: bool) {
+        if let Self::Device(output) = self {
+            output.set_playing(playing);
+        }
+    }
+
+    fn write(&mut self, samples: Vec<f32>) -> bool {
+        match self {
+            Self::Device(output) => output.write(samples),
+            Self::File(sink) => sink.write(&samples).is_ok(),
+        }
+    }
+
+    fn position(&self) -> f64 {
+        match self {
+            Self::Device(output) => output.position(),
+            Self::File(sink) => sink.position(),
+        }
+    }
+
+    fn occupancy(&self) -> f32 {
+        match self {
+            Self::Device(output) => output.occupancy(),
+            // 同步写入文件，不存在欠载
+            Self::File(_) => 1.0,
+        }
+    }
+
+    fn position_frames(&self) -> u64 {
+        match self {
+            Self::Device(output) => output.position_frames(),
+            Self::File(sink) => sink.position_frames(),
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Device(output) => output.sample_rate(),
+            Self::File(sink) => sink.sample_rate(),
+        }
+    }
+
+    fn reset_position(&mut self) {
+        match self {
+            Self::Device(output) => output.reset_position(),
+            Self::File(sink) => sink.reset_position(),
+        }
+    }
+
+    /// 曲目结束时调用：渲染文件回填 WAV 头
+    fn finish(&mut self) {
+        if let Self::File(sink) = self {
+            let _ = sink.finalize();
+        }
+    }
 }
 
 impl EngineState {
@@ -82,16 +251,45 @@ impl EngineState {
             evt_tx,
             playback_state: PlaybackState::Idle,
             current_track: None,
-            volume: 1.0,
+            gain: Arc::new(GainRamp::new(1.0)),
             position_base: Duration::ZERO,
             last_position_update: std::time::Instant::now(),
+            position_update_interval: DEFAULT_POSITION_UPDATE_INTERVAL,
+            render_target: None,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            pending_play: false,
+            queue: Vec::new(),
+            queue_index: None,
+            last_buffer_level: None,
         }
     }
 
     fn handle_command(&mut self, cmd: PlayerCommand) -> bool {
         match cmd {
             PlayerCommand::Load(path) => {
-                self.load_track(path);
+                self.queue_index = None;
+                self.load_track(path, LoadKind::Furry);
+            }
+            PlayerCommand::LoadRaw(path) => {
+                self.queue_index = None;
+                self.load_track(path, LoadKind::Raw);
+            }
+            PlayerCommand::LoadTrack { path, track } => {
+                self.queue_index = None;
+                self.load_track(path, LoadKind::AlbumTrack(track));
+            }
+            PlayerCommand::SetQueue(paths) => {
+                self.queue = paths;
+                self.queue_index = self.queue_index.filter(|&i| i < self.queue.len());
+            }
+            PlayerCommand::PlayIndex(index) => {
+                self.play_index(index);
+            }
+            PlayerCommand::Next => {
+                self.advance(true);
+            }
+            PlayerCommand::Previous => {
+                self.advance(false);
             }
             PlayerCommand::Play => {
                 self.play();
@@ -105,8 +303,31 @@ impl EngineState {
             PlayerCommand::Seek(pos) => {
                 self.seek(pos);
             }
+            PlayerCommand::SeekFraction(fraction) => {
+                self.seek_fraction(fraction);
+            }
             PlayerCommand::SetVolume(vol) => {
-                self.volume = vol.clamp(0.0, 1.0);
+                self.gain.set_target(vol.clamp(0.0, 1.0));
+                self.send_volume();
+            }
+            PlayerCommand::QueryVolume => {
+                self.send_volume();
+            }
+            PlayerCommand::QueryState => {
+                self.send_snapshot();
+            }
+            PlayerCommand::SetPositionUpdateInterval(interval) => {
+                self.position_update_interval = interval.max(MIN_POSITION_UPDATE_INTERVAL);
+            }
+            PlayerCommand::SetBufferSize(frames) => {
+                self.buffer_size =
+                    frames.clamp(*BUFFER_SIZE_RANGE.start(), *BUFFER_SIZE_RANGE.end());
+            }
+            PlayerCommand::SetRenderTarget(path) => {
+                self.render_target = path;
+            }
+            PlayerCommand::Reset => {
+                self.reset();
             }
             PlayerCommand::Shutdown => {
                 return false;
@@ -115,7 +336,23 @@ impl EngineState {
         true
     }
 
-    fn load_track(&mut self, path: PathBuf) {
+    fn load_track(&mut self, path: PathBuf, kind: LoadKind) {
+        match self.try_load_track(path, kind) {
+            Ok(()) => {
+                let _ = self.evt_tx.send(PlayerEvent::Loaded);
+                if std::mem::take(&mut self.pending_play) {
+                    self.play();
+                }
+            }
+            Err(e) => {
+                self.pending_play = false;
+                let _ = self.evt_tx.send(PlayerEvent::Error(e.to_string()));
+                self.set_state(PlaybackState::Idle);
+            }
+        }
+    }
+
+    fn try_load_track(&mut self, path: PathBuf, kind: LoadKind) -> Result<(), LoadError> {
         self.set_state(PlaybackState::Loading);
         self.position_base = Duration::ZERO;
 
@@ -124,78 +361,128 @@ impl EngineState {
             track.output.set_playing(false);
         }
 
-        // 尝试打开 .furry 文件
-        let stream = match VirtualAudioStream::open(&path, &self.master_key) {
-            Ok(s) => s,
-            Err(e) => {
-                let _ = self
-                    .evt_tx
-                    .send(PlayerEvent::Error(format!("Failed to open file: {}", e)));
-                self.set_state(PlaybackState::Idle);
-                return;
-            }
-        };
-
-        // 获取原始格式作为解码提示
-        let format_hint = match stream.original_format() {
-            furry_format::OriginalFormat::Mp3 => Some("mp3"),
-            furry_format::OriginalFormat::Ogg => Some("ogg"),
-            furry_format::OriginalFormat::Flac => Some("flac"),
-            furry_format::OriginalFormat::Wav => Some("wav"),
-            _ => None,
+        let decoder = match kind {
+            LoadKind::Furry => self.open_furry_decoder(&path, None)?,
+            LoadKind::Raw => Self::open_raw_decoder(&path)?,
+            LoadKind::AlbumTrack(track) => self.open_furry_decoder(&path, Some(track))?,
         };
 
-        // 创建解码器
-        let decoder = match AudioDecoder::new(stream, format_hint) {
-            Ok(d) => d,
-            Err(e) => {
-                let _ = self
-                    .evt_tx
-                    .send(PlayerEvent::Error(format!("Failed to decode: {}", e)));
-                self.set_state(PlaybackState::Idle);
-                return;
+        let output = self.open_output(&decoder.info)?;
+        self.install_track(path.clone(), decoder, output, Some(kind));
+        if kind == LoadKind::Furry {
+            if let Some(table) = self.read_track_table(&path) {
+                let _ = self.evt_tx.send(PlayerEvent::TrackTable {
+                    path,
+                    tracks: table.tracks,
+                });
             }
-        };
-
-        let info = &decoder.info;
-        let duration = info.duration.unwrap_or(Duration::ZERO);
+        }
+        Ok(())
+    }
 
-        // 创建音频输出
+    /// 创建音频输出；无声卡（CI/服务器）时回退到渲染目标
+    fn open_output(&self, info: &AudioInfo) -> Result<TrackOutput, LoadError> {
         let output_config = OutputConfig {
             sample_rate: info.sample_rate,
             channels: info.channels as u16,
-            buffer_size: 8192,
+            buffer_size: self.buffer_size,
+            gain: self.gain.clone(),
         };
 
-        let output = match AudioOutput::new(output_config) {
-            Ok(o) => o,
-            Err(e) => {
-                let _ = self
-                    .evt_tx
-                    .send(PlayerEvent::Error(format!("Audio output error: {}", e)));
-                self.set_state(PlaybackState::Idle);
-                return;
-            }
+        match AudioOutput::new(output_config) {
+            Ok(output) => Ok(TrackOutput::Device(output)),
+            Err(OutputError::NoDevice) => match &self.render_target {
+                Some(target) => Ok(TrackOutput::File(
+                    FileSink::create(target, info.sample_rate, info.channels as u16)?
+                        .with_gain(self.gain.clone()),
+                )),
+                None => Err(LoadError::NoDevice),
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 装入已打开的解码器与输出，发送曲目信息并进入暂停状态
+    ///
+    /// 与打开方式无关，测试可直接传入内存中的解码器与渲染输出（`kind` 为 `None`）。
+    fn install_track(
+        &mut self,
+        path: PathBuf,
+        decoder: AudioDecoder,
+        output: TrackOutput,
+        kind: Option<LoadKind>,
+    ) {
+        let album_track = match kind {
+            Some(LoadKind::AlbumTrack(track)) => Some(track),
+            _ => None,
         };
+        let source = kind.map(|kind| (path.clone(), kind));
+        let info = &decoder.info;
+        let duration = info.duration.unwrap_or(Duration::ZERO);
+        self.last_buffer_level = None;
 
         // 发送曲目信息
         let track_info = TrackInfo {
-            path: path.clone(),
+            path,
             format: info.codec.clone(),
             sample_rate: info.sample_rate,
             channels: info.channels as u16,
             duration,
+            bits_per_sample: info.bits_per_sample,
+            bit_rate: info.bit_rate,
+            seekable: info.seekable,
+            album_track,
+            format_warning: info.format_mismatch.clone(),
         };
 
-        let _ = self.evt_tx.send(PlayerEvent::TrackInfo(track_info));
+        let _ = self.evt_tx.send(PlayerEvent::TrackInfo(track_info.clone()));
         let _ = self.evt_tx.send(PlayerEvent::Duration(duration));
 
-        self.current_track = Some(LoadedTrack { decoder, output });
+        self.current_track = Some(LoadedTrack {
+            decoder,
+            output,
+            info: track_info,
+            source,
+            origin: Duration::ZERO,
+        });
 
         self.set_state(PlaybackState::Paused);
     }
 
+    fn open_furry_decoder(
+        &self,
+        path: &Path,
+        track: Option<u32>,
+    ) -> Result<AudioDecoder, LoadError> {
+        crate::pcm_reader::open_furry_decoder(
+            path,
+            &self.master_key,
+            &StreamOptions {
+                prefetch: true,
+                track,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// 读取多曲目专辑的曲目表；普通文件或读取失败时为 `None`
+    fn read_track_table(&self, path: &Path) -> Option<furry_format::TrackTable> {
+        let file = std::fs::File::open(path).ok()?;
+        let mut reader = furry_format::FurryReader::open(file, &self.master_key).ok()?;
+        reader.read_track_table().ok().flatten()
+    }
+
+    fn open_raw_decoder(path: &Path) -> Result<AudioDecoder, LoadError> {
+        let file = std::fs::File::open(path)?;
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        Ok(AudioDecoder::new(file, &DecodeHint::from_extension(ext))?)
+    }
+
     fn play(&mut self) {
+        if self.playback_state == PlaybackState::Loading {
+            self.pending_play = true;
+            return;
+        }
         if let Some(track) = &self.current_track {
             if self.playback_state != PlaybackState::Playing {
                 track.output.set_playing(true);
@@ -205,6 +492,7 @@ impl EngineState {
     }
 
     fn pause(&mut self) {
+        self.pending_play = false;
         if let Some(track) = &self.current_track {
             if self.playback_state == PlaybackState::Playing {
                 track.output.set_playing(false);
@@ -214,6 +502,7 @@ impl EngineState {
     }
 
     fn stop(&mut self) {
+        self.pending_play = false;
         if let Some(track) = self.current_track.take() {
             track.output.set_playing(false);
         }
@@ -221,38 +510,247 @@ impl EngineState {
         self.set_state(PlaybackState::Stopped);
     }
 
+    fn reset(&mut self) {
+        // 丢弃输出即清空其环形缓冲区；渲染文件先回填 WAV 头
+        if let Some(mut track) = self.current_track.take() {
+            track.output.set_playing(false);
+            track.output.finish();
+        }
+        *self = Self::new(self.master_key.clone(), self.evt_tx.clone());
+        let _ = self
+            .evt_tx
+            .send(PlayerEvent::StateChanged(PlaybackState::Idle));
+        self.send_volume();
+    }
+
+    fn send_snapshot(&self) {
+        let track = self.current_track.as_ref();
+        let _ = self.evt_tx.send(PlayerEvent::StateSnapshot {
+            state: self.playback_state,
+            track: track.map(|t| t.info.clone()),
+            position: track.map_or(Duration::ZERO, |t| {
+                self.position_base + Duration::from_secs_f64(t.output.position())
+            }),
+            duration: track.map_or(Duration::ZERO, |t| t.info.duration),
+            volume: self.gain.target(),
+        });
+    }
+
+    fn send_volume(&self) {
+        let _ = self
+            .evt_tx
+            .send(PlayerEvent::VolumeChanged(self.gain.target()));
+    }
+
     fn seek(&mut self, pos: Duration) {
-        if let Some(track) = &mut self.current_track {
-            if let Err(e) = track.decoder.seek(pos) {
+        // 早于按字节比例 seek 的落点：从头重新打开
+        if self.current_track.as_ref().is_some_and(|t| pos < t.origin) {
+            if let Err(e) = self.reopen_at_fraction(0.0) {
                 let _ = self
                     .evt_tx
                     .send(PlayerEvent::Error(format!("Seek error: {}", e)));
-            } else {
+                return;
+            }
+        }
+        let Some(track) = &mut self.current_track else {
+            return;
+        };
+        // 时长为 0 视为未知，不做钳制
+        let duration = track.decoder.info.duration.filter(|d| !d.is_zero());
+        if duration.is_some_and(|duration| pos + SEEK_END_TOLERANCE >= duration) {
+            // 跳到结尾：直接结束，避免解码器停在 EOF 而播放卡住
+            self.end_track();
+            return;
+        }
+
+        match track.decoder.seek(pos - track.origin) {
+            Ok(()) => {
                 track.output.reset_position();
                 self.position_base = pos;
                 let _ = self.evt_tx.send(PlayerEvent::Position(pos));
+                self.send_precise_position();
+            }
+            // 时长未知时越界 seek 只能靠解码器报错发现
+            Err(_) if duration.is_none() => self.end_track(),
+            Err(e) => {
+                let _ = self
+                    .evt_tx
+                    .send(PlayerEvent::Error(format!("Seek error: {}", e)));
             }
         }
     }
 
+    fn seek_fraction(&mut self, fraction: f64) {
+        let Some(track) = &self.current_track else {
+            return;
+        };
+        if fraction.is_nan() {
+            return;
+        }
+        let fraction = fraction.clamp(0.0, 1.0);
+        if !track.info.duration.is_zero() {
+            self.seek(track.info.duration.mul_f64(fraction));
+            return;
+        }
+        // 时长未知：按虚拟流的字节比例 seek
+        if !matches!(
+            track.source,
+            Some((_, LoadKind::Furry | LoadKind::AlbumTrack(_)))
+        ) {
+            let _ = self.evt_tx.send(PlayerEvent::Error(
+                "Seek error: track duration is unknown".to_string(),
+            ));
+            return;
+        }
+        if fraction >= 1.0 {
+            self.end_track();
+            return;
+        }
+        match self.reopen_at_fraction(fraction) {
+            Ok(()) => {
+                let _ = self.evt_tx.send(PlayerEvent::Position(self.position_base));
+                self.send_precise_position();
+            }
+            Err(e) => {
+                let _ = self
+                    .evt_tx
+                    .send(PlayerEvent::Error(format!("Seek error: {}", e)));
+            }
+        }
+    }
+
+    /// 从虚拟流的 `fraction` 处重新打开解码器，适用于时长未知的 CBR 数据（如无 Xing 头的 MP3）
+    ///
+    /// 落点的曲目位置按新解码器估算的剩余时长换算；无法估算时记为 0，之后的进度从这里起算。
+    /// 普通文件与内存曲目没有虚拟流，调用方应先排除。
+    fn reopen_at_fraction(&mut self, fraction: f64) -> Result<(), LoadError> {
+        let Some(track) = &mut self.current_track else {
+            return Ok(());
+        };
+        let (path, track_index) = match &track.source {
+            Some((path, LoadKind::AlbumTrack(index))) => (path.clone(), Some(*index)),
+            Some((path, _)) => (path.clone(), None),
+            None => return Ok(()),
+        };
+
+        let mut stream = VirtualAudioStream::open_with_options(
+            &path,
+            &self.master_key,
+            &StreamOptions {
+                prefetch: true,
+                track: track_index,
+                ..Default::default()
+            },
+        )?;
+        let offset = (stream.len() as f64 * fraction) as u64;
+        stream.skip_front(offset);
+        let hint = DecodeHint::from_extension(stream.format_hint());
+        let mut decoder = AudioDecoder::new(stream, &hint)?;
+        if decoder.info.sample_rate != track.decoder.info.sample_rate
+            || decoder.info.channels != track.decoder.info.channels
+        {
+            return Err(DecoderError::Decode(
+                "stream parameters changed at the seek target".to_string(),
+            )
+            .into());
+        }
+
+        // 剩余时长 / (1 - fraction) ≈ 全长
+        let origin = match decoder.info.duration {
+            Some(rest) if offset > 0 && fraction < 1.0 => rest.mul_f64(fraction / (1.0 - fraction)),
+            _ => Duration::ZERO,
+        };
+        // 新解码器的时长只是剩余部分，不能用于钳制之后的 seek
+        decoder.info.duration = None;
+        decoder.info.seekable = false;
+
+        track.decoder = decoder;
+        track.origin = origin;
+        track.output.reset_position();
+        self.position_base = origin;
+        Ok(())
+    }
+
+    /// 曲目播放结束；从队列播放时自动切到下一首
+    fn end_track(&mut self) {
+        self.pending_play = false;
+        if let Some(track) = &mut self.current_track {
+            track.output.set_playing(false);
+            track.output.finish();
+        }
+        self.set_state(PlaybackState::Stopped);
+        let _ = self.evt_tx.send(PlayerEvent::TrackEnded);
+        if self.queue_index.is_some() {
+            self.advance(true);
+        }
+    }
+
+    /// 加载并播放队列中的第 `index` 首
+    fn play_index(&mut self, index: usize) {
+        let Some(path) = self.queue.get(index).cloned() else {
+            return;
+        };
+        self.queue_index = Some(index);
+        let _ = self.evt_tx.send(PlayerEvent::TrackChanged {
+            index,
+            path: path.clone(),
+            queue_len: self.queue.len(),
+        });
+        self.pending_play = true;
+        self.load_track(path, LoadKind::Furry);
+    }
+
+    /// 队列中的下一首/上一首，两端循环；没有当前序号时从第一首开始
+    fn advance(&mut self, forward: bool) {
+        let len = self.queue.len();
+        if len == 0 {
+            return;
+        }
+        let index = match self.queue_index {
+            Some(i) if forward => (i + 1) % len,
+            Some(i) => (i + len - 1) % len,
+            None => 0,
+        };
+        self.play_index(index);
+    }
+
+    /// 按采样帧计算并发送精确进度
+    fn send_precise_position(&self) {
+        let Some(track) = &self.current_track else {
+            return;
+        };
+        let sample_rate = track.output.sample_rate();
+        if sample_rate == 0 {
+            return;
+        }
+        let base_frames = (self.position_base.as_secs_f64() * sample_rate as f64).round() as u64;
+        let frame = base_frames + track.output.position_frames();
+        let time = Duration::from_nanos(
+            (frame as u128 * 1_000_000_000 / sample_rate as u128).min(u64::MAX as u128) as u64,
+        );
+        let _ = self.evt_tx.send(PlayerEvent::PositionPrecise {
+            time,
+            frame,
+            sample_rate,
+        });
+    }
+
     fn decode_and_play(&mut self) {
         if let Some(track) = &mut self.current_track {
             // 解码并发送到输出
             match track.decoder.decode_next() {
                 Ok(Some(samples)) => {
-                    // 应用音量
-                    let mut samples = samples;
-                    for sample in &mut samples {
-                        *sample *= self.volume;
-                    }
-
+                    // 音量由输出端在消费采样时作用
                     track.output.write(samples);
                 }
-                Ok(None) => {
-                    // 播放结束
-                    track.output.set_playing(false);
-                    self.set_state(PlaybackState::Stopped);
-                    let _ = self.evt_tx.send(PlayerEvent::TrackEnded);
+                Ok(None) => self.end_track(),
+                // 文件损坏：继续解码只会反复失败，明确告知后停止
+                Err(DecoderError::ChunkAuthFailed(index)) => {
+                    let _ = self.evt_tx.send(PlayerEvent::Error(format!(
+                        "audio chunk {} failed authentication",
+                        index
+                    )));
+                    self.stop();
                 }
                 Err(e) => {
                     let _ = self
@@ -264,17 +762,35 @@ impl EngineState {
     }
 
     fn update_position(&mut self) {
-        // 每 100ms 更新一次位置
-        if self.last_position_update.elapsed() >= Duration::from_millis(100) {
+        // 按配置的间隔更新位置
+        if self.last_position_update.elapsed() >= self.position_update_interval {
             if let Some(track) = &self.current_track {
                 let pos = track.output.position();
                 let pos = self.position_base + Duration::from_secs_f64(pos);
                 let _ = self.evt_tx.send(PlayerEvent::Position(pos));
+                self.send_precise_position();
             }
+            self.send_buffer_level();
             self.last_position_update = std::time::Instant::now();
         }
     }
 
+    /// 缓冲区填充比例变化超过 [`BUFFER_LEVEL_STEP`]（或首次）时发送 `BufferLevel`
+    fn send_buffer_level(&mut self) {
+        let Some(track) = &self.current_track else {
+            return;
+        };
+        let level = track.output.occupancy();
+        if self
+            .last_buffer_level
+            .is_some_and(|last| (level - last).abs() < BUFFER_LEVEL_STEP)
+        {
+            return;
+        }
+        self.last_buffer_level = Some(level);
+        let _ = self.evt_tx.send(PlayerEvent::BufferLevel(level));
+    }
+
     fn set_state(&mut self, state: PlaybackState) {
         if self.playback_state != state {
             self.playback_state = state;
@@ -282,3 +798,305 @@ impl EngineState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    use furry_format::{FurryWriter, OriginalFormat};
+
+    /// 内存中的 .furry：8 kHz 立体声 16-bit WAV，左声道为帧序号，右声道为 0
+    fn furry_wav_in_memory(frames: u32) -> Vec<u8> {
+        let mut wav = b"RIFF".to_vec();
+        wav.extend_from_slice(&(36 + frames * 4).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&8_000u32.to_le_bytes());
+        wav.extend_from_slice(&32_000u32.to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(frames * 4).to_le_bytes());
+        for frame in 0..frames {
+            wav.extend_from_slice(&(frame as i16).to_le_bytes());
+            wav.extend_from_slice(&0i16.to_le_bytes());
+        }
+
+        let mut writer = FurryWriter::create(
+            Cursor::new(Vec::new()),
+            &MasterKey::default_key(),
+            OriginalFormat::Wav,
+        )
+        .unwrap();
+        for (i, chunk) in wav.chunks(4096).enumerate() {
+            writer.write_audio_chunk(chunk, i as u64 * 4096).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// 从内存装入曲目并渲染到 WAV，播放结束后返回采样与期间的事件
+    fn play_in_memory(
+        name: &str,
+        furry: Vec<u8>,
+        before_play: &[PlayerCommand],
+    ) -> (Vec<f32>, Vec<PlayerEvent>) {
+        let (evt_tx, evt_rx) = crossbeam_channel::unbounded();
+        let mut state = EngineState::new(MasterKey::default_key(), evt_tx);
+        let stream = VirtualAudioStream::from_reader(
+            Cursor::new(furry),
+            &state.master_key,
+            &StreamOptions::default(),
+        )
+        .unwrap();
+        let decoder = crate::pcm_reader::stream_decoder(stream).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("furry_engine_{}_{}.wav", name, std::process::id()));
+        let output = TrackOutput::File(
+            FileSink::create(&path, 8_000, 2)
+                .unwrap()
+                .with_gain(state.gain.clone()),
+        );
+        state.install_track(PathBuf::from(name), decoder, output, None);
+
+        for cmd in before_play {
+            state.handle_command(cmd.clone());
+        }
+        state.handle_command(PlayerCommand::Play);
+        for _ in 0..10_000 {
+            if state.playback_state != PlaybackState::Playing {
+                break;
+            }
+            state.decode_and_play();
+        }
+        state.handle_command(PlayerCommand::Reset);
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let samples = bytes[44..]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        (samples, evt_rx.try_iter().collect())
+    }
+
+    #[test]
+    fn test_in_memory_track_renders_every_frame() {
+        let (samples, events) = play_in_memory("full", furry_wav_in_memory(6_000), &[]);
+        assert!(events.iter().any(|e| matches!(e, PlayerEvent::TrackEnded)));
+        assert_eq!(samples.len(), 6_000 * 2);
+        assert!(samples
+            .chunks_exact(2)
+            .enumerate()
+            .all(|(frame, s)| s[0] == frame as f32 / 32_768.0 && s[1] == 0.0));
+
+        // 先 seek 再播放：从 0.5s（第 4000 帧）开始
+        let (samples, _) = play_in_memory(
+            "seek",
+            furry_wav_in_memory(6_000),
+            &[PlayerCommand::Seek(Duration::from_millis(500))],
+        );
+        assert_eq!(samples.len(), 2_000 * 2);
+        assert_eq!(samples[0], 4_000.0 / 32_768.0);
+    }
+
+    #[test]
+    fn test_seek_fraction_maps_onto_duration() {
+        // 6000 帧 = 0.75s，0.5 落在 0.375s（第 3000 帧）
+        let (samples, _) = play_in_memory(
+            "fraction",
+            furry_wav_in_memory(6_000),
+            &[PlayerCommand::SeekFraction(0.5)],
+        );
+        assert_eq!(samples.len(), 3_000 * 2);
+        assert_eq!(samples[0], 3_000.0 / 32_768.0);
+
+        // 越界比例钳制到两端
+        let (samples, _) = play_in_memory(
+            "fraction_low",
+            furry_wav_in_memory(6_000),
+            &[PlayerCommand::SeekFraction(-1.0)],
+        );
+        assert_eq!(samples.len(), 6_000 * 2);
+        assert_eq!(samples[0], 0.0);
+
+        // 跳到结尾：曲目在开始播放之前就已结束
+        let (_, events) = play_in_memory(
+            "fraction_high",
+            furry_wav_in_memory(6_000),
+            &[PlayerCommand::SeekFraction(2.0)],
+        );
+        let ended = events
+            .iter()
+            .position(|e| matches!(e, PlayerEvent::TrackEnded))
+            .unwrap();
+        let playing = events
+            .iter()
+            .position(|e| matches!(e, PlayerEvent::StateChanged(PlaybackState::Playing)))
+            .unwrap();
+        assert!(ended < playing);
+    }
+
+    #[test]
+    fn test_format_mismatch_reported_in_track_info() {
+        let (evt_tx, evt_rx) = crossbeam_channel::unbounded();
+        let mut state = EngineState::new(MasterKey::default_key(), evt_tx);
+        let stream = VirtualAudioStream::from_reader(
+            Cursor::new(furry_wav_in_memory(100)),
+            &state.master_key,
+            &StreamOptions::default(),
+        )
+        .unwrap();
+        let mut decoder = crate::pcm_reader::stream_decoder(stream).unwrap();
+        decoder.info.format_mismatch = Some("format hint 'mp3' does not match".to_string());
+        let path =
+            std::env::temp_dir().join(format!("furry_engine_mismatch_{}.wav", std::process::id()));
+        let output = TrackOutput::File(FileSink::create(&path, 8_000, 2).unwrap());
+        state.install_track(PathBuf::from("mismatch"), decoder, output, None);
+        state.handle_command(PlayerCommand::Reset);
+        std::fs::remove_file(&path).ok();
+
+        let warning = evt_rx.try_iter().find_map(|e| match e {
+            PlayerEvent::TrackInfo(info) => info.format_warning,
+            _ => None,
+        });
+        assert_eq!(warning.as_deref(), Some("format hint 'mp3' does not match"));
+    }
+
+    #[test]
+    fn test_corrupt_chunk_stops_with_error() {
+        let mut furry = furry_wav_in_memory(6_000);
+        let entry = furry_format::FurryReader::open(Cursor::new(&furry), &MasterKey::default_key())
+            .unwrap()
+            .index
+            .audio_entries()[3]
+            .clone();
+        furry[(entry.file_offset + entry.record_len as u64 - 1) as usize] ^= 0xFF;
+
+        let (samples, events) = play_in_memory("corrupt", furry, &[]);
+        assert!(samples.len() < 6_000 * 2);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PlayerEvent::Error(msg) if msg == "audio chunk 3 failed authentication"
+        )));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PlayerEvent::StateChanged(PlaybackState::Stopped))));
+        assert!(!events.iter().any(|e| matches!(e, PlayerEvent::TrackEnded)));
+    }
+
+    #[test]
+    fn test_volume_reported_after_clamp_and_reset() {
+        let (evt_tx, evt_rx) = crossbeam_channel::unbounded();
+        let mut state = EngineState::new(MasterKey::default_key(), evt_tx);
+        let volumes = |rx: &Receiver<PlayerEvent>| -> Vec<f32> {
+            rx.try_iter()
+                .filter_map(|e| match e {
+                    PlayerEvent::VolumeChanged(v) => Some(v),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        state.handle_command(PlayerCommand::SetVolume(1.7));
+        state.handle_command(PlayerCommand::SetVolume(0.25));
+        state.handle_command(PlayerCommand::QueryVolume);
+        assert_eq!(volumes(&evt_rx), vec![1.0, 0.25, 0.25]);
+
+        state.handle_command(PlayerCommand::Reset);
+        assert_eq!(volumes(&evt_rx), vec![1.0]);
+
+        state.handle_command(PlayerCommand::SetVolume(0.5));
+        state.handle_command(PlayerCommand::QueryState);
+        let snapshot = evt_rx.try_iter().last();
+        assert!(matches!(
+            snapshot,
+            Some(PlayerEvent::StateSnapshot {
+                state: PlaybackState::Idle,
+                track: None,
+                position: Duration::ZERO,
+                volume: 0.5,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_queue_emits_track_changed() {
+        let (evt_tx, evt_rx) = crossbeam_channel::unbounded();
+        let mut state = EngineState::new(MasterKey::default_key(), evt_tx);
+        let changes = |rx: &Receiver<PlayerEvent>| -> Vec<(usize, PathBuf, usize)> {
+            rx.try_iter()
+                .filter_map(|e| match e {
+                    PlayerEvent::TrackChanged {
+                        index,
+                        path,
+                        queue_len,
+                    } => Some((index, path, queue_len)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // 文件不存在：加载失败，但序号照常推进
+        let queue: Vec<PathBuf> = ["a.furry", "b.furry", "c.furry"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        state.handle_command(PlayerCommand::SetQueue(queue.clone()));
+        state.handle_command(PlayerCommand::Next);
+        state.handle_command(PlayerCommand::PlayIndex(2));
+        state.handle_command(PlayerCommand::Next);
+        state.handle_command(PlayerCommand::Previous);
+        state.handle_command(PlayerCommand::PlayIndex(7));
+        assert_eq!(
+            changes(&evt_rx),
+            vec![
+                (0, queue[0].clone(), 3),
+                (2, queue[2].clone(), 3),
+                (0, queue[0].clone(), 3),
+                (2, queue[2].clone(), 3),
+            ]
+        );
+
+        // 直接加载的曲目不属于队列，Next 从头开始
+        state.handle_command(PlayerCommand::Load(PathBuf::from("x.furry")));
+        state.handle_command(PlayerCommand::Next);
+        assert_eq!(changes(&evt_rx), vec![(0, queue[0].clone(), 3)]);
+    }
+
+    #[test]
+    fn test_volume_applied_exactly_once() {
+        let (raw, _) = play_in_memory("gain_raw", furry_wav_in_memory(6_000), &[]);
+        let (full, _) = play_in_memory(
+            "gain_full",
+            furry_wav_in_memory(6_000),
+            &[PlayerCommand::SetVolume(1.0)],
+        );
+        let (half, _) = play_in_memory(
+            "gain_half",
+            furry_wav_in_memory(6_000),
+            &[PlayerCommand::SetVolume(0.5)],
+        );
+
+        assert_eq!(raw.len(), 6_000 * 2);
+        // 1.0 与不缩放逐位相同
+        assert!(full
+            .iter()
+            .zip(&raw)
+            .all(|(a, b)| a.to_bits() == b.to_bits()));
+        // 0.5 从 1.0 渐变而来；渐变结束后恰为一半，重复作用会得到 0.25
+        let ramp = (crate::GAIN_RAMP_DURATION.as_secs_f64() * 8_000.0) as usize * 2;
+        assert_eq!(half.len(), raw.len());
+        assert!(half[..ramp]
+            .iter()
+            .zip(&raw)
+            .all(|(h, r)| h.abs() <= r.abs()));
+        assert!(half[ramp..]
+            .iter()
+            .zip(&raw[ramp..])
+            .all(|(h, r)| *h == r * 0.5));
+    }
+}
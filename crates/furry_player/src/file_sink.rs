@@ -0,0 +1,121 @@
+//! 文件输出（渲染模式）
+//!
+//! 将解码后的 f32 采样写入 32-bit float WAV，用于无声卡环境或批量解码。
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::GainRamp;
+
+const WAV_HEADER_LEN: u32 = 44;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// WAV 文件输出
+pub struct FileSink {
+    writer: BufWriter<File>,
+    sample_rate: u32,
+    channels: u16,
+    data_bytes: u32,
+    position_frames: u64,
+    /// 写入时逐帧作用的音量增益，与声卡回调相同
+    gain: Arc<GainRamp>,
+}
+
+impl FileSink {
+    /// 创建输出文件并写入 WAV 头（长度字段在 `finalize` 时回填）
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> std::io::Result<Self> {
+        let mut sink = Self {
+            writer: BufWriter::new(File::create(path)?),
+            sample_rate,
+            channels,
+            data_bytes: 0,
+            position_frames: 0,
+            gain: Arc::new(GainRamp::new(1.0)),
+        };
+        sink.write_header()?;
+        Ok(sink)
+    }
+
+    /// 使用共享的音量增益（默认不缩放）
+    pub fn with_gain(mut self, gain: Arc<GainRamp>) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// 写入交错采样（写入前应用音量增益）
+    pub fn write(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        let mut samples = samples.to_vec();
+        self.gain
+            .apply(&mut samples, self.channels as usize, self.sample_rate);
+        for sample in &samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+        let bytes = (samples.len() * 4) as u32;
+        self.data_bytes = self.data_bytes.saturating_add(bytes);
+        self.position_frames += (samples.len() / self.channels.max(1) as usize) as u64;
+        Ok(())
+    }
+
+    /// 回填 WAV 头中的长度字段并刷新到磁盘（可重复调用）
+    pub fn finalize(&mut self) -> std::io::Result<()> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.write_header()?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()
+    }
+
+    /// 获取当前写入位置（秒）
+    pub fn position(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.position_frames as f64 / self.sample_rate as f64
+    }
+
+    /// 获取已写入的采样帧数
+    pub fn position_frames(&self) -> u64 {
+        self.position_frames
+    }
+
+    /// 重置位置计数（不影响已写入数据）
+    pub fn reset_position(&mut self) {
+        self.position_frames = 0;
+    }
+
+    /// 获取采样率
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// 获取声道数
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        let block_align = self.channels * 4;
+        let w = &mut self.writer;
+        w.write_all(b"RIFF")?;
+        w.write_all(&(WAV_HEADER_LEN - 8 + self.data_bytes).to_le_bytes())?;
+        w.write_all(b"WAVE")?;
+        w.write_all(b"fmt ")?;
+        w.write_all(&16u32.to_le_bytes())?;
+        w.write_all(&WAVE_FORMAT_IEEE_FLOAT.to_le_bytes())?;
+        w.write_all(&self.channels.to_le_bytes())?;
+        w.write_all(&self.sample_rate.to_le_bytes())?;
+        w.write_all(&(self.sample_rate * block_align as u32).to_le_bytes())?;
+        w.write_all(&block_align.to_le_bytes())?;
+        w.write_all(&32u16.to_le_bytes())?;
+        w.write_all(b"data")?;
+        w.write_all(&self.data_bytes.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        let _ = self.finalize();
+    }
+}
@@ -0,0 +1,95 @@
+//! 音量增益（带线性渐变）
+//!
+//! 音量突变会在波形上产生台阶，听起来是"咔哒"声；目标值变化后在
+//! [`GAIN_RAMP_DURATION`] 内逐帧线性过渡，静音/取消静音同样渐变。
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// 音量渐变时长
+pub const GAIN_RAMP_DURATION: Duration = Duration::from_millis(20);
+
+/// 带线性渐变的增益
+///
+/// 当前值与目标值以 f32 位模式存放在原子量中：控制线程 [`Self::set_target`]，
+/// 输出端（声卡回调/渲染文件）在采样真正被消费时 [`Self::apply`]，
+/// 音量变化不必等待已排队的缓冲区播完。
+#[derive(Debug)]
+pub struct GainRamp {
+    current: AtomicU32,
+    target: AtomicU32,
+}
+
+impl GainRamp {
+    /// 以 `gain` 为初始值（不渐变）
+    pub fn new(gain: f32) -> Self {
+        Self {
+            current: AtomicU32::new(gain.to_bits()),
+            target: AtomicU32::new(gain.to_bits()),
+        }
+    }
+
+    /// 设置目标增益，之后的 `apply` 会渐变到该值
+    pub fn set_target(&self, gain: f32) {
+        self.target.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    /// 目标增益
+    pub fn target(&self) -> f32 {
+        f32::from_bits(self.target.load(Ordering::Relaxed))
+    }
+
+    /// 对交错采样应用增益；同一帧内各声道使用相同增益
+    ///
+    /// 渐变进度保存在 `current` 中，应只由消费采样的一端调用。
+    pub fn apply(&self, samples: &mut [f32], channels: usize, sample_rate: u32) {
+        let channels = channels.max(1);
+        let target = self.target();
+        let mut current = f32::from_bits(self.current.load(Ordering::Relaxed));
+        if current == target {
+            if current != 1.0 {
+                samples.iter_mut().for_each(|s| *s *= current);
+            }
+            return;
+        }
+
+        let ramp_frames = (GAIN_RAMP_DURATION.as_secs_f32() * sample_rate as f32).max(1.0);
+        let step = 1.0 / ramp_frames;
+        for frame in samples.chunks_mut(channels) {
+            let delta = target - current;
+            current = if delta.abs() <= step {
+                target
+            } else {
+                current + step.copysign(delta)
+            };
+            frame.iter_mut().for_each(|s| *s *= current);
+        }
+        self.current.store(current.to_bits(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ramp_reaches_target_without_jump() {
+        let gain = GainRamp::new(1.0);
+        gain.set_target(0.0);
+
+        // 8 kHz 下 20ms = 160 帧
+        let mut samples = vec![1.0f32; 2 * 200];
+        gain.apply(&mut samples, 2, 8_000);
+
+        let max_step = samples
+            .chunks(2)
+            .map(|f| f[0])
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|w| (w[0] - w[1]).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_step <= 1.0 / 160.0 + 1e-6);
+        assert_eq!(samples[samples.len() - 1], 0.0);
+        assert!(samples.chunks(2).all(|f| f[0] == f[1]));
+    }
+}
@@ -2,14 +2,30 @@
 //!
 //! 提供 .furry 文件的解码和播放功能。
 
+#[cfg(feature = "async")]
+mod async_api;
+mod channels;
 mod command;
+mod controller;
 mod decoder;
 mod engine;
+mod file_sink;
+mod gain;
 mod output;
+mod pcm_reader;
 mod virtual_stream;
+mod wav_decoder;
 
+#[cfg(feature = "async")]
+pub use async_api::*;
+pub use channels::*;
 pub use command::*;
+pub use controller::*;
 pub use decoder::*;
 pub use engine::*;
+pub use file_sink::*;
+pub use gain::*;
 pub use output::*;
+pub use pcm_reader::*;
 pub use virtual_stream::*;
+pub use wav_decoder::*;
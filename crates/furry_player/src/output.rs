@@ -7,9 +7,11 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
+use cpal::{BufferSize, Device, SampleFormat, Stream, StreamConfig, SupportedBufferSize};
 use crossbeam_channel::{bounded, Sender};
 
+use crate::GainRamp;
+
 /// 音频输出错误
 #[derive(thiserror::Error, Debug)]
 pub enum OutputError {
@@ -17,16 +19,30 @@ pub enum OutputError {
     NoDevice,
     #[error("No supported config")]
     NoConfig,
+    #[error("Invalid output config: {0}")]
+    InvalidConfig(String),
     #[error("Stream error: {0}")]
     Stream(String),
 }
 
+/// 默认缓冲区大小（帧）
+pub const DEFAULT_BUFFER_SIZE: usize = 8192;
+/// 可设置的缓冲区大小范围（帧）
+pub const BUFFER_SIZE_RANGE: std::ops::RangeInclusive<usize> = 64..=65536;
+
 /// 音频输出配置
 #[derive(Debug, Clone)]
 pub struct OutputConfig {
     pub sample_rate: u32,
     pub channels: u16,
+    /// 缓冲区大小（帧）
+    ///
+    /// 越小延迟越低，但系统繁忙时更容易断音；越大越稳定，但暂停/seek/音量响应更慢。
+    /// 设备报告了支持范围时同时作为 cpal 回调缓冲区请求（按范围钳制），
+    /// 内部环形缓冲区为其 4 倍。
     pub buffer_size: usize,
+    /// 音量增益，在回调中逐帧作用；与控制端共享，音量变化立即生效
+    pub gain: Arc<GainRamp>,
 }
 
 impl Default for OutputConfig {
@@ -35,6 +51,7 @@ impl Default for OutputConfig {
             sample_rate: 44100,
             channels: 2,
             buffer_size: 4096,
+            gain: Arc::new(GainRamp::new(1.0)),
         }
     }
 }
@@ -45,8 +62,13 @@ pub struct AudioOutput {
     sample_tx: Sender<Vec<f32>>,
     is_playing: Arc<AtomicBool>,
     position_samples: Arc<AtomicU64>,
+    /// 与回调共享的环形缓冲区，仅用于查询填充程度
+    ring_buffer: Arc<RingBuffer>,
     sample_rate: u32,
+    /// 设备声道数
     channels: u16,
+    /// 写入数据的声道数（`OutputConfig.channels`）
+    source_channels: u16,
 }
 
 impl AudioOutput {
@@ -60,20 +82,48 @@ impl AudioOutput {
 
     /// 使用指定设备创建音频输出
     pub fn with_device(device: &Device, config: OutputConfig) -> Result<Self, OutputError> {
-        let supported_config = device
+        // 采样率为 0 会让 cpal 配置无效，并使进度换算除以零
+        if config.sample_rate == 0 || config.channels == 0 {
+            return Err(OutputError::InvalidConfig(format!(
+                "{} Hz, {} channels",
+                config.sample_rate, config.channels
+            )));
+        }
+        let candidates: Vec<_> = device
             .supported_output_configs()
             .map_err(|e| OutputError::Stream(e.to_string()))?
-            .find(|c| {
-                c.channels() == config.channels
-                    && c.min_sample_rate().0 <= config.sample_rate
+            .filter(|c| {
+                c.min_sample_rate().0 <= config.sample_rate
                     && c.max_sample_rate().0 >= config.sample_rate
                     && c.sample_format() == SampleFormat::F32
             })
+            .collect();
+        // 优先源声道数；否则取不超过源声道数的最多声道（降混），再否则取最少声道（升混）
+        let supported_config = candidates
+            .iter()
+            .find(|c| c.channels() == config.channels)
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .filter(|c| c.channels() < config.channels)
+                    .max_by_key(|c| c.channels())
+            })
+            .or_else(|| candidates.iter().min_by_key(|c| c.channels()))
+            .cloned()
             .ok_or(OutputError::NoConfig)?;
+        let device_channels = supported_config.channels();
 
-        let stream_config: StreamConfig = supported_config
+        let device_buffer = match supported_config.buffer_size() {
+            SupportedBufferSize::Range { min, max } => {
+                let frames = u32::try_from(config.buffer_size).unwrap_or(u32::MAX);
+                BufferSize::Fixed(frames.clamp(*min, *max))
+            }
+            SupportedBufferSize::Unknown => BufferSize::Default,
+        };
+        let mut stream_config: StreamConfig = supported_config
             .with_sample_rate(cpal::SampleRate(config.sample_rate))
             .into();
+        stream_config.buffer_size = device_buffer;
 
         let (sample_tx, sample_rx) = bounded::<Vec<f32>>(32);
         let is_playing = Arc::new(AtomicBool::new(false));
@@ -81,11 +131,14 @@ impl AudioOutput {
 
         let is_playing_clone = is_playing.clone();
         let position_clone = position_samples.clone();
-        let channels = config.channels as usize;
+        let channels = device_channels as usize;
+        let sample_rate = config.sample_rate;
+        let gain = config.gain.clone();
 
         // 创建环形缓冲区
         let ring_buffer = Arc::new(RingBuffer::new(config.buffer_size * 4));
         let ring_clone = ring_buffer.clone();
+        let ring_callback = ring_buffer.clone();
 
         // 启动填充线程
         std::thread::spawn(move || {
@@ -99,11 +152,7 @@ impl AudioOutput {
                 &stream_config,
                 move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
                     if is_playing_clone.load(Ordering::Relaxed) {
-                        let read = ring_buffer.read(data);
-                        // 填充未读取部分为静音
-                        for sample in &mut data[read..] {
-                            *sample = 0.0;
-                        }
+                        let read = write_audio(data, &ring_callback, &gain, channels, sample_rate);
                         // 更新位置
                         position_clone.fetch_add((read / channels) as u64, Ordering::Relaxed);
                     } else {
@@ -129,13 +178,26 @@ impl AudioOutput {
             sample_tx,
             is_playing,
             position_samples,
+            ring_buffer,
             sample_rate: config.sample_rate,
-            channels: config.channels,
+            channels: device_channels,
+            source_channels: config.channels,
         })
     }
 
-    /// 写入采样数据
+    /// 写入采样数据（源声道数的交错采样，必要时降混/升混到设备声道数）
+    ///
+    /// 写入的是未缩放的采样，音量在回调中作用（见 [`OutputConfig::gain`]）。
     pub fn write(&self, samples: Vec<f32>) -> bool {
+        let samples = if self.source_channels == self.channels {
+            samples
+        } else {
+            crate::remix(
+                &samples,
+                self.source_channels as usize,
+                self.channels as usize,
+            )
+        };
         self.sample_tx.try_send(samples).is_ok()
     }
 
@@ -146,10 +208,18 @@ impl AudioOutput {
 
     /// 获取当前播放位置（秒）
     pub fn position(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
         let samples = self.position_samples.load(Ordering::Relaxed);
         samples as f64 / self.sample_rate as f64
     }
 
+    /// 获取已播放的采样帧数
+    pub fn position_frames(&self) -> u64 {
+        self.position_samples.load(Ordering::Relaxed)
+    }
+
     /// 重置位置
     pub fn reset_position(&self) {
         self.position_samples.store(0, Ordering::Relaxed);
@@ -160,12 +230,36 @@ impl AudioOutput {
         self.sample_rate
     }
 
-    /// 获取声道数
+    /// 环形缓冲区填充比例（0.0 - 1.0），不含尚在发送通道中、未进入缓冲区的数据
+    pub fn occupancy(&self) -> f32 {
+        self.ring_buffer.occupancy()
+    }
+
+    /// 获取设备声道数（可能少于源声道数，此时写入时降混）
     pub fn channels(&self) -> u16 {
         self.channels
     }
 }
 
+/// 回调主体：从环形缓冲区取出采样、逐帧应用增益，未填满部分输出静音
+///
+/// 返回读取的采样数。
+fn write_audio(
+    data: &mut [f32],
+    ring: &RingBuffer,
+    gain: &GainRamp,
+    channels: usize,
+    sample_rate: u32,
+) -> usize {
+    let read = ring.read(data);
+    gain.apply(&mut data[..read], channels, sample_rate);
+    // 填充未读取部分为静音
+    for sample in &mut data[read..] {
+        *sample = 0.0;
+    }
+    read
+}
+
 /// 简单的环形缓冲区
 struct RingBuffer {
     buffer: std::sync::Mutex<VecDeque<f32>>,
@@ -198,6 +292,14 @@ impl RingBuffer {
         buf.extend(data.iter().copied());
     }
 
+    fn occupancy(&self) -> f32 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        let len = self.buffer.lock().unwrap_or_else(|e| e.into_inner()).len();
+        len as f32 / self.capacity as f32
+    }
+
     fn read(&self, output: &mut [f32]) -> usize {
         let mut buf = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
         let to_read = output.len().min(buf.len());
@@ -214,3 +316,38 @@ impl RingBuffer {
         to_read
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_occupancy() {
+        let ring = RingBuffer::new(8);
+        assert_eq!(ring.occupancy(), 0.0);
+        ring.write(&[0.1; 6]);
+        assert_eq!(ring.occupancy(), 0.75);
+        ring.write(&[0.2; 6]);
+        assert_eq!(ring.occupancy(), 1.0);
+        let mut out = [0.0; 4];
+        assert_eq!(ring.read(&mut out), 4);
+        assert_eq!(ring.occupancy(), 0.5);
+    }
+
+    #[test]
+    fn test_gain_change_applies_to_buffered_audio() {
+        let ring = RingBuffer::new(64);
+        let gain = GainRamp::new(1.0);
+        ring.write(&[1.0; 32]);
+
+        // 采样入队之后才静音：下一次回调即开始渐变
+        gain.set_target(0.0);
+        let mut out = [0.5; 40];
+        // 400 Hz 下 20ms = 8 帧
+        assert_eq!(write_audio(&mut out, &ring, &gain, 2, 400), 32);
+        assert!(out[..32].chunks(2).all(|f| f[0] == f[1]));
+        assert_eq!(out[0], 0.875);
+        assert_eq!(out[14], 0.0);
+        assert!(out[32..].iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,248 @@
+//! 不经过音频输出的 PCM 解码
+//!
+//! 响度分析、波形、指纹等工具只需要解码后的采样，不应打开 cpal 设备。
+
+use std::path::Path;
+use std::time::Duration;
+
+use furry_crypto::MasterKey;
+
+use crate::{
+    AudioDecoder, AudioInfo, DecodeHint, DecoderError, LoadError, StreamOptions, VirtualAudioStream,
+};
+
+/// 打开 .furry 文件并按其原始格式创建解码器
+pub(crate) fn open_furry_decoder(
+    path: &Path,
+    master_key: &MasterKey,
+    options: &StreamOptions,
+) -> Result<AudioDecoder, LoadError> {
+    let stream = VirtualAudioStream::open_with_options(path, master_key, options)?;
+    stream_decoder(stream)
+}
+
+/// 按虚拟流的原始格式创建解码器，并应用打包时记录的无缝播放裁剪
+pub(crate) fn stream_decoder(stream: VirtualAudioStream) -> Result<AudioDecoder, LoadError> {
+    // 原始格式（或覆盖的提示）映射为扩展名 + MIME
+    let hint = DecodeHint::from_extension(stream.format_hint());
+
+    let gapless = stream.gapless();
+    let mut decoder = AudioDecoder::new(stream, &hint)?;
+    if let Some(gapless) = &gapless {
+        decoder.set_gapless(gapless);
+    }
+    Ok(decoder)
+}
+
+/// 逐块读取交错 f32 PCM
+///
+/// 采样率/声道数在打开时即可通过 [`Self::info`] 获取；同时实现了
+/// `Iterator<Item = Result<Vec<f32>, DecoderError>>`，解码出错后迭代结束。
+pub struct PcmReader {
+    decoder: AudioDecoder,
+    failed: bool,
+}
+
+/// 解码整个 .furry 文件，见 [`PcmReader`]
+pub fn decode_all(path: &Path, master_key: &MasterKey) -> Result<PcmReader, LoadError> {
+    PcmReader::open(path, master_key)
+}
+
+/// 预览解码时在起点前多解码的时长，抵消有损格式 seek 后的预热误差
+const PREVIEW_PREROLL: Duration = Duration::from_millis(200);
+
+/// 解码 `[start, start + len)` 区间的交错采样，用于试听片段和波形缩略图
+///
+/// 先跳到起点之前一点再解码，丢弃多出的帧，返回的采样数恰为
+/// `round(len × sample_rate) × channels`；曲目提前结束时只返回实际解码到的部分。
+pub fn decode_range(
+    path: &Path,
+    master_key: &MasterKey,
+    start: Duration,
+    len: Duration,
+) -> Result<(AudioInfo, Vec<f32>), LoadError> {
+    let mut decoder = open_furry_decoder(path, master_key, &StreamOptions::default())?;
+    let info = decoder.info.clone();
+    let frames = |d: Duration| (d.as_secs_f64() * info.sample_rate as f64).round() as u64;
+
+    let mut skip_frames = 0;
+    if !start.is_zero() {
+        let preroll_start = start.saturating_sub(PREVIEW_PREROLL);
+        skip_frames = decoder.seek_before(preroll_start)? + frames(start) - frames(preroll_start);
+    }
+
+    let wanted = frames(len) as usize * info.channels;
+    let mut skip = skip_frames as usize * info.channels;
+    let mut samples = Vec::with_capacity(wanted);
+    while samples.len() < wanted {
+        let Some(block) = decoder.decode_next()? else {
+            break;
+        };
+        let dropped = skip.min(block.len());
+        skip -= dropped;
+        let block = &block[dropped..];
+        let take = block.len().min(wanted - samples.len());
+        samples.extend_from_slice(&block[..take]);
+    }
+    Ok((info, samples))
+}
+
+impl PcmReader {
+    /// 打开 .furry 文件
+    pub fn open(path: &Path, master_key: &MasterKey) -> Result<Self, LoadError> {
+        Self::open_with_options(path, master_key, &StreamOptions::default())
+    }
+
+    /// 按选项打开 .furry 文件（如覆盖格式提示）
+    pub fn open_with_options(
+        path: &Path,
+        master_key: &MasterKey,
+        options: &StreamOptions,
+    ) -> Result<Self, LoadError> {
+        let decoder = open_furry_decoder(path, master_key, options)?;
+        Ok(Self::from_decoder(decoder))
+    }
+
+    /// 包装已有解码器（如未加密的原始文件）
+    pub fn from_decoder(decoder: AudioDecoder) -> Self {
+        Self {
+            decoder,
+            failed: false,
+        }
+    }
+
+    pub fn info(&self) -> &AudioInfo {
+        &self.decoder.info
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.decoder.info.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.decoder.info.channels
+    }
+
+    /// 解码下一块交错采样；流结束返回 `None`
+    pub fn next_block(&mut self) -> Result<Option<Vec<f32>>, DecoderError> {
+        self.decoder.decode_next()
+    }
+}
+
+impl Iterator for PcmReader {
+    type Item = Result<Vec<f32>, DecoderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let block = self.next_block().transpose();
+        self.failed = matches!(block, Some(Err(_)));
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use furry_format::{FurryWriter, OriginalFormat};
+
+    /// 8 kHz 双声道 16-bit WAV，左声道采样值为帧号
+    fn write_test_furry(name: &str, frames: u32) -> std::path::PathBuf {
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + frames * 4).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&8_000u32.to_le_bytes());
+        wav.extend_from_slice(&32_000u32.to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(frames * 4).to_le_bytes());
+        for frame in 0..frames {
+            wav.extend_from_slice(&(frame as i16).to_le_bytes());
+            wav.extend_from_slice(&0i16.to_le_bytes());
+        }
+
+        let path =
+            std::env::temp_dir().join(format!("furry_{}_{}.furry", name, std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer =
+            FurryWriter::create(file, &MasterKey::default_key(), OriginalFormat::Wav).unwrap();
+        for (i, chunk) in wav.chunks(4096).enumerate() {
+            writer.write_audio_chunk(chunk, i as u64 * 4096).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_decode_all_yields_every_frame() {
+        let frames = 10_000u32;
+        let master_key = MasterKey::default_key();
+        let path = write_test_furry("pcm", frames);
+
+        let reader = decode_all(&path, &master_key).unwrap();
+        assert_eq!(reader.sample_rate(), 8_000);
+        assert_eq!(reader.channels(), 2);
+        assert_eq!(reader.info().bits_per_sample, Some(16));
+        assert_eq!(reader.info().bit_rate, Some(256_000));
+        let samples: usize = reader.map(|block| block.unwrap().len()).sum();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(samples, frames as usize * 2);
+    }
+
+    #[test]
+    fn test_decode_range_returns_exact_window() {
+        let master_key = MasterKey::default_key();
+        let path = write_test_furry("pcm_range", 10_000);
+
+        let (info, samples) = decode_range(
+            &path,
+            &master_key,
+            Duration::from_millis(500),
+            Duration::from_millis(250),
+        )
+        .unwrap();
+        // 超出结尾的部分被截断
+        let (_, tail) = decode_range(
+            &path,
+            &master_key,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(info.sample_rate, 8_000);
+        assert_eq!(samples.len(), 2_000 * 2);
+        assert_eq!(samples[0], 4_000.0 / 32_768.0);
+        assert_eq!(samples[samples.len() - 2], 5_999.0 / 32_768.0);
+        assert_eq!(tail.len(), 2_000 * 2);
+    }
+
+    #[test]
+    fn test_auth_failure_is_reported_with_chunk_index() {
+        let master_key = MasterKey::default_key();
+        let path = write_test_furry("pcm_corrupt", 10_000);
+        let entry =
+            furry_format::FurryReader::open(std::fs::File::open(&path).unwrap(), &master_key)
+                .unwrap()
+                .index
+                .audio_entries()[2]
+                .clone();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[(entry.file_offset + entry.record_len as u64 - 1) as usize] ^= 0xFF;
+        std::fs::write(&path, bytes).unwrap();
+
+        let blocks: Vec<_> = decode_all(&path, &master_key).unwrap().collect();
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(
+            blocks.last(),
+            Some(Err(DecoderError::ChunkAuthFailed(2)))
+        ));
+    }
+}
@@ -6,9 +6,11 @@
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
+use std::thread;
 
+use crossbeam_channel::{bounded, Receiver, Sender};
 use furry_crypto::MasterKey;
-use furry_format::{FurryReader, IndexEntryV1};
+use furry_format::{FormatError, FurryReader, IndexEntryV1, TrackTable};
 
 /// 虚拟音频流错误
 #[derive(thiserror::Error, Debug)]
@@ -21,21 +23,81 @@ pub enum StreamError {
 
     #[error("Seek out of bounds")]
     SeekOutOfBounds,
+
+    #[error("Track {0} not found in track table")]
+    TrackNotFound(u32),
+
+    #[error("Audio chunk {0} failed authentication")]
+    ChunkAuthFailed(usize),
+}
+
+impl StreamError {
+    /// 从 `Read` 返回的 IO 错误中取回认证失败的 chunk 序号
+    ///
+    /// 解码器只能看到 `io::Error`；认证失败以 `InvalidData` 包装本类型，据此与普通 IO 错误区分。
+    pub fn auth_failed_chunk(err: &std::io::Error) -> Option<usize> {
+        match err.get_ref()?.downcast_ref::<StreamError>()? {
+            StreamError::ChunkAuthFailed(index) => Some(*index),
+            _ => None,
+        }
+    }
 }
 
+impl From<StreamError> for std::io::Error {
+    fn from(e: StreamError) -> Self {
+        match e {
+            StreamError::Io(e) => e,
+            StreamError::ChunkAuthFailed(_) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+            }
+            e => std::io::Error::other(e),
+        }
+    }
+}
+
+/// 虚拟音频流选项
+#[derive(Debug, Clone, Default)]
+pub struct StreamOptions {
+    /// 在后台线程预读并解密下一个 chunk（会额外占用一个线程和一个文件句柄）
+    pub prefetch: bool,
+    /// 覆盖解码器格式提示（扩展名，如 `aac`）；默认取原始格式或 SourceInfo 中的扩展名
+    pub format_hint: Option<String>,
+    /// 只暴露曲目表中的第 n 首（多曲目专辑），流的起点与长度都是该曲目的
+    pub track: Option<u32>,
+}
+
+/// 虚拟流的底层输入：文件或内存（`Cursor`）均可
+///
+/// symphonia 的 `MediaSource` 要求 `Send + Sync`。
+pub trait ReadSeek: Read + Seek + Send + Sync {}
+
+impl<T: Read + Seek + Send + Sync> ReadSeek for T {}
+
+type BoxedReader = FurryReader<Box<dyn ReadSeek>>;
+
 /// 虚拟音频流
 ///
 /// 将 .furry 文件中的加密 AUDIO chunks 映射为连续的可读字节流。
 pub struct VirtualAudioStream {
-    reader: FurryReader<File>,
+    reader: BoxedReader,
     /// 排序后的 AUDIO 条目
     audio_entries: Vec<IndexEntryV1>,
     /// 虚拟流总长度
     total_len: u64,
-    /// 当前虚拟位置
+    /// 流起点在 AUDIO 虚拟偏移中的位置（只暴露专辑中的一首时非 0）
+    base: u64,
+    /// 当前虚拟位置（相对 `base`）
     position: u64,
     /// 当前缓存的 chunk 数据
     current_chunk: Option<ChunkCache>,
+    /// 后台预读
+    prefetcher: Option<Prefetcher>,
+    /// 解码器扩展名提示
+    format_hint: String,
+    /// 打包时记录的无缝播放裁剪信息
+    gapless: Option<furry_format::GaplessInfo>,
+    /// 多曲目专辑的曲目表
+    track_table: Option<TrackTable>,
 }
 
 struct ChunkCache {
@@ -43,23 +105,141 @@ struct ChunkCache {
     data: Vec<u8>,
     /// 该 chunk 的虚拟起始偏移
     virtual_start: u64,
+    /// 该 chunk 在 `audio_entries` 中的下标
+    index: usize,
+}
+
+/// 后台预读线程（第二个缓存槽）
+struct Prefetcher {
+    req_tx: Sender<usize>,
+    resp_rx: Receiver<(usize, Result<Vec<u8>, FormatError>)>,
+    /// 已请求但尚未取回的 chunk 下标
+    pending: Option<usize>,
+}
+
+impl Prefetcher {
+    fn spawn(mut reader: BoxedReader, entries: Vec<IndexEntryV1>) -> Self {
+        let (req_tx, req_rx) = bounded::<usize>(1);
+        let (resp_tx, resp_rx) = bounded(1);
+
+        thread::spawn(move || {
+            while let Ok(idx) = req_rx.recv() {
+                let result = reader.read_chunk(&entries[idx]);
+                if resp_tx.send((idx, result)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            req_tx,
+            resp_rx,
+            pending: None,
+        }
+    }
+
+    fn request(&mut self, idx: usize) {
+        if self.pending.is_none() && self.req_tx.try_send(idx).is_ok() {
+            self.pending = Some(idx);
+        }
+    }
+
+    /// 取回预读结果；若预读的不是 `idx` 则丢弃
+    fn take(&mut self, idx: usize) -> Option<Vec<u8>> {
+        let pending = self.pending.take()?;
+        let (got, result) = self.resp_rx.recv().ok()?;
+        if pending == idx && got == idx {
+            result.ok()
+        } else {
+            None
+        }
+    }
 }
 
 impl VirtualAudioStream {
     /// 打开 .furry 文件并创建虚拟流
     pub fn open(path: &Path, master_key: &MasterKey) -> Result<Self, StreamError> {
-        let file = File::open(path)?;
-        let reader = FurryReader::open(file, master_key)?;
+        Self::open_with_options(path, master_key, &StreamOptions::default())
+    }
+
+    /// 打开 .furry 文件并按选项创建虚拟流
+    pub fn open_with_options(
+        path: &Path,
+        master_key: &MasterKey,
+        options: &StreamOptions,
+    ) -> Result<Self, StreamError> {
+        // 预读线程使用独立的文件句柄，避免与主读取争用文件位置
+        let prefetch_file = match options.prefetch {
+            true => Some(Box::new(File::open(path)?) as Box<dyn ReadSeek>),
+            false => None,
+        };
+        Self::open_boxed(
+            Box::new(File::open(path)?),
+            prefetch_file,
+            master_key,
+            options,
+        )
+    }
+
+    /// 从任意可 seek 的输入（如内存中的 `Cursor`）创建虚拟流
+    ///
+    /// 预读需要第二个独立句柄，因此忽略 `options.prefetch`。
+    pub fn from_reader<R: ReadSeek + 'static>(
+        inner: R,
+        master_key: &MasterKey,
+        options: &StreamOptions,
+    ) -> Result<Self, StreamError> {
+        Self::open_boxed(Box::new(inner), None, master_key, options)
+    }
+
+    fn open_boxed(
+        inner: Box<dyn ReadSeek>,
+        prefetch_inner: Option<Box<dyn ReadSeek>>,
+        master_key: &MasterKey,
+        options: &StreamOptions,
+    ) -> Result<Self, StreamError> {
+        let mut reader = FurryReader::open(inner, master_key)?;
+        let format_hint = match &options.format_hint {
+            Some(hint) => hint.clone(),
+            None => reader.original_extension(),
+        };
+        let mut gapless = reader.read_gapless().ok().flatten();
+        let track_table = reader.read_track_table().ok().flatten();
 
         let audio_entries: Vec<_> = reader.index.audio_entries().into_iter().cloned().collect();
-        let total_len = reader.index.header.audio_stream_len;
+        let mut total_len = audio_stream_len(reader.index.header.audio_stream_len, &audio_entries);
+        let mut base = 0;
+        if let Some(index) = options.track {
+            let track = track_table
+                .as_ref()
+                .and_then(|table| table.get(index))
+                .filter(|track| track.virtual_start < total_len)
+                .ok_or(StreamError::TrackNotFound(index))?;
+            base = track.virtual_start;
+            total_len = track.virtual_end().min(total_len) - base;
+            // 裁剪信息属于整条流，不适用于其中一首
+            gapless = None;
+        }
+
+        let prefetcher = match prefetch_inner {
+            Some(inner) => {
+                let prefetch_reader = FurryReader::open(inner, master_key)?;
+                Some(Prefetcher::spawn(prefetch_reader, audio_entries.clone()))
+            }
+            None => None,
+        };
 
         Ok(Self {
             reader,
             audio_entries,
             total_len,
+            base,
             position: 0,
             current_chunk: None,
+            prefetcher,
+            format_hint,
+            gapless,
+            track_table,
         })
     }
 
@@ -68,6 +248,32 @@ impl VirtualAudioStream {
         self.reader.index.header.original_format
     }
 
+    /// 供解码器探测使用的扩展名提示（可能为空）
+    pub fn format_hint(&self) -> &str {
+        &self.format_hint
+    }
+
+    /// 编码器 delay/padding（未记录时为 `None`，不裁剪）
+    pub fn gapless(&self) -> Option<furry_format::GaplessInfo> {
+        self.gapless
+    }
+
+    /// 多曲目专辑的曲目表（普通文件为 `None`）
+    pub fn track_table(&self) -> Option<&TrackTable> {
+        self.track_table.as_ref()
+    }
+
+    /// 丢弃前 `offset` 字节，之后的读取、seek 与长度都从该处起算
+    ///
+    /// 用于时长未知时按字节比例 seek；裁剪信息只适用于流的起点，一并丢弃。
+    pub(crate) fn skip_front(&mut self, offset: u64) {
+        let offset = offset.min(self.total_len);
+        self.base += offset;
+        self.total_len -= offset;
+        self.position = 0;
+        self.gapless = None;
+    }
+
     /// 获取总长度
     pub fn len(&self) -> u64 {
         self.total_len
@@ -82,7 +288,7 @@ impl VirtualAudioStream {
         self.audio_entries
             .binary_search_by(|entry| {
                 let start = entry.virtual_offset;
-                let end = start + entry.plain_len as u64;
+                let end = start.saturating_add(entry.plain_len as u64);
                 if virtual_offset < start {
                     std::cmp::Ordering::Greater
                 } else if virtual_offset >= end {
@@ -100,30 +306,71 @@ impl VirtualAudioStream {
             return Ok(());
         }
 
+        let offset = self.base + self.position;
         let need_load = match &self.current_chunk {
             None => true,
             Some(cache) => {
                 let end = cache.virtual_start + cache.data.len() as u64;
-                self.position < cache.virtual_start || self.position >= end
+                offset < cache.virtual_start || offset >= end
             }
         };
 
         if need_load {
             let chunk_idx = self
-                .find_chunk_index(self.position)
+                .find_chunk_index(offset)
                 .ok_or(StreamError::SeekOutOfBounds)?;
 
+            let prefetched = self.prefetcher.as_mut().and_then(|p| p.take(chunk_idx));
+
             let entry = &self.audio_entries[chunk_idx];
-            let data = self.reader.read_chunk(entry)?;
+            let data = match prefetched {
+                Some(data) => data,
+                None => self.reader.read_chunk(entry).map_err(|e| match e {
+                    FormatError::Crypto(_) => StreamError::ChunkAuthFailed(chunk_idx),
+                    e => e.into(),
+                })?,
+            };
 
             self.current_chunk = Some(ChunkCache {
                 data,
                 virtual_start: entry.virtual_offset,
+                index: chunk_idx,
             });
         }
 
         Ok(())
     }
+
+    /// 当读取位置进入当前 chunk 的最后 25% 时，请求预读下一个 chunk
+    fn maybe_prefetch_next(&mut self) {
+        let (Some(prefetcher), Some(cache)) = (self.prefetcher.as_mut(), &self.current_chunk)
+        else {
+            return;
+        };
+        let next = cache.index + 1;
+        if next >= self.audio_entries.len() {
+            return;
+        }
+        let offset_in_chunk = (self.base + self.position).saturating_sub(cache.virtual_start);
+        if offset_in_chunk.saturating_mul(4) >= cache.data.len() as u64 * 3 {
+            prefetcher.request(next);
+        }
+    }
+}
+
+/// 虚拟流总长度
+///
+/// 优先使用索引头中的 `audio_stream_len`；为 0（旧版/流式写入未回填）时
+/// 由 AUDIO 条目推算，否则 `SeekFrom::End` 与 `byte_len()` 都会出错。
+fn audio_stream_len(header_len: u64, entries: &[IndexEntryV1]) -> u64 {
+    if header_len != 0 {
+        return header_len;
+    }
+    entries
+        .iter()
+        .map(|e| e.virtual_offset.saturating_add(e.plain_len as u64))
+        .max()
+        .unwrap_or(0)
 }
 
 impl Read for VirtualAudioStream {
@@ -132,20 +379,25 @@ impl Read for VirtualAudioStream {
             return Ok(0);
         }
 
-        self.ensure_chunk_loaded().map_err(std::io::Error::other)?;
+        self.ensure_chunk_loaded()?;
 
         let cache = self.current_chunk.as_ref().ok_or_else(|| {
-            std::io::Error::other(
-                "virtual stream chunk cache missing after ensure_chunk_loaded",
-            )
+            std::io::Error::other("virtual stream chunk cache missing after ensure_chunk_loaded")
         })?;
-        let offset_in_chunk = (self.position - cache.virtual_start) as usize;
-        let available = cache.data.len() - offset_in_chunk;
-        let to_read = buf.len().min(available);
+        // 索引可能被篡改：位置不在缓存范围内时返回错误而不是 panic
+        let chunk_data = (self.base + self.position)
+            .checked_sub(cache.virtual_start)
+            .and_then(|off| cache.data.get(off as usize..))
+            .ok_or_else(|| std::io::Error::other("virtual stream position outside chunk"))?;
+        // 只暴露一首时不能读过曲目末尾
+        let remaining = (self.total_len - self.position).min(usize::MAX as u64) as usize;
+        let to_read = buf.len().min(chunk_data.len()).min(remaining);
 
-        buf[..to_read].copy_from_slice(&cache.data[offset_in_chunk..offset_in_chunk + to_read]);
+        buf[..to_read].copy_from_slice(&chunk_data[..to_read]);
         self.position += to_read as u64;
 
+        self.maybe_prefetch_next();
+
         Ok(to_read)
     }
 }
@@ -153,19 +405,19 @@ impl Read for VirtualAudioStream {
 impl Seek for VirtualAudioStream {
     fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
         let new_pos = match pos {
-            SeekFrom::Start(offset) => offset as i64,
-            SeekFrom::End(offset) => self.total_len as i64 + offset,
-            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.total_len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
         };
 
-        if new_pos < 0 {
+        let Some(new_pos) = new_pos else {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                "seek to negative position",
+                "seek to negative or overflowing position",
             ));
-        }
+        };
 
-        self.position = new_pos as u64;
+        self.position = new_pos;
         Ok(self.position)
     }
 }
@@ -180,3 +432,168 @@ impl symphonia::core::io::MediaSource for VirtualAudioStream {
         Some(self.total_len)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    use furry_format::{
+        ChunkRecordHeaderV1, ChunkType, FurryHeaderV1, FurryIndexV1, OriginalFormat,
+        FURRY_HEADER_LEN,
+    };
+
+    /// 手工写出索引头 `audio_stream_len = 0` 的文件（模拟旧版写入器）
+    fn write_file_without_stream_len(path: &Path, master_key: &MasterKey, chunks: &[&[u8]]) {
+        let file_id = furry_crypto::generate_file_id().unwrap();
+        let mut header = FurryHeaderV1::new(file_id, furry_crypto::generate_salt().unwrap());
+        let keys = furry_crypto::derive_file_keys(master_key, &header.kdf_salt().unwrap()).unwrap();
+
+        // 头部先占位，写完索引后回填
+        let mut out = vec![0u8; FURRY_HEADER_LEN as usize];
+        let mut index = FurryIndexV1::new(0, OriginalFormat::Wav);
+
+        let seal = |out: &mut Vec<u8>, chunk_header: ChunkRecordHeaderV1, data: &[u8]| {
+            let nonce = furry_crypto::nonce_for_chunk(&keys.nonce_prefix, chunk_header.chunk_seq);
+            let aad = furry_crypto::build_aad_v1(
+                &header.file_id,
+                header.version,
+                header.flags,
+                &chunk_header.to_bytes(),
+            );
+            let mut buf = data.to_vec();
+            let tag =
+                furry_crypto::encrypt_in_place_detached(&keys.aead_key, &nonce, &aad, &mut buf)
+                    .unwrap();
+            chunk_header.write_to(&mut *out).unwrap();
+            out.write_all(&buf).unwrap();
+            out.write_all(&tag).unwrap();
+            chunk_header.record_len()
+        };
+
+        let mut virtual_offset = 0u64;
+        for (seq, data) in chunks.iter().enumerate() {
+            let file_offset = out.len() as u64;
+            let chunk_header = ChunkRecordHeaderV1::new(
+                ChunkType::Audio,
+                seq as u64,
+                virtual_offset,
+                data.len() as u32,
+            );
+            let record_len = seal(&mut out, chunk_header, data);
+            index.add_entry(IndexEntryV1::new_audio(
+                seq as u64,
+                file_offset,
+                record_len,
+                data.len() as u32,
+                virtual_offset,
+            ));
+            virtual_offset += data.len() as u64;
+        }
+        assert_eq!(index.header.audio_stream_len, 0);
+
+        let index_bytes = index.to_bytes();
+        header.index_offset = out.len() as u64;
+        let index_header = ChunkRecordHeaderV1::new(
+            ChunkType::Index,
+            chunks.len() as u64,
+            0,
+            index_bytes.len() as u32,
+        );
+        header.index_total_len = seal(&mut out, index_header, &index_bytes);
+
+        let mut header_bytes = Vec::new();
+        header.write_to(&mut header_bytes).unwrap();
+        out[..header_bytes.len()].copy_from_slice(&header_bytes);
+        std::fs::write(path, out).unwrap();
+    }
+
+    #[test]
+    fn test_total_len_recovered_when_header_len_is_zero() {
+        let master_key = MasterKey::default_key();
+        let path = std::env::temp_dir().join(format!(
+            "furry_test_zero_stream_len_{}.furry",
+            std::process::id()
+        ));
+        write_file_without_stream_len(&path, &master_key, &[b"hello ", b"world"]);
+
+        let mut stream = VirtualAudioStream::open(&path, &master_key).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stream.len(), 11);
+        assert_eq!(
+            symphonia::core::io::MediaSource::byte_len(&stream),
+            Some(11)
+        );
+        assert_eq!(stream.seek(SeekFrom::End(-5)).unwrap(), 6);
+        let mut tail = String::new();
+        stream.read_to_string(&mut tail).unwrap();
+        assert_eq!(tail, "world");
+    }
+
+    #[test]
+    fn test_album_track_window() {
+        let master_key = MasterKey::default_key();
+        let path = std::env::temp_dir().join(format!(
+            "furry_test_album_window_{}.furry",
+            std::process::id()
+        ));
+        let file = File::create(&path).unwrap();
+        let mut writer =
+            furry_format::FurryWriter::create(file, &master_key, OriginalFormat::Wav).unwrap();
+        let table = TrackTable {
+            tracks: vec![
+                furry_format::TrackTableEntry {
+                    index: 0,
+                    virtual_start: 0,
+                    virtual_len: 7,
+                    title: "one".into(),
+                    tags_json: None,
+                },
+                furry_format::TrackTableEntry {
+                    index: 1,
+                    virtual_start: 7,
+                    virtual_len: 6,
+                    title: "two".into(),
+                    tags_json: None,
+                },
+            ],
+        };
+        writer
+            .write_meta_chunk(furry_format::MetaKind::TrackTable, &table.to_bytes(), 0)
+            .unwrap();
+        writer.write_audio_chunk(b"first s", 0).unwrap();
+        writer.write_audio_chunk(b"econd!", 7).unwrap();
+        writer.finish().unwrap();
+
+        let options = |track| StreamOptions {
+            track: Some(track),
+            ..Default::default()
+        };
+        let mut stream =
+            VirtualAudioStream::open_with_options(&path, &master_key, &options(1)).unwrap();
+        assert_eq!(stream.track_table(), Some(&table));
+        assert_eq!(stream.len(), 6);
+        let mut second = String::new();
+        stream.read_to_string(&mut second).unwrap();
+        assert_eq!(second, "econd!");
+
+        let mut stream =
+            VirtualAudioStream::open_with_options(&path, &master_key, &options(0)).unwrap();
+        stream.seek(SeekFrom::Start(2)).unwrap();
+        let mut first = String::new();
+        stream.read_to_string(&mut first).unwrap();
+        assert_eq!(first, "rst s");
+
+        // 丢弃开头后窗口整体后移
+        stream.skip_front(4);
+        assert_eq!(stream.len(), 3);
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "t s");
+
+        let missing = VirtualAudioStream::open_with_options(&path, &master_key, &options(5));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(missing, Err(StreamError::TrackNotFound(5))));
+    }
+}
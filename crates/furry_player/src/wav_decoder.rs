@@ -0,0 +1,313 @@
+//! 内置 WAV 解码器
+//!
+//! 仅支持标准 PCM（8/16/24/32 bit 整数）与 IEEE float（32/64 bit），
+//! 其余情况由 `AudioDecoder` 回退到 symphonia。`data` 长度为 0/0xFFFFFFFF
+//! 或超出流末尾（流式录制未回填）时按流实际长度处理。
+
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use symphonia::core::codecs::{
+    CodecType, CODEC_TYPE_PCM_F32LE, CODEC_TYPE_PCM_F64LE, CODEC_TYPE_PCM_S16LE,
+    CODEC_TYPE_PCM_S24LE, CODEC_TYPE_PCM_S32LE, CODEC_TYPE_PCM_U8,
+};
+
+use crate::{DecoderError, CHANNELS_RANGE, SAMPLE_RATE_RANGE};
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// 每次 `decode_next` 输出的帧数
+const FRAMES_PER_BLOCK: usize = 4096;
+
+/// 采样编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SampleEncoding {
+    U8,
+    S16,
+    S24,
+    S32,
+    F32,
+    F64,
+}
+
+impl SampleEncoding {
+    fn from_fmt(format_tag: u16, bits: u16) -> Option<Self> {
+        match (format_tag, bits) {
+            (WAVE_FORMAT_PCM, 8) => Some(Self::U8),
+            (WAVE_FORMAT_PCM, 16) => Some(Self::S16),
+            (WAVE_FORMAT_PCM, 24) => Some(Self::S24),
+            (WAVE_FORMAT_PCM, 32) => Some(Self::S32),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => Some(Self::F32),
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => Some(Self::F64),
+            _ => None,
+        }
+    }
+
+    fn bytes(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::S16 => 2,
+            Self::S24 => 3,
+            Self::S32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// 对应的 symphonia codec，保持 `AudioInfo::codec` 与 symphonia 路径一致
+    fn codec(self) -> CodecType {
+        match self {
+            Self::U8 => CODEC_TYPE_PCM_U8,
+            Self::S16 => CODEC_TYPE_PCM_S16LE,
+            Self::S24 => CODEC_TYPE_PCM_S24LE,
+            Self::S32 => CODEC_TYPE_PCM_S32LE,
+            Self::F32 => CODEC_TYPE_PCM_F32LE,
+            Self::F64 => CODEC_TYPE_PCM_F64LE,
+        }
+    }
+
+    fn decode(self, b: &[u8]) -> f32 {
+        match self {
+            Self::U8 => (b[0] as f32 - 128.0) / 128.0,
+            Self::S16 => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            Self::S24 => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+            Self::S32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+            Self::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            Self::F64 => {
+                f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32
+            }
+        }
+    }
+}
+
+/// 内置 WAV 解码器
+pub struct WavDecoder<R: Read + Seek> {
+    inner: R,
+    encoding: SampleEncoding,
+    sample_rate: u32,
+    channels: usize,
+    /// 每帧字节数
+    block_align: usize,
+    /// `data` 负载在流中的起始偏移
+    data_start: u64,
+    /// `data` 负载长度（已按整帧截断）
+    data_len: u64,
+    /// 相对 `data_start` 的读取位置
+    position: u64,
+    buf: Vec<u8>,
+}
+
+impl<R: Read + Seek> WavDecoder<R> {
+    /// 解析 RIFF/WAVE 头部；非标准 PCM/float 时返回错误
+    pub fn open(mut inner: R) -> Result<Self, DecoderError> {
+        let layout = read_layout(&mut inner)?;
+        Ok(Self::with_layout(inner, layout))
+    }
+
+    /// 同 `open`，但失败时将流 seek 回开头并连同错误交还，供调用方回退到 symphonia
+    pub fn try_open(mut inner: R) -> Result<Self, (R, DecoderError)> {
+        match read_layout(&mut inner) {
+            Ok(layout) => Ok(Self::with_layout(inner, layout)),
+            Err(e) => {
+                let _ = inner.seek(SeekFrom::Start(0));
+                Err((inner, e))
+            }
+        }
+    }
+
+    /// 流开头是否为 RIFF/WAVE 头；读取后 seek 回开头
+    pub fn sniff(inner: &mut R) -> bool {
+        let mut magic = [0u8; 12];
+        let found = inner.read_exact(&mut magic).is_ok()
+            && &magic[..4] == b"RIFF"
+            && &magic[8..] == b"WAVE";
+        inner.seek(SeekFrom::Start(0)).is_ok() && found
+    }
+
+    fn with_layout(inner: R, layout: WavLayout) -> Self {
+        Self {
+            inner,
+            encoding: layout.encoding,
+            sample_rate: layout.sample_rate,
+            channels: layout.channels,
+            block_align: layout.block_align,
+            data_start: layout.data_start,
+            data_len: layout.data_len,
+            position: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// 采样率（Hz）
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// 声道数
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// 对应的 symphonia codec 类型
+    pub fn codec(&self) -> CodecType {
+        self.encoding.codec()
+    }
+
+    /// 每个采样的位数
+    pub fn bits_per_sample(&self) -> u32 {
+        self.encoding.bytes() as u32 * 8
+    }
+
+    /// 总帧数
+    pub fn frames(&self) -> u64 {
+        self.data_len / self.block_align as u64
+    }
+
+    /// 下一块采样的起始帧
+    pub fn position_frames(&self) -> u64 {
+        self.position / self.block_align as u64
+    }
+
+    /// 总时长
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames() as f64 / self.sample_rate as f64)
+    }
+
+    /// 读取下一块交错 f32 采样；结束时返回 `None`
+    pub fn decode_next(&mut self) -> Result<Option<Vec<f32>>, DecoderError> {
+        let remaining = self.data_len.saturating_sub(self.position);
+        let want = remaining.min((FRAMES_PER_BLOCK * self.block_align) as u64) as usize;
+        if want == 0 {
+            return Ok(None);
+        }
+
+        self.buf.resize(want, 0);
+        let mut read = 0;
+        while read < want {
+            match self.inner.read(&mut self.buf[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let read = read - read % self.block_align;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.position += read as u64;
+
+        let width = self.encoding.bytes();
+        let samples = self.buf[..read]
+            .chunks_exact(width)
+            .map(|b| self.encoding.decode(b))
+            .collect();
+        Ok(Some(samples))
+    }
+
+    /// 跳转到指定时间（超出末尾时停在末尾）
+    pub fn seek(&mut self, time: Duration) -> Result<(), DecoderError> {
+        let frame = (time.as_secs_f64() * self.sample_rate as f64) as u64;
+        self.position = frame
+            .saturating_mul(self.block_align as u64)
+            .min(self.data_len);
+        self.inner
+            .seek(SeekFrom::Start(self.data_start + self.position))?;
+        Ok(())
+    }
+}
+
+struct WavLayout {
+    encoding: SampleEncoding,
+    sample_rate: u32,
+    channels: usize,
+    block_align: usize,
+    data_start: u64,
+    data_len: u64,
+}
+
+/// 遍历 RIFF chunk，定位 `fmt ` 与 `data`；读取位置停在 `data` 负载开头
+fn read_layout<R: Read + Seek>(inner: &mut R) -> Result<WavLayout, DecoderError> {
+    let stream_len = inner.seek(SeekFrom::End(0))?;
+    inner.seek(SeekFrom::Start(0))?;
+
+    let mut riff = [0u8; 12];
+    inner.read_exact(&mut riff)?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err(DecoderError::Decode("not a RIFF/WAVE stream".into()));
+    }
+
+    let mut fmt: Option<(SampleEncoding, u32, usize, usize)> = None;
+    loop {
+        let mut chunk = [0u8; 8];
+        inner.read_exact(&mut chunk)?;
+        let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        match &chunk[0..4] {
+            b"fmt " => {
+                if !(16..=64).contains(&size) {
+                    return Err(DecoderError::Decode("invalid fmt chunk size".into()));
+                }
+                let mut body = vec![0u8; size as usize];
+                inner.read_exact(&mut body)?;
+                fmt = Some(parse_fmt(&body)?);
+                if size % 2 == 1 {
+                    inner.seek(SeekFrom::Current(1))?;
+                }
+            }
+            b"data" => {
+                let (encoding, sample_rate, channels, block_align) =
+                    fmt.ok_or_else(|| DecoderError::Decode("data chunk before fmt".into()))?;
+                let data_start = inner.stream_position()?;
+                let available = stream_len.saturating_sub(data_start);
+                let data_len = match size {
+                    0 | u32::MAX => available,
+                    size => (size as u64).min(available),
+                };
+                return Ok(WavLayout {
+                    encoding,
+                    sample_rate,
+                    channels,
+                    block_align,
+                    data_start,
+                    data_len: data_len - data_len % block_align as u64,
+                });
+            }
+            _ => {
+                let skip = size as i64 + (size % 2) as i64;
+                inner.seek(SeekFrom::Current(skip))?;
+            }
+        }
+    }
+}
+
+/// 解析 fmt 负载，返回 `(编码, 采样率, 声道数, block_align)`
+fn parse_fmt(body: &[u8]) -> Result<(SampleEncoding, u32, usize, usize), DecoderError> {
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    let mut format_tag = u16_at(0);
+    let channels = u16_at(2) as usize;
+    let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+    let block_align = u16_at(12) as usize;
+    let bits = u16_at(14);
+
+    // WAVE_FORMAT_EXTENSIBLE：实际格式在 SubFormat GUID 的前两个字节
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        if body.len() < 40 {
+            return Err(DecoderError::UnsupportedCodec);
+        }
+        format_tag = u16_at(24);
+    }
+
+    let encoding =
+        SampleEncoding::from_fmt(format_tag, bits).ok_or(DecoderError::UnsupportedCodec)?;
+    if !CHANNELS_RANGE.contains(&channels) {
+        return Err(DecoderError::InvalidChannels(channels));
+    }
+    if !SAMPLE_RATE_RANGE.contains(&sample_rate) {
+        return Err(DecoderError::InvalidSampleRate(sample_rate));
+    }
+    if block_align != channels * encoding.bytes() {
+        return Err(DecoderError::Decode("inconsistent WAV block_align".into()));
+    }
+    Ok((encoding, sample_rate, channels, block_align))
+}